@@ -1,4 +1,4 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
 
 use secp256k1_zkp::{
     global::SECP256K1, rand::thread_rng, EcdsaAdaptorSignature, Keypair,
@@ -19,6 +19,11 @@ use sha2::{Digest, Sha256};
 use rand::rngs::ThreadRng;
 use rand_core::{OsRng, RngCore};
 
+use dlc_fast_prototyping::dlc_controller::ControllerType;
+use dlc_fast_prototyping::dlc_session::{
+    DlcSession, MethodKind, SchemeKind, SessionConfig, SessionOracle,
+};
+
 // -------------------------------------------  Signing and verification -------------------------------------------
 
 fn bench_secp256k1_zkp_sign(c: &mut Criterion) {
@@ -362,6 +367,34 @@ fn bench_xonly_pubkey(c: &mut Criterion) {
     });
 }
 
+// -------------------------------------------  Basis method window sweep -------------------------------------------
+
+const WINDOW_SWEEP_CONTRACT_PATH: &str =
+    "./input_contracts/sample_contracts/reduced_contract_input.json";
+const WINDOW_SWEEP_NB_DIGITS: u8 = 14;
+
+// Whole storage initialization (tables of each window size, anticipation points and adaptor signatures of all 2^14
+// outcomes), to see whether larger tables pay off end to end (`--basis-window` of `run`/`bench` subcommands)
+fn bench_basis_window_sweep(c: &mut Criterion) {
+    let oracles = SessionOracle::new_group(MethodKind::Basis, WINDOW_SWEEP_NB_DIGITS, 1, 0);
+    for window_bits in [1, 2, 4, 8, 12] {
+        let config = SessionConfig::new(SchemeKind::Schnorr, MethodKind::Basis)
+            .with_basis_window(window_bits);
+        c.bench_function(&format!("init_storage_basis_w{}", window_bits), |b| {
+            b.iter_batched(
+                || {
+                    let mut session =
+                        DlcSession::new(config, ControllerType::Offerer, oracles.clone()).unwrap();
+                    session.load_input(WINDOW_SWEEP_CONTRACT_PATH).unwrap();
+                    session
+                },
+                |mut session| black_box(session.init_storage().unwrap()),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default().sample_size(100000);
@@ -371,7 +404,13 @@ criterion_group! {
     // targets = bench_ecdsa_adaptor_signature_clone, bench_schnorr_adaptor_signature_clone
     targets = bench_create_message, bench_create_keypair_from_sk, bench_create_sk_from_keypair, bench_clone_keypair, bench_xonly_pubkey
 }
-criterion_main!(benches);
+// Each iteration sets up whole contract, so only a few samples
+criterion_group! {
+    name = window_benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_basis_window_sweep
+}
+criterion_main!(benches, window_benches);
 
 /*
 We compared the k256 crate and secp256k1-zkp.
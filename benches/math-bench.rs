@@ -1,16 +1,30 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use dlc_fast_prototyping::config::runparams::{MyAdaptorSignatureScheme, MyCryptoUtils};
 use rand::thread_rng;
 use secp256k1_zkp::{Keypair, Secp256k1};
 
 // Import necessary types and functions
-use dlc_fast_prototyping::adaptor_signature_scheme::AdaptorSignatureScheme;
+use dlc_fast_prototyping::adaptor_signature_scheme::{
+    AdaptorSignatureScheme, EcdsaAdaptorSignatureScheme, SchnorrAdaptorSignatureScheme,
+};
 use dlc_fast_prototyping::common::fun; // contains create_cet and create_message
 use dlc_fast_prototyping::common::types::OutcomeU32;
-use dlc_fast_prototyping::crypto_utils::CryptoUtils;
+use dlc_fast_prototyping::config::runparams::MyCryptoUtils;
+use dlc_fast_prototyping::config::DEFAULT_BASE;
+use dlc_fast_prototyping::crypto_utils::{
+    basis_crypto_utils::BasisCryptoUtils, digit_crypto_utils::DigitCryptoUtils,
+    simple_crypto_utils::SimpleCryptoUtils, CryptoUtils,
+};
+use secp256k1_zkp::PublicKey;
+
+#[cfg(feature = "schnorr-fun")]
+use dlc_fast_prototyping::{
+    adaptor_signature_scheme::SchnorrFunAdaptorSignatureScheme,
+    crypto_utils::simple_fun_crypto_utils::SimpleFunCryptoUtils,
+};
 
 const POW2_20SUB1: u32 = 1_048_575; // twenty bits set to 1 in binary
 const POW2_10SUB1: u32 = 1023; // ten bits set to 1 in binary
+const NB_DIGITS: u8 = 20; // enough digits for both benchmarked outcomes
 
 fn bench_create_cet(c: &mut Criterion) {
     let total_collateral = 1000;
@@ -33,51 +47,140 @@ fn bench_create_message(c: &mut Criterion) {
     });
 }
 
-fn bench_compute_anticipation_point(c: &mut Criterion) {
+// Public nonces as announced by oracle for given crypto utils
+fn generate_nonces<CU: CryptoUtils>() -> Vec<PublicKey> {
+    let secp = Secp256k1::new();
+    (0..CU::nb_nonces(NB_DIGITS))
+        .map(|_| secp.generate_keypair(&mut thread_rng()).1)
+        .collect()
+}
+
+fn bench_compute_anticipation_point<CU: CryptoUtils>(c: &mut Criterion, name: &str) {
     let secp = Secp256k1::new();
     let (_, oracle_pub) = secp.generate_keypair(&mut thread_rng());
-    let (_, oracle_nonce) = secp.generate_keypair(&mut thread_rng());
-    let crypto_utils_engine = MyCryptoUtils::new(&oracle_pub, &oracle_nonce);
-    let outcome = OutcomeU32::from(POW2_10SUB1);
-    c.bench_function("compute_anticipation_point", |b| {
+    let oracle_nonces = generate_nonces::<CU>();
+    let crypto_utils_engine = CU::new(&oracle_pub, &oracle_nonces, NB_DIGITS, DEFAULT_BASE);
+    for (bits, value) in [(10, POW2_10SUB1), (20, POW2_20SUB1)] {
+        let outcome = OutcomeU32::from(value);
+        c.bench_function(
+            &format!("compute_anticipation_point_{}_{}", name, bits),
+            |b| {
+                b.iter(|| {
+                    let atp = black_box(crypto_utils_engine.compute_anticipation_point(&outcome))
+                        .unwrap();
+                    black_box(atp)
+                })
+            },
+        );
+    }
+}
+
+// Sweep over all outcomes with 10 digits, points of consecutive outcomes are derived one from another
+fn bench_compute_anticipation_points<CU: CryptoUtils>(c: &mut Criterion, name: &str) {
+    let secp = Secp256k1::new();
+    let (_, oracle_pub) = secp.generate_keypair(&mut thread_rng());
+    let oracle_nonces = generate_nonces::<CU>();
+    let crypto_utils_engine = CU::new(&oracle_pub, &oracle_nonces, NB_DIGITS, DEFAULT_BASE);
+    let outcomes: Vec<OutcomeU32> = (0..=POW2_10SUB1).map(OutcomeU32::from).collect();
+    c.bench_function(&format!("compute_anticipation_points_{}_10", name), |b| {
         b.iter(|| {
-            let atp = black_box(crypto_utils_engine.compute_anticipation_point(&outcome)).unwrap();
-            black_box(atp)
+            let atps = black_box(crypto_utils_engine.compute_anticipation_points(outcomes.iter()))
+                .unwrap();
+            black_box(atps)
         })
     });
 }
 
-fn bench_pre_sign(c: &mut Criterion) {
+fn bench_compute_anticipation_point_simple(c: &mut Criterion) {
+    bench_compute_anticipation_point::<SimpleCryptoUtils>(c, "simple");
+}
+
+fn bench_compute_anticipation_point_basis(c: &mut Criterion) {
+    bench_compute_anticipation_point::<BasisCryptoUtils>(c, "basis");
+    bench_compute_anticipation_points::<BasisCryptoUtils>(c, "basis");
+}
+
+// Table size/time tradeoff of basis method: larger windows need fewer additions per point, but more precomputed points
+fn bench_basis_windows(c: &mut Criterion) {
+    let secp = Secp256k1::new();
+    let (_, oracle_pub) = secp.generate_keypair(&mut thread_rng());
+    let oracle_nonces = generate_nonces::<BasisCryptoUtils>();
+    let outcome = OutcomeU32::from(POW2_20SUB1);
+    for window_bits in [1, 2, 4, 8] {
+        let new_engine = || {
+            BasisCryptoUtils::with_window(
+                &oracle_pub,
+                &oracle_nonces,
+                NB_DIGITS,
+                DEFAULT_BASE,
+                window_bits,
+            )
+        };
+        let crypto_utils_engine = new_engine();
+        c.bench_function(
+            &format!(
+                "precompute_basis_w{}_{}_points",
+                window_bits,
+                crypto_utils_engine.table_size()
+            ),
+            |b| b.iter(|| black_box(new_engine())),
+        );
+        c.bench_function(
+            &format!("compute_anticipation_point_basis_w{}_20", window_bits),
+            |b| {
+                b.iter(|| {
+                    let atp = black_box(crypto_utils_engine.compute_anticipation_point(&outcome))
+                        .unwrap();
+                    black_box(atp)
+                })
+            },
+        );
+    }
+}
+
+fn bench_compute_anticipation_point_digit(c: &mut Criterion) {
+    bench_compute_anticipation_point::<DigitCryptoUtils>(c, "digit");
+    bench_compute_anticipation_points::<DigitCryptoUtils>(c, "digit");
+}
+
+fn bench_pre_sign<ASigS: AdaptorSignatureScheme>(c: &mut Criterion, name: &str) {
     let secp = Secp256k1::new();
     let keypair = Keypair::new(&secp, &mut thread_rng());
     let cet_str = "Alice gets 600 sats and Bob gets 400 sats".to_string();
     let msg = fun::create_message(&cet_str).unwrap();
     // For anticipation point, generate dummy keys:
     let (_, oracle_pub) = secp.generate_keypair(&mut thread_rng());
-    let (_, oracle_nonce) = secp.generate_keypair(&mut thread_rng());
-    let crypto_utils_engine = MyCryptoUtils::new(&oracle_pub, &oracle_nonce);
+    let oracle_nonces = generate_nonces::<MyCryptoUtils>();
+    let crypto_utils_engine =
+        MyCryptoUtils::new(&oracle_pub, &oracle_nonces, NB_DIGITS, DEFAULT_BASE);
     let outcome = OutcomeU32::from(POW2_10SUB1);
     let atp_point = crypto_utils_engine
         .compute_anticipation_point(&outcome)
         .unwrap();
-    c.bench_function("pre_sign", |b| {
+    let mut rng = thread_rng();
+    c.bench_function(&format!("pre_sign_{}", name), |b| {
         b.iter(|| {
-            let _ = MyAdaptorSignatureScheme::pre_sign(&keypair, &msg, &atp_point);
+            let _ = ASigS::pre_sign(&keypair, &msg, &atp_point, &mut rng);
         })
     });
 }
 
-fn bench_verify_adaptor(c: &mut Criterion) {
-    use dlc_fast_prototyping::adaptor_signature_scheme::AdaptorSignatureScheme;
-    use rand::thread_rng;
-    use secp256k1_zkp::Secp256k1;
+fn bench_pre_sign_ecdsa(c: &mut Criterion) {
+    bench_pre_sign::<EcdsaAdaptorSignatureScheme>(c, "ecdsa");
+}
 
+fn bench_pre_sign_schnorr(c: &mut Criterion) {
+    bench_pre_sign::<SchnorrAdaptorSignatureScheme>(c, "schnorr");
+}
+
+fn bench_verify_adaptor<ASigS: AdaptorSignatureScheme>(c: &mut Criterion, name: &str) {
     let secp = Secp256k1::new();
     let keypair = Keypair::new(&secp, &mut thread_rng());
     let (_, oracle_pk) = secp.generate_keypair(&mut thread_rng());
-    let (_, oracle_nonce) = secp.generate_keypair(&mut thread_rng());
+    let oracle_nonces = generate_nonces::<MyCryptoUtils>();
 
-    let crypto_utils_engine = MyCryptoUtils::new(&oracle_pk, &oracle_nonce);
+    let crypto_utils_engine =
+        MyCryptoUtils::new(&oracle_pk, &oracle_nonces, NB_DIGITS, DEFAULT_BASE);
 
     let outcome = OutcomeU32::from(POW2_10SUB1);
     let cet_str = fun::create_cet(400, 1000);
@@ -86,10 +189,10 @@ fn bench_verify_adaptor(c: &mut Criterion) {
         .compute_anticipation_point(&outcome)
         .unwrap();
 
-    let adaptor_sig = MyAdaptorSignatureScheme::pre_sign(&keypair, &msg, &atp_point);
-    c.bench_function("verify_adaptor_sig", |b| {
+    let adaptor_sig = ASigS::pre_sign(&keypair, &msg, &atp_point, &mut thread_rng());
+    c.bench_function(&format!("verify_adaptor_sig_{}", name), |b| {
         b.iter(|| {
-            let _check = black_box(MyAdaptorSignatureScheme::pre_verify(
+            let _check = black_box(ASigS::pre_verify(
                 &keypair.public_key(),
                 &msg,
                 &atp_point,
@@ -99,10 +202,95 @@ fn bench_verify_adaptor(c: &mut Criterion) {
     });
 }
 
+fn bench_verify_adaptor_ecdsa(c: &mut Criterion) {
+    bench_verify_adaptor::<EcdsaAdaptorSignatureScheme>(c, "ecdsa");
+}
+
+fn bench_verify_adaptor_schnorr(c: &mut Criterion) {
+    bench_verify_adaptor::<SchnorrAdaptorSignatureScheme>(c, "schnorr");
+}
+
+// Verification of 2^10 adaptor signatures at once (compare with 2^10 times `verify_adaptor_sig`)
+fn bench_batch_verify_adaptors<ASigS: AdaptorSignatureScheme>(c: &mut Criterion, name: &str) {
+    let secp = Secp256k1::new();
+    let keypair = Keypair::new(&secp, &mut thread_rng());
+    let (_, oracle_pk) = secp.generate_keypair(&mut thread_rng());
+    let oracle_nonces = generate_nonces::<MyCryptoUtils>();
+    let crypto_utils_engine =
+        MyCryptoUtils::new(&oracle_pk, &oracle_nonces, NB_DIGITS, DEFAULT_BASE);
+
+    let msgs: Vec<_> = (0..=POW2_10SUB1)
+        .map(|value| fun::create_message(fun::create_cet(value as u64, 2000)).unwrap())
+        .collect();
+    let atp_points: Vec<PublicKey> = (0..=POW2_10SUB1)
+        .map(|value| {
+            crypto_utils_engine
+                .compute_anticipation_point(&OutcomeU32::from(value))
+                .unwrap()
+        })
+        .collect();
+    let adaptor_sigs: Vec<ASigS::AdaptorSignature> = msgs
+        .iter()
+        .zip(&atp_points)
+        .map(|(msg, atp_point)| ASigS::pre_sign(&keypair, msg, atp_point, &mut thread_rng()))
+        .collect();
+    c.bench_function(&format!("batch_verify_adaptor_sigs_{}_10", name), |b| {
+        b.iter(|| {
+            let _check = black_box(ASigS::batch_pre_verify(
+                &keypair.public_key(),
+                &msgs,
+                &atp_points,
+                &adaptor_sigs,
+            ));
+        })
+    });
+    // Mode pinpointing invalid signatures, with all of them valid it is a single batch verification (same time as above)
+    c.bench_function(&format!("find_invalid_adaptor_sigs_{}_10", name), |b| {
+        b.iter(|| {
+            let invalid = black_box(ASigS::find_invalid_pre_signatures(
+                &keypair.public_key(),
+                &msgs,
+                &atp_points,
+                &adaptor_sigs,
+            ));
+            assert_eq!(invalid, Ok(Vec::new()));
+        })
+    });
+}
+
+fn bench_batch_verify_adaptors_ecdsa(c: &mut Criterion) {
+    bench_batch_verify_adaptors::<EcdsaAdaptorSignatureScheme>(c, "ecdsa");
+}
+
+fn bench_batch_verify_adaptors_schnorr(c: &mut Criterion) {
+    bench_batch_verify_adaptors::<SchnorrAdaptorSignatureScheme>(c, "schnorr");
+}
+
+// Pure-Rust stack, benchmarked only with `schnorr-fun` feature
+#[cfg(feature = "schnorr-fun")]
+fn bench_schnorr_fun(c: &mut Criterion) {
+    bench_compute_anticipation_point::<SimpleFunCryptoUtils>(c, "simple_fun");
+    bench_pre_sign::<SchnorrFunAdaptorSignatureScheme>(c, "schnorr_fun");
+    bench_verify_adaptor::<SchnorrFunAdaptorSignatureScheme>(c, "schnorr_fun");
+}
+
+#[cfg(not(feature = "schnorr-fun"))]
+fn bench_schnorr_fun(_c: &mut Criterion) {}
+
 criterion_group! {
     name = benches;
     config = Criterion::default().sample_size(10000);
-    targets = bench_create_cet, bench_create_message, bench_compute_anticipation_point, bench_pre_sign, bench_verify_adaptor
-    // targets = bench_compute_anticipation_point
+    targets = bench_create_cet, bench_create_message,
+        bench_compute_anticipation_point_simple, bench_compute_anticipation_point_basis,
+        bench_basis_windows, bench_compute_anticipation_point_digit,
+        bench_pre_sign_ecdsa, bench_pre_sign_schnorr,
+        bench_verify_adaptor_ecdsa, bench_verify_adaptor_schnorr, bench_schnorr_fun
+    // targets = bench_compute_anticipation_point_simple, bench_compute_anticipation_point_basis
+}
+// Batches take thousand times longer than single operations, so fewer samples are enough
+criterion_group! {
+    name = batch_benches;
+    config = Criterion::default().sample_size(20);
+    targets = bench_batch_verify_adaptors_ecdsa, bench_batch_verify_adaptors_schnorr
 }
-criterion_main!(benches);
+criterion_main!(benches, batch_benches);
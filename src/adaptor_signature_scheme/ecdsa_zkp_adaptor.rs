@@ -1,15 +1,25 @@
 // src/adaptor_signature_scheme/ecdsa_zkp_adaptor.rs
-use crate::{adaptor_signature_scheme::AdaptorSignatureScheme, common::types};
+use crate::{
+    adaptor_signature_scheme::AdaptorSignatureScheme,
+    common::{error::AdaptorSignatureError, types},
+};
 
 use secp256k1_zkp::{
-    ecdsa, rand::thread_rng, EcdsaAdaptorSignature, Keypair, Message, PublicKey, SecretKey,
-    SECP256K1,
+    ecdsa,
+    rand::{CryptoRng, Rng},
+    EcdsaAdaptorSignature, Keypair, Message, PublicKey, SecretKey, SECP256K1,
 };
 
 pub struct EcdsaAdaptorSignatureScheme;
 
 // We are using SECP global context, but it might be created as a static variable if needed.
 
+// Adaptor signatures are verified one by one (default `batch_pre_verify`), no batching speeds them up. Each DLEQ proof
+// (R = kT, R' = kG) is bound to its own Fiat-Shamir challenge, recomputed from its own commitments, so two double
+// multiplications per signature remain. Only the DLEQ-independent equation s'R' = mG + rP could be combined, but
+// secp256k1-zkp exposes neither a multi-scalar multiplication (a_i s'_i R'_i would still cost one multiplication
+// per signature) nor DLEQ verification without it, so a batch would do more work than `EcdsaAdaptorSignature::verify`.
+
 impl AdaptorSignatureScheme for EcdsaAdaptorSignatureScheme {
     type AdaptorSignature = EcdsaAdaptorSignature;
     type Signature = ecdsa::Signature;
@@ -18,14 +28,14 @@ impl AdaptorSignatureScheme for EcdsaAdaptorSignatureScheme {
         signing_keypair: &Keypair,
         message: &Message,
         anticipation_point: &PublicKey,
+        rng: &mut (impl Rng + CryptoRng),
     ) -> Self::AdaptorSignature {
-        let mut rng = thread_rng();
         EcdsaAdaptorSignature::encrypt_with_rng(
             SECP256K1,
             message,
             &signing_keypair.secret_key(),
             anticipation_point,
-            &mut rng,
+            rng,
         )
     }
 
@@ -34,28 +44,37 @@ impl AdaptorSignatureScheme for EcdsaAdaptorSignatureScheme {
         message: &Message,
         anticipation_point: &PublicKey,
         adaptor_signature: &Self::AdaptorSignature,
-    ) -> bool {
-        adaptor_signature
-            .verify(SECP256K1, message, verification_key, anticipation_point)
-            .is_ok()
+    ) -> Result<(), AdaptorSignatureError> {
+        Ok(adaptor_signature.verify(SECP256K1, message, verification_key, anticipation_point)?)
     }
 
     fn adapt(
         adaptor_signature: &Self::AdaptorSignature,
         attestation: &SecretKey,
-    ) -> Self::Signature {
-        adaptor_signature
-            .decrypt(attestation)
-            .expect("Failed to decrypt signature")
+    ) -> Result<Self::Signature, AdaptorSignatureError> {
+        Ok(adaptor_signature.decrypt(attestation)?)
+    }
+
+    fn sign(signing_keypair: &Keypair, message: &Message) -> Self::Signature {
+        signing_keypair.secret_key().sign_ecdsa(*message)
+    }
+
+    fn verify(
+        verification_key: &PublicKey,
+        message: &Message,
+        signature: &Self::Signature,
+    ) -> bool {
+        SECP256K1
+            .verify_ecdsa(message, signature, verification_key)
+            .is_ok()
     }
 
     fn extract(
         signature: &Self::Signature,
         adaptor_signature: &Self::AdaptorSignature,
         anticipation_point: &PublicKey,
-    ) -> types::Attestation {
-        adaptor_signature
-            .recover(SECP256K1, signature, anticipation_point)
-            .expect("Failed to recover attestation")
+    ) -> Result<types::Attestation, AdaptorSignatureError> {
+        // Recovery itself checks secret against anticipation point
+        Ok(adaptor_signature.recover(SECP256K1, signature, anticipation_point)?)
     }
 }
@@ -1,43 +1,323 @@
 // src/adaptor_signature_scheme/mod.rs
 
-use secp256k1_zkp::{Keypair, Message, PublicKey, SecretKey};
+use secp256k1_zkp::{
+    rand::{CryptoRng, Rng},
+    Keypair, Message, PublicKey, SecretKey,
+};
 
 pub trait AdaptorSignatureScheme {
     type AdaptorSignature: Clone;
     type Signature;
 
+    /// Creates adaptor signature, schemes with randomized nonces draw them from `rng` (see `Seed`).
     fn pre_sign(
         signing_keypair: &Keypair,
         message: &Message,
         anticipation_point: &PublicKey,
+        rng: &mut (impl Rng + CryptoRng),
     ) -> Self::AdaptorSignature;
 
+    /// Checks that adaptor signature is pre-signature of message under verification key for anticipation point.
     fn pre_verify(
         verification_key: &PublicKey,
         message: &Message,
         anticipation_point: &PublicKey,
         adaptor_signature: &Self::AdaptorSignature,
-    ) -> bool;
+    ) -> Result<(), AdaptorSignatureError>;
 
+    /// Verifies adaptor signatures of the same verification key at once (i-th signature of i-th message
+    /// and anticipation point), true only if all of them are valid (false if batch lengths differ).
+    /// Default verifies them one by one.
+    fn batch_pre_verify(
+        verification_key: &PublicKey,
+        messages: &[Message],
+        anticipation_points: &[PublicKey],
+        adaptor_signatures: &[Self::AdaptorSignature],
+    ) -> bool {
+        if check_batch_lengths(messages, anticipation_points, adaptor_signatures).is_err() {
+            return false;
+        }
+        messages
+            .iter()
+            .zip(anticipation_points)
+            .zip(adaptor_signatures)
+            .all(|((message, anticipation_point), adaptor_signature)| {
+                Self::pre_verify(
+                    verification_key,
+                    message,
+                    anticipation_point,
+                    adaptor_signature,
+                )
+                .is_ok()
+            })
+    }
+
+    /// Indices of invalid adaptor signatures with reason (e.g. after `batch_pre_verify` failed), fails if batch
+    /// lengths differ. Default verifies them one by one.
+    fn find_invalid_pre_signatures(
+        verification_key: &PublicKey,
+        messages: &[Message],
+        anticipation_points: &[PublicKey],
+        adaptor_signatures: &[Self::AdaptorSignature],
+    ) -> Result<Vec<(usize, AdaptorSignatureError)>, AdaptorSignatureError> {
+        check_batch_lengths(messages, anticipation_points, adaptor_signatures)?;
+        Ok((0..adaptor_signatures.len())
+            .filter_map(|i| {
+                Self::pre_verify(
+                    verification_key,
+                    &messages[i],
+                    &anticipation_points[i],
+                    &adaptor_signatures[i],
+                )
+                .err()
+                .map(|error| (i, error))
+            })
+            .collect())
+    }
+
+    /// Adapts counterparty's adaptor signature with attestation into ordinary signature.
     fn adapt(
         adaptor_signature: &Self::AdaptorSignature,
         attestation: &SecretKey,
-    ) -> Self::Signature;
+    ) -> Result<Self::Signature, AdaptorSignatureError>;
 
-    #[allow(dead_code)] // delete if used
+    /// Creates an ordinary signature of the scheme (our own half of the finalized transaction).
+    fn sign(signing_keypair: &Keypair, message: &Message) -> Self::Signature;
+
+    /// Verifies an ordinary (or already adapted) signature of the scheme.
+    fn verify(verification_key: &PublicKey, message: &Message, signature: &Self::Signature)
+        -> bool;
+
+    /// Recovers secret of anticipation point from signature adapted from given adaptor signature.
+    /// Fails if signature wasn't adapted from it (recovered secret doesn't unlock anticipation point).
     fn extract(
         signature: &Self::Signature,
         adaptor_signature: &Self::AdaptorSignature,
         anticipation_point: &PublicKey,
-    ) -> types::Attestation;
+    ) -> Result<types::Attestation, AdaptorSignatureError>;
+}
+
+// Each adaptor signature needs its message and anticipation point
+fn check_batch_lengths<T>(
+    messages: &[Message],
+    anticipation_points: &[PublicKey],
+    adaptor_signatures: &[T],
+) -> Result<(), AdaptorSignatureError> {
+    if messages.len() == adaptor_signatures.len()
+        && anticipation_points.len() == adaptor_signatures.len()
+    {
+        Ok(())
+    } else {
+        Err(AdaptorSignatureError::LengthMismatch {
+            messages: messages.len(),
+            anticipation_points: anticipation_points.len(),
+            adaptor_signatures: adaptor_signatures.len(),
+        })
+    }
+}
+
+// Finds invalid signatures of scheme with real batch verification by splitting failing batches in halves,
+// so only halves containing invalid signature are verified again. Batch lengths must be checked before.
+fn bisect_invalid_pre_signatures<ASigS: AdaptorSignatureScheme + ?Sized>(
+    verification_key: &PublicKey,
+    messages: &[Message],
+    anticipation_points: &[PublicKey],
+    adaptor_signatures: &[ASigS::AdaptorSignature],
+) -> Vec<(usize, AdaptorSignatureError)> {
+    if ASigS::batch_pre_verify(
+        verification_key,
+        messages,
+        anticipation_points,
+        adaptor_signatures,
+    ) {
+        return Vec::new();
+    }
+    if adaptor_signatures.len() == 1 {
+        let error = ASigS::pre_verify(
+            verification_key,
+            &messages[0],
+            &anticipation_points[0],
+            &adaptor_signatures[0],
+        )
+        .err()
+        .unwrap_or(AdaptorSignatureError::InvalidPreSignature);
+        return vec![(0, error)];
+    }
+    let mid = adaptor_signatures.len() / 2;
+    let mut invalid = bisect_invalid_pre_signatures::<ASigS>(
+        verification_key,
+        &messages[..mid],
+        &anticipation_points[..mid],
+        &adaptor_signatures[..mid],
+    );
+    invalid.extend(
+        bisect_invalid_pre_signatures::<ASigS>(
+            verification_key,
+            &messages[mid..],
+            &anticipation_points[mid..],
+            &adaptor_signatures[mid..],
+        )
+        .into_iter()
+        .map(|(i, error)| (i + mid, error)),
+    );
+    invalid
 }
 
 mod ecdsa_zkp_adaptor;
+#[cfg(feature = "schnorr-fun")]
+mod schnorr_fun_adaptor;
 mod schnorr_zkp_adaptor;
 
-#[cfg(feature = "ecdsa")]
 pub use ecdsa_zkp_adaptor::EcdsaAdaptorSignatureScheme;
-#[cfg(feature = "schnorr")]
+#[cfg(feature = "schnorr-fun")]
+pub use schnorr_fun_adaptor::SchnorrFunAdaptorSignatureScheme;
 pub use schnorr_zkp_adaptor::SchnorrAdaptorSignatureScheme;
 
-use crate::common::types;
+use crate::common::{error::AdaptorSignatureError, types};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common;
+    use secp256k1_zkp::{rand::thread_rng, SECP256K1};
+
+    const NB_SIGNATURES: usize = 20;
+
+    fn check_batch_verification<ASigS: AdaptorSignatureScheme>() {
+        let mut rng = thread_rng();
+        let keypair = Keypair::new(SECP256K1, &mut rng);
+        let messages: Vec<Message> = (0..NB_SIGNATURES as u64)
+            .map(|i| common::fun::create_message(common::fun::create_cet(i, 1000)).unwrap())
+            .collect();
+        let mut anticipation_points: Vec<PublicKey> = (0..NB_SIGNATURES)
+            .map(|_| SECP256K1.generate_keypair(&mut rng).1)
+            .collect();
+        let adaptor_signatures: Vec<ASigS::AdaptorSignature> = messages
+            .iter()
+            .zip(&anticipation_points)
+            .map(|(message, point)| ASigS::pre_sign(&keypair, message, point, &mut rng))
+            .collect();
+        let verification_key = keypair.public_key();
+
+        assert!(ASigS::batch_pre_verify(
+            &verification_key,
+            &messages,
+            &anticipation_points,
+            &adaptor_signatures
+        ));
+        assert!(ASigS::find_invalid_pre_signatures(
+            &verification_key,
+            &messages,
+            &anticipation_points,
+            &adaptor_signatures
+        )
+        .unwrap()
+        .is_empty());
+        assert!(ASigS::batch_pre_verify(&verification_key, &[], &[], &[]));
+
+        // Batch with a missing anticipation point isn't verified (nor pinpointed) partially
+        let short_points = &anticipation_points[..NB_SIGNATURES - 1];
+        assert!(!ASigS::batch_pre_verify(
+            &verification_key,
+            &messages,
+            short_points,
+            &adaptor_signatures
+        ));
+        assert_eq!(
+            ASigS::find_invalid_pre_signatures(
+                &verification_key,
+                &messages,
+                short_points,
+                &adaptor_signatures
+            ),
+            Err(AdaptorSignatureError::LengthMismatch {
+                messages: NB_SIGNATURES,
+                anticipation_points: NB_SIGNATURES - 1,
+                adaptor_signatures: NB_SIGNATURES,
+            })
+        );
+
+        // Signatures committing to other anticipation points than expected
+        anticipation_points.swap(3, 11);
+        assert!(!ASigS::batch_pre_verify(
+            &verification_key,
+            &messages,
+            &anticipation_points,
+            &adaptor_signatures
+        ));
+        let invalid: Vec<usize> = ASigS::find_invalid_pre_signatures(
+            &verification_key,
+            &messages,
+            &anticipation_points,
+            &adaptor_signatures,
+        )
+        .unwrap()
+        .into_iter()
+        .map(|(index, _)| index)
+        .collect();
+        assert_eq!(invalid, vec![3, 11]);
+
+        // Signatures of other key
+        let other_key = Keypair::new(SECP256K1, &mut rng).public_key();
+        assert!(!ASigS::batch_pre_verify(
+            &other_key,
+            &messages,
+            &anticipation_points,
+            &adaptor_signatures
+        ));
+    }
+
+    // Adapting and extracting with wrong secret or signature fails with error instead of panicking
+    fn check_adapt_extract_errors<ASigS: AdaptorSignatureScheme>() {
+        let mut rng = thread_rng();
+        let keypair = Keypair::new(SECP256K1, &mut rng);
+        let message = common::fun::create_message(common::fun::create_cet(1, 2)).unwrap();
+        let (secret, point) = SECP256K1.generate_keypair(&mut rng);
+        let adaptor_signature = ASigS::pre_sign(&keypair, &message, &point, &mut rng);
+        let verification_key = keypair.public_key();
+
+        let signature = ASigS::adapt(&adaptor_signature, &secret).unwrap();
+        assert!(ASigS::verify(&verification_key, &message, &signature));
+        assert_eq!(
+            ASigS::extract(&signature, &adaptor_signature, &point),
+            Ok(secret)
+        );
+
+        // Ordinary signature isn't adapted from the adaptor signature
+        let signature = ASigS::sign(&keypair, &message);
+        assert!(ASigS::extract(&signature, &adaptor_signature, &point).is_err());
+        // Adaptor signature for other anticipation point
+        let other_point = SECP256K1.generate_keypair(&mut rng).1;
+        assert!(ASigS::pre_verify(
+            &verification_key,
+            &message,
+            &other_point,
+            &adaptor_signature
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_batch_verification_ecdsa() {
+        check_batch_verification::<EcdsaAdaptorSignatureScheme>();
+    }
+
+    #[test]
+    fn test_batch_verification_schnorr() {
+        check_batch_verification::<SchnorrAdaptorSignatureScheme>();
+    }
+
+    #[cfg(feature = "schnorr-fun")]
+    #[test]
+    fn test_batch_verification_schnorr_fun() {
+        check_batch_verification::<SchnorrFunAdaptorSignatureScheme>();
+    }
+
+    #[test]
+    fn test_adapt_extract_errors() {
+        check_adapt_extract_errors::<EcdsaAdaptorSignatureScheme>();
+        check_adapt_extract_errors::<SchnorrAdaptorSignatureScheme>();
+        #[cfg(feature = "schnorr-fun")]
+        check_adapt_extract_errors::<SchnorrFunAdaptorSignatureScheme>();
+    }
+}
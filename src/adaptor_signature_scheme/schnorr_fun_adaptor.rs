@@ -0,0 +1,144 @@
+// src/adaptor_signature_scheme/schnorr_fun_adaptor.rs
+
+use crate::{
+    adaptor_signature_scheme::AdaptorSignatureScheme,
+    common::{error::AdaptorSignatureError, types},
+};
+use schnorr_fun::{
+    adaptor::{Adaptor, EncryptedSign, EncryptedSignature},
+    fun::{marker::*, nonce, KeyPair, Point, Scalar},
+    Schnorr, Signature,
+};
+use secp256k1_zkp::{
+    rand::{CryptoRng, Rng},
+    Keypair, Message, PublicKey, SecretKey,
+};
+use sha2::Sha256;
+use std::{cell::RefCell, sync::LazyLock};
+
+pub struct SchnorrFunAdaptorSignatureScheme;
+
+// INFO: Pure-Rust (`schnorr_fun`) counterpart of `SchnorrAdaptorSignatureScheme`, producing BIP340 signatures as well.
+// Keys, messages and anticipation points still come as `secp256k1_zkp` types (as the rest of the crate uses them),
+// so they are converted at the boundary. Points are converted through uncompressed encoding, which avoids square roots.
+// Nonces are derived deterministically from signing key, anticipation point and message (no RNG needed per signature).
+
+type SchnorrFun = Schnorr<Sha256, nonce::Deterministic<Sha256>>;
+
+static SCHNORR: LazyLock<SchnorrFun> = LazyLock::new(SchnorrFun::default);
+
+thread_local! {
+    // Conversion of signing keypair costs a scalar multiplication, but we pre-sign all CETs with the same key
+    static SIGNING_KEYPAIR: RefCell<Option<([u8; 32], KeyPair<EvenY>)>> = const { RefCell::new(None) };
+}
+
+fn to_fun_keypair(signing_keypair: &Keypair) -> KeyPair<EvenY> {
+    let secret_bytes = signing_keypair.secret_bytes();
+    SIGNING_KEYPAIR.with(|cached| {
+        let mut cached = cached.borrow_mut();
+        match cached.as_ref() {
+            Some((bytes, keypair)) if *bytes == secret_bytes => keypair.clone(),
+            _ => {
+                let secret = Scalar::<Secret, NonZero>::from_bytes(secret_bytes)
+                    .expect("Secret key is a valid non-zero scalar");
+                let keypair = SCHNORR.new_keypair(secret);
+                *cached = Some((secret_bytes, keypair.clone()));
+                keypair
+            }
+        }
+    })
+}
+
+fn to_fun_point(public_key: &PublicKey) -> Point {
+    Point::from_bytes_uncompressed(public_key.serialize_uncompressed())
+        .expect("Public key is a valid point")
+}
+
+// BIP340 verification key, i.e. x-only key with even Y
+fn to_fun_verification_key(verification_key: &PublicKey) -> Point<EvenY> {
+    to_fun_point(verification_key).into_point_with_even_y().0
+}
+
+fn to_fun_message(message: &Message) -> schnorr_fun::Message<'_, Public> {
+    schnorr_fun::Message::raw(message.as_ref())
+}
+
+fn to_secret_key(scalar: &Scalar<impl Secrecy, impl ZeroChoice>) -> SecretKey {
+    SecretKey::from_slice(&scalar.to_bytes()).expect("Scalar is a valid secret key")
+}
+
+impl AdaptorSignatureScheme for SchnorrFunAdaptorSignatureScheme {
+    type AdaptorSignature = EncryptedSignature;
+    type Signature = Signature;
+
+    fn pre_sign(
+        signing_keypair: &Keypair,
+        message: &Message,
+        anticipation_point: &PublicKey,
+        _rng: &mut (impl Rng + CryptoRng), // Nonce is derived deterministically
+    ) -> Self::AdaptorSignature {
+        SCHNORR.encrypted_sign(
+            &to_fun_keypair(signing_keypair),
+            &to_fun_point(anticipation_point),
+            to_fun_message(message),
+        )
+    }
+
+    fn pre_verify(
+        verification_key: &PublicKey,
+        message: &Message,
+        anticipation_point: &PublicKey,
+        adaptor_signature: &Self::AdaptorSignature,
+    ) -> Result<(), AdaptorSignatureError> {
+        SCHNORR
+            .verify_encrypted_signature(
+                &to_fun_verification_key(verification_key),
+                &to_fun_point(anticipation_point),
+                to_fun_message(message),
+                adaptor_signature,
+            )
+            .then_some(())
+            .ok_or(AdaptorSignatureError::InvalidPreSignature)
+    }
+
+    // Attestation (secret key) is always valid non-zero scalar, so adapting can't fail
+    fn adapt(
+        adaptor_signature: &Self::AdaptorSignature,
+        attestation: &SecretKey,
+    ) -> Result<Self::Signature, AdaptorSignatureError> {
+        let decryption_key = Scalar::<Secret, NonZero>::from_bytes(attestation.secret_bytes())
+            .expect("Attestation is a valid non-zero scalar");
+        Ok(SCHNORR.decrypt_signature(decryption_key, adaptor_signature.clone()))
+    }
+
+    fn sign(signing_keypair: &Keypair, message: &Message) -> Self::Signature {
+        SCHNORR.sign(&to_fun_keypair(signing_keypair), to_fun_message(message))
+    }
+
+    fn verify(
+        verification_key: &PublicKey,
+        message: &Message,
+        signature: &Self::Signature,
+    ) -> bool {
+        SCHNORR.verify(
+            &to_fun_verification_key(verification_key),
+            to_fun_message(message),
+            signature,
+        )
+    }
+
+    fn extract(
+        signature: &Self::Signature,
+        adaptor_signature: &Self::AdaptorSignature,
+        anticipation_point: &PublicKey,
+    ) -> Result<types::Attestation, AdaptorSignatureError> {
+        SCHNORR
+            .recover_decryption_key(
+                &to_fun_point(anticipation_point),
+                adaptor_signature,
+                signature,
+            )
+            .map(|decryption_key| to_secret_key(&decryption_key))
+            .ok_or(AdaptorSignatureError::NotAdapted)
+    }
+}
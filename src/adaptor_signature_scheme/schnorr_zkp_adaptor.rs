@@ -1,12 +1,96 @@
 // src/adaptor_signature_scheme/schnorr_zkp_adaptor.rs
 
-use crate::{adaptor_signature_scheme::AdaptorSignatureScheme, common::types};
+use crate::{
+    adaptor_signature_scheme::{
+        bisect_invalid_pre_signatures, check_batch_lengths, AdaptorSignatureScheme,
+    },
+    common::{error::AdaptorSignatureError, types},
+    crypto_utils::secp_utils::{hash_to_scalar, BIP340Hash},
+};
+use bitcoin::hashes::Hash;
 use secp256k1_zkp::{
-    schnorr, Keypair, Message, PublicKey, SchnorrAdaptorPreSignature, SecretKey, SECP256K1,
+    rand::{thread_rng, CryptoRng, Rng, RngCore},
+    schnorr, Keypair, Message, Parity, PublicKey, Scalar, SchnorrAdaptorPreSignature, SecretKey,
+    XOnlyPublicKey, SECP256K1,
 };
 
 pub struct SchnorrAdaptorSignatureScheme;
 
+// INFO: Presignature (R, s') of message m under key P commits to anticipation point T = R - s'G + eP,
+// where e = H(R, P, m) is BIP340 challenge. Batch of presignatures is verified by random linear combination
+// of their equations, sum(a_i (R_i - T_i)) + sum(a_i e_i) P = sum(a_i s'_i) G with random 128-bit a_i,
+// so all multiplications by G and P are shared and invalid presignature passes only with negligible probability.
+
+impl SchnorrAdaptorSignatureScheme {
+    // None if some presignature can't be combined (e.g. it is malformed), such batch is verified one by one
+    fn combined_pre_verify(
+        verification_key: &PublicKey,
+        messages: &[Message],
+        anticipation_points: &[PublicKey],
+        adaptor_signatures: &[SchnorrAdaptorPreSignature],
+    ) -> Option<bool> {
+        let xonly_pk = verification_key.x_only_public_key().0;
+        // Coefficients are verifier's own randomness, unlike nonces of `pre_sign` they are not drawn from run's `Seed`:
+        // counterparty knowing them in advance could craft invalid presignatures whose errors cancel out in the sum
+        let mut rng = thread_rng();
+        let mut lhs_points = Vec::with_capacity(adaptor_signatures.len() + 1);
+        let mut challenge_sum: Option<SecretKey> = None;
+        let mut s_sum: Option<SecretKey> = None;
+        for ((message, anticipation_point), adaptor_signature) in messages
+            .iter()
+            .zip(anticipation_points)
+            .zip(adaptor_signatures)
+        {
+            let serialized = adaptor_signature.serialize();
+            let r = PublicKey::from_slice(&serialized[..33]).ok()?;
+            let s = SecretKey::from_slice(&serialized[33..]).ok()?;
+            let challenge = bip340_challenge(&r, &xonly_pk, message);
+
+            let mut coefficient = [0u8; 32];
+            rng.fill_bytes(&mut coefficient[16..]);
+            let coefficient = Scalar::from(SecretKey::from_slice(&coefficient).ok()?);
+
+            let nonce_point = r.combine(&anticipation_point.negate(SECP256K1)).ok()?;
+            lhs_points.push(nonce_point.mul_tweak(SECP256K1, &coefficient).ok()?);
+            // Challenge is zero only with negligible probability, such batch is verified one by one
+            let challenge = SecretKey::from_slice(&challenge.to_be_bytes()).ok()?;
+            challenge_sum = Some(add_scalars(
+                challenge_sum,
+                challenge.mul_tweak(&coefficient).ok()?,
+            )?);
+            s_sum = Some(add_scalars(s_sum, s.mul_tweak(&coefficient).ok()?)?);
+        }
+        let (Some(challenge_sum), Some(s_sum)) = (challenge_sum, s_sum) else {
+            return Some(true); // Empty batch
+        };
+        let even_pk = xonly_pk.public_key(Parity::Even);
+        lhs_points.push(
+            even_pk
+                .mul_tweak(SECP256K1, &Scalar::from(challenge_sum))
+                .ok()?,
+        );
+        let lhs = PublicKey::combine_keys(&lhs_points.iter().collect::<Vec<_>>()).ok()?;
+        Some(lhs == s_sum.public_key(SECP256K1))
+    }
+}
+
+// BIP340 challenge e = tagged_hash("BIP0340/challenge", R.x || P || m) reduced modulo group order
+fn bip340_challenge(r: &PublicKey, xonly_pk: &XOnlyPublicKey, message: &Message) -> Scalar {
+    let mut buf = Vec::<u8>::with_capacity(96);
+    buf.extend(&r.serialize()[1..]);
+    buf.extend(xonly_pk.serialize());
+    buf.extend(message.as_ref());
+    hash_to_scalar(BIP340Hash::hash(&buf).to_byte_array())
+}
+
+// Sum of scalars, None if it is zero
+fn add_scalars(sum: Option<SecretKey>, scalar: SecretKey) -> Option<SecretKey> {
+    match sum {
+        None => Some(scalar),
+        Some(sum) => sum.add_tweak(&Scalar::from(scalar)).ok(),
+    }
+}
+
 impl AdaptorSignatureScheme for SchnorrAdaptorSignatureScheme {
     type AdaptorSignature = SchnorrAdaptorPreSignature;
     type Signature = schnorr::Signature;
@@ -15,6 +99,7 @@ impl AdaptorSignatureScheme for SchnorrAdaptorSignatureScheme {
         signing_keypair: &Keypair,
         message: &Message,
         anticipation_point: &PublicKey,
+        _rng: &mut (impl Rng + CryptoRng), // Nonce is derived deterministically
     ) -> Self::AdaptorSignature {
         SchnorrAdaptorPreSignature::presign(SECP256K1, message, signing_keypair, anticipation_point)
     }
@@ -24,44 +109,100 @@ impl AdaptorSignatureScheme for SchnorrAdaptorSignatureScheme {
         message: &Message,
         anticipation_point: &PublicKey,
         adaptor_signature: &Self::AdaptorSignature,
-    ) -> bool {
+    ) -> Result<(), AdaptorSignatureError> {
         // Convert `verification_key` into an x-only public key (for BIP-340).
         let xonly_pk = verification_key.x_only_public_key().0;
 
         // Extract the public key of the "anticipation point" from the presignature.
-        let extracted_ap = match adaptor_signature.extract_adaptor(message, &xonly_pk) {
-            Ok(pk) => pk,
-            Err(_) => return false,
-        };
+        let extracted_ap = adaptor_signature.extract_adaptor(message, &xonly_pk)?;
 
         // Compare it to the `anticipation_point` we *expected*.
         // If they match, we've confirmed that the presignature commits
         // to the same anticipation point we provided.
         if extracted_ap != *anticipation_point {
+            return Err(AdaptorSignatureError::InvalidPreSignature);
+        }
+        Ok(())
+    }
+
+    fn batch_pre_verify(
+        verification_key: &PublicKey,
+        messages: &[Message],
+        anticipation_points: &[PublicKey],
+        adaptor_signatures: &[Self::AdaptorSignature],
+    ) -> bool {
+        if check_batch_lengths(messages, anticipation_points, adaptor_signatures).is_err() {
             return false;
         }
+        Self::combined_pre_verify(
+            verification_key,
+            messages,
+            anticipation_points,
+            adaptor_signatures,
+        )
+        .unwrap_or_else(|| {
+            messages
+                .iter()
+                .zip(anticipation_points)
+                .zip(adaptor_signatures)
+                .all(|((message, anticipation_point), adaptor_signature)| {
+                    Self::pre_verify(
+                        verification_key,
+                        message,
+                        anticipation_point,
+                        adaptor_signature,
+                    )
+                    .is_ok()
+                })
+        })
+    }
 
-        // If we get here, the presignature is "valid" in the sense that
-        // it corresponds to the claimed anticipation point for this pubkey/message.
-        true
+    fn find_invalid_pre_signatures(
+        verification_key: &PublicKey,
+        messages: &[Message],
+        anticipation_points: &[PublicKey],
+        adaptor_signatures: &[Self::AdaptorSignature],
+    ) -> Result<Vec<(usize, AdaptorSignatureError)>, AdaptorSignatureError> {
+        check_batch_lengths(messages, anticipation_points, adaptor_signatures)?;
+        Ok(bisect_invalid_pre_signatures::<Self>(
+            verification_key,
+            messages,
+            anticipation_points,
+            adaptor_signatures,
+        ))
     }
 
     fn adapt(
         adaptor_signature: &Self::AdaptorSignature,
         attestation: &SecretKey,
-    ) -> Self::Signature {
-        adaptor_signature
-            .adapt(attestation)
-            .expect("Failed to adapt schnorr signature")
+    ) -> Result<Self::Signature, AdaptorSignatureError> {
+        Ok(adaptor_signature.adapt(attestation)?)
+    }
+
+    fn sign(signing_keypair: &Keypair, message: &Message) -> Self::Signature {
+        signing_keypair.sign_schnorr(*message)
+    }
+
+    fn verify(
+        verification_key: &PublicKey,
+        message: &Message,
+        signature: &Self::Signature,
+    ) -> bool {
+        SECP256K1
+            .verify_schnorr(signature, message, &verification_key.x_only_public_key().0)
+            .is_ok()
     }
 
     fn extract(
         signature: &Self::Signature,
         adaptor_signature: &Self::AdaptorSignature,
-        _anticipation_point: &PublicKey,
-    ) -> types::Attestation {
-        adaptor_signature
-            .extract_secadaptor(signature)
-            .expect("Failed to extract secret adaptor")
+        anticipation_point: &PublicKey,
+    ) -> Result<types::Attestation, AdaptorSignatureError> {
+        // Difference of signatures is some scalar even for unrelated ones, so we check it against anticipation point
+        let secret = adaptor_signature.extract_secadaptor(signature)?;
+        if secret.public_key(SECP256K1) != *anticipation_point {
+            return Err(AdaptorSignatureError::NotAdapted);
+        }
+        Ok(secret)
     }
 }
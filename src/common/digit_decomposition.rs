@@ -0,0 +1,499 @@
+// src/common/digit_decomposition.rs
+
+//! Digit decomposition of outcome ranges for CET compression.
+//! Based on https://github.com/discreetlogcontracts/dlcspecs/blob/master/CETCompression.md
+
+use crate::common::fun;
+use crate::common::types::{AllowedDifference, NumericOutcome, ParsedContract, PayoutT};
+
+/// Prefix of the representation of an outcome in given base (most significant digit first).
+/// Prefix of length `len` with digits `value` covers all outcomes whose first `len` digits equal `value`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigitPrefix {
+    pub value: u64,
+    pub len: u8,
+    pub base: u8,
+}
+
+impl DigitPrefix {
+    /// Returns `index`-th digit of the prefix (0 is the most significant one).
+    pub fn digit(&self, index: u8) -> u8 {
+        debug_assert!(
+            index < self.len,
+            "Digit index must be less than prefix length"
+        );
+        fun::digit_of(self.value, self.len - 1 - index, self.base)
+    }
+
+    // Number of outcomes covered by the prefix (u128, so that the last prefix of 64-digit outcomes doesn't overflow)
+    fn block_size(&self, nb_digits: u8) -> u128 {
+        fun::nb_outcomes(nb_digits - self.len, self.base)
+    }
+
+    /// First outcome covered by the prefix.
+    pub fn first_outcome(&self, nb_digits: u8) -> u64 {
+        (self.value as u128 * self.block_size(nb_digits)) as u64
+    }
+
+    /// Last outcome covered by the prefix.
+    pub fn last_outcome(&self, nb_digits: u8) -> u64 {
+        ((self.value as u128 + 1) * self.block_size(nb_digits) - 1) as u64
+    }
+
+    /// Returns true if the prefix covers given outcome.
+    pub fn contains(&self, outcome: u64, nb_digits: u8) -> bool {
+        outcome as u128 / self.block_size(nb_digits) == self.value as u128
+    }
+
+    /// Splits prefix into all its extensions of length `len` (or returns prefix itself, if it's not shorter).
+    pub fn extensions(&self, len: u8) -> Vec<DigitPrefix> {
+        if self.len >= len {
+            return vec![*self];
+        }
+        let nb_extensions = fun::nb_outcomes(len - self.len, self.base) as u64;
+        let first = self.value * nb_extensions;
+        (first..first + nb_extensions)
+            .map(|value| DigitPrefix {
+                value,
+                len,
+                base: self.base,
+            })
+            .collect()
+    }
+}
+
+/// Digit prefix for each oracle of an oracle subset (in subset order) and payout of their CET.
+pub type OraclePrefixes = (Vec<DigitPrefix>, PayoutT);
+
+/// Covers outcome range [start, end] with minimal set of digit prefixes in given base, sorted by covered outcomes.
+/// Prefixes have at least 1 digit, so even whole outcome range is split into `base` prefixes (oracle always attests
+/// some digit).
+pub fn decompose_range(start: u64, end: u64, nb_digits: u8, base: u8) -> Vec<DigitPrefix> {
+    debug_assert!(start <= end, "Range start must not be greater than its end");
+    let mut prefixes = Vec::new();
+    // u128, so that range can end at the last 64-digit outcome
+    let mut from = start as u128;
+    let until = end as u128 + 1;
+
+    while from < until {
+        // Largest block of base^k outcomes aligned at `from` which still fits into the range
+        let mut block_log = nb_digits - 1;
+        let mut block_size = fun::nb_outcomes(block_log, base);
+        while !from.is_multiple_of(block_size) || block_size > until - from {
+            block_log -= 1;
+            block_size /= base as u128;
+        }
+        prefixes.push(DigitPrefix {
+            value: (from / block_size) as u64,
+            len: nb_digits - block_log,
+            base,
+        });
+        from += block_size;
+    }
+    prefixes
+}
+
+/// Groups consecutive outcomes with equal payout into ranges and covers each range with digit prefixes.
+/// Parsed contract must start at outcome 0 and be in increasing order, and payout of each outcome holds up to the next
+/// outcome in it (or up to the last outcome), so it can contain either all outcomes or only those where payout changes
+/// (see `SparseOutU64Parser`).
+pub fn compress_contract<Out: NumericOutcome>(
+    parsed_contract: &ParsedContract<Out>,
+    nb_digits: u8,
+    base: u8,
+) -> Vec<(DigitPrefix, PayoutT)> {
+    let mut compressed = Vec::new();
+    let mut i = 0;
+    while i < parsed_contract.len() {
+        let (start_outcome, payout) = &parsed_contract[i];
+        let payout = *payout;
+        let mut j = i + 1;
+        while j < parsed_contract.len() && parsed_contract[j].1 == payout {
+            j += 1;
+        }
+        // Range ends right before the next payout (or at the last outcome)
+        let end_outcome = match parsed_contract.get(j) {
+            Some((next_outcome, _)) => next_outcome.to_u64() - 1,
+            None => fun::max_outcome(nb_digits, base),
+        };
+        compressed.extend(
+            decompose_range(start_outcome.to_u64(), end_outcome, nb_digits, base)
+                .into_iter()
+                .map(|prefix| (prefix, payout)),
+        );
+        i = j;
+    }
+    compressed
+}
+
+/// CETs for subsets of `nb_oracles` oracles whose outcomes may differ as allowed by `difference` (dlcspecs
+/// MultiOracle.md). The first (lead) oracle of a subset determines payout, so its prefixes come unchanged from
+/// compressed contract. Let `E = base^max_error_exp` and `S = base^min_support_exp`. Each other oracle gets at most 3
+/// prefixes per lead prefix: the lead prefix itself if it covers at least `E` outcomes (otherwise the aligned block of
+/// `E` outcomes containing it), and the `S` outcomes right before/after that block if the lead prefix starts/ends less
+/// than `S` outcomes from its edge. One CET per combination of them gives at most `3^(nb_oracles - 1)` CETs per lead
+/// prefix. The contract settles whenever other outcomes differ by at most `S` from lead outcome, and never if they
+/// differ by `E + S` or more, unless lead prefix covers more than `E` outcomes (a single payout range), in which case
+/// other outcomes may be anywhere within `S` of it. Without `difference`, all oracles of a CET share the same prefix.
+/// Every combination of outcomes is covered by at most one CET, and CETs are sorted by lead prefix.
+pub fn cover_oracle_outcomes(
+    compressed_contract: &[(DigitPrefix, PayoutT)],
+    nb_digits: u8,
+    base: u8,
+    nb_oracles: usize,
+    difference: Option<AllowedDifference>,
+) -> Vec<OraclePrefixes> {
+    debug_assert!(nb_oracles > 0, "There must be at least one oracle");
+    let difference = match difference {
+        Some(difference) if nb_oracles > 1 => difference,
+        _ => {
+            return compressed_contract
+                .iter()
+                .map(|&(prefix, payout)| (vec![prefix; nb_oracles], payout))
+                .collect()
+        }
+    };
+    debug_assert!(
+        difference.min_support_exp < difference.max_error_exp
+            && difference.max_error_exp < nb_digits,
+        "Allowed difference must satisfy min_support_exp < max_error_exp < nb_digits"
+    );
+
+    let block_len = nb_digits - difference.max_error_exp;
+    let margin_len = nb_digits - difference.min_support_exp;
+    let min_support = difference.min_support(base);
+    let max_outcome = fun::max_outcome(nb_digits, base);
+
+    let mut cets = Vec::new();
+    for &(lead, payout) in compressed_contract {
+        // Lead prefix itself or the aligned block of E outcomes containing it
+        let block = if lead.len <= block_len {
+            lead
+        } else {
+            DigitPrefix {
+                value: lead.value / fun::nb_outcomes(lead.len - block_len, base) as u64,
+                len: block_len,
+                base,
+            }
+        };
+        let (block_first, block_last) = (
+            block.first_outcome(nb_digits),
+            block.last_outcome(nb_digits),
+        );
+        let mut other_prefixes = Vec::with_capacity(3);
+        // Blocks are aligned to S outcomes, so each margin is a single prefix
+        if block_first > 0 && lead.first_outcome(nb_digits) - block_first < min_support {
+            other_prefixes.push(DigitPrefix {
+                value: block_first / min_support - 1,
+                len: margin_len,
+                base,
+            });
+        }
+        other_prefixes.push(block);
+        if block_last < max_outcome && block_last - lead.last_outcome(nb_digits) < min_support {
+            other_prefixes.push(DigitPrefix {
+                value: (block_last / min_support) + 1,
+                len: margin_len,
+                base,
+            });
+        }
+
+        // Cartesian product of other oracles' prefixes
+        let mut combinations = vec![vec![lead]];
+        for _ in 1..nb_oracles {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combination| {
+                    other_prefixes.iter().map(move |&other| {
+                        let mut extended = combination.clone();
+                        extended.push(other);
+                        extended
+                    })
+                })
+                .collect();
+        }
+        cets.extend(combinations.into_iter().map(|prefixes| (prefixes, payout)));
+    }
+    cets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::types::OutcomeU32;
+
+    fn binary(value: u64, len: u8) -> DigitPrefix {
+        DigitPrefix {
+            value,
+            len,
+            base: 2,
+        }
+    }
+
+    // Checks that prefixes cover exactly [start, end] without gaps or overlaps
+    fn assert_exact_cover(prefixes: &[DigitPrefix], start: u64, end: u64, nb_digits: u8) {
+        let mut next = start as u128;
+        for prefix in prefixes {
+            assert_eq!(prefix.first_outcome(nb_digits) as u128, next);
+            next = prefix.last_outcome(nb_digits) as u128 + 1;
+        }
+        assert_eq!(next, end as u128 + 1);
+    }
+
+    #[test]
+    fn test_decompose_range_example() {
+        let prefixes = decompose_range(135677, 138621, 20, 2);
+        assert_exact_cover(&prefixes, 135677, 138621, 20);
+        assert_eq!(prefixes.len(), 12);
+    }
+
+    #[test]
+    fn test_decompose_aligned_and_single() {
+        assert_eq!(decompose_range(8, 15, 5, 2), vec![binary(1, 2)]);
+        assert_eq!(decompose_range(5, 5, 5, 2), vec![binary(5, 5)]);
+        // Whole range still needs at least one digit
+        assert_eq!(
+            decompose_range(0, 31, 5, 2),
+            vec![binary(0, 1), binary(1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_decompose_all_ranges_small() {
+        let nb_digits = 4;
+        for start in 0..16u64 {
+            for end in start..16u64 {
+                let prefixes = decompose_range(start, end, nb_digits, 2);
+                assert_exact_cover(&prefixes, start, end, nb_digits);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decompose_full_u32_range() {
+        let prefixes = decompose_range(1, u32::MAX as u64, 32, 2);
+        assert_exact_cover(&prefixes, 1, u32::MAX as u64, 32);
+        assert_eq!(prefixes.len(), 32);
+    }
+
+    #[test]
+    fn test_decompose_full_u64_range() {
+        let prefixes = decompose_range(1, u64::MAX, 64, 2);
+        assert_exact_cover(&prefixes, 1, u64::MAX, 64);
+        assert_eq!(prefixes.len(), 64);
+        let last = prefixes.last().unwrap();
+        assert!(last.contains(u64::MAX, 64));
+        assert_eq!(last.last_outcome(64), u64::MAX);
+    }
+
+    #[test]
+    fn test_prefix_digits() {
+        let prefix = binary(0b101, 3);
+        assert_eq!(prefix.digit(0), 1);
+        assert_eq!(prefix.digit(1), 0);
+        assert_eq!(prefix.digit(2), 1);
+        assert_eq!(prefix.first_outcome(5), 0b10100);
+        assert_eq!(prefix.last_outcome(5), 0b10111);
+    }
+
+    #[test]
+    fn test_prefix_extensions() {
+        let prefix = binary(0b10, 2);
+        assert!(prefix.contains(0b1011, 4));
+        assert!(!prefix.contains(0b0111, 4));
+        assert_eq!(prefix.extensions(1), vec![prefix]);
+        assert_eq!(
+            prefix.extensions(3),
+            vec![binary(0b100, 3), binary(0b101, 3)]
+        );
+    }
+
+    fn difference(max_error_exp: u8, min_support_exp: u8) -> Option<AllowedDifference> {
+        Some(AllowedDifference {
+            max_error_exp,
+            min_support_exp,
+        })
+    }
+
+    #[test]
+    fn test_cover_oracle_outcomes_bounds() {
+        let nb_digits = 5;
+        let payouts: Vec<PayoutT> = (0..32).map(|i| if i < 12 { 0 } else { i * 10 }).collect();
+        let parsed_contract: ParsedContract<OutcomeU32> = payouts
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| (OutcomeU32::from(i as u32), p))
+            .collect();
+        let compressed = compress_contract(&parsed_contract, nb_digits, 2);
+
+        for exps in [
+            None,
+            difference(1, 0),
+            difference(2, 0),
+            difference(2, 1),
+            difference(3, 1),
+            difference(4, 3),
+        ] {
+            let (max_error, min_support) =
+                exps.map_or((1, 0), |d| (d.max_error(2), d.min_support(2)));
+            let cets = cover_oracle_outcomes(&compressed, nb_digits, 2, 2, exps);
+            for lead in 0..32u64 {
+                for other in 0..32u64 {
+                    let covering: Vec<&OraclePrefixes> = cets
+                        .iter()
+                        .filter(|(prefixes, _)| {
+                            prefixes[0].contains(lead, nb_digits)
+                                && prefixes[1].contains(other, nb_digits)
+                        })
+                        .collect();
+                    let difference = lead.abs_diff(other);
+                    assert!(covering.len() <= 1);
+                    if difference <= min_support {
+                        assert_eq!(covering.len(), 1, "{:?} {} {}", exps, lead, other);
+                    }
+                    if let Some((prefixes, payout)) = covering.first() {
+                        let lead_range = prefixes[0].block_size(nb_digits) as u64;
+                        assert!(difference < max_error + min_support || lead_range > max_error);
+                        assert_eq!(*payout, payouts[lead as usize]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cover_oracle_outcomes_combinations() {
+        let compressed = vec![(binary(1, 2), 7)];
+        // Lead prefix 01 (outcomes 8..=15) covers more than 4 outcomes, so other oracles get it widened by 2 on both
+        // sides: 0011x, 01, 1000x, one CET per combination of 2 other oracles
+        let cets = cover_oracle_outcomes(&compressed, 5, 2, 3, difference(2, 1));
+        assert_eq!(cets.len(), 3 * 3);
+        assert!(cets.iter().all(|(prefixes, payout)| prefixes.len() == 3
+            && prefixes[0] == binary(1, 2)
+            && *payout == 7));
+        assert!(
+            cets.iter()
+                .all(|(prefixes, _)| [binary(3, 4), binary(1, 2), binary(8, 4)]
+                    .contains(&prefixes[2]))
+        );
+        assert_eq!(cover_oracle_outcomes(&compressed, 5, 2, 3, None).len(), 1);
+
+        // Lead prefix 01010 (outcome 10) is in block 010xx, 2 outcomes from its start and 1 from its end, so only the
+        // right margin 0110x is added
+        let cets = cover_oracle_outcomes(&[(binary(10, 5), 7)], 5, 2, 2, difference(2, 1));
+        let others: Vec<DigitPrefix> = cets.iter().map(|(prefixes, _)| prefixes[1]).collect();
+        assert_eq!(others, vec![binary(2, 3), binary(6, 4)]);
+    }
+
+    #[test]
+    fn test_cover_oracle_outcomes_cet_count() {
+        // 20 digits, payout changing every 10000 outcomes in the middle third of outcome range
+        let sparse_contract: ParsedContract<OutcomeU32> = (0..=35u32)
+            .map(|i| {
+                (
+                    OutcomeU32::from(if i == 0 { 0 } else { 340000 + i * 10000 }),
+                    i as PayoutT * 1000,
+                )
+            })
+            .collect();
+        let compressed = compress_contract(&sparse_contract, 20, 2);
+        for max_error_exp in [4, 7, 12] {
+            let exps = difference(max_error_exp, max_error_exp - 1);
+            // Number of lead prefixes doesn't depend on the allowed difference, each gets at most 3 other prefixes
+            let cets = cover_oracle_outcomes(&compressed, 20, 2, 2, exps);
+            assert!(cets.len() <= 3 * compressed.len());
+            let cets = cover_oracle_outcomes(&compressed, 20, 2, 3, exps);
+            assert!(cets.len() <= 3 * 3 * compressed.len());
+        }
+    }
+
+    #[test]
+    fn test_compress_contract() {
+        let payouts = [0, 0, 0, 5, 5, 7, 7, 7];
+        let parsed_contract: ParsedContract<OutcomeU32> = payouts
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| (OutcomeU32::from(i as u32), p))
+            .collect();
+        let compressed = compress_contract(&parsed_contract, 3, 2);
+
+        // [0, 2] -> 00x, 010; [3, 4] -> 011, 100; [5, 7] -> 101, 11x
+        assert_eq!(compressed.len(), 6);
+        for (prefix, payout) in &compressed {
+            for outcome in prefix.first_outcome(3)..=prefix.last_outcome(3) {
+                assert_eq!(payouts[outcome as usize], *payout);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compress_sparse_contract() {
+        // Only outcomes where payout changes, payouts hold up to the next one (as in the dense contract above)
+        let sparse_contract: ParsedContract<OutcomeU32> = [(0, 0), (3, 5), (5, 7)]
+            .iter()
+            .map(|&(outcome, payout)| (OutcomeU32::from(outcome), payout))
+            .collect();
+        let dense_contract: ParsedContract<OutcomeU32> = [0, 0, 0, 5, 5, 7, 7, 7]
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| (OutcomeU32::from(i as u32), p))
+            .collect();
+        assert_eq!(
+            compress_contract(&sparse_contract, 3, 2),
+            compress_contract(&dense_contract, 3, 2)
+        );
+    }
+
+    #[test]
+    fn test_decompose_decimal_range() {
+        // [1234, 5678] of 4 decimal digits -> 1234..1239, 124x..129x, 13xx..19xx, 2xxx..4xxx, 50xx..55xx, 560x..566x,
+        // 5670..5678
+        let prefixes = decompose_range(1234, 5678, 4, 10);
+        assert_eq!(prefixes.len(), 6 + 6 + 7 + 3 + 6 + 7 + 9);
+        let mut next = 1234;
+        for prefix in &prefixes {
+            assert_eq!(prefix.base, 10);
+            assert_eq!(prefix.first_outcome(4), next);
+            next = prefix.last_outcome(4) + 1;
+        }
+        assert_eq!(next, 5679);
+
+        let prefix = DigitPrefix {
+            value: 56,
+            len: 2,
+            base: 10,
+        };
+        assert_eq!((prefix.digit(0), prefix.digit(1)), (5, 6));
+        assert!(prefix.contains(5678, 4) && !prefix.contains(5700, 4));
+        assert_eq!(prefix.extensions(3).len(), 10);
+        // Whole range is split into one prefix per first digit
+        assert_eq!(decompose_range(0, 999, 3, 10).len(), 10);
+    }
+
+    #[test]
+    fn test_compress_and_cover_ternary_contract() {
+        // 3 ternary digits (outcomes 0..=26)
+        let payouts: Vec<PayoutT> = (0..27).map(|i| if i < 10 { 0 } else { 100 }).collect();
+        let parsed_contract: ParsedContract<OutcomeU32> = payouts
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| (OutcomeU32::from(i as u32), p))
+            .collect();
+        let compressed = compress_contract(&parsed_contract, 3, 3);
+        // [0, 9] -> 0xx, 100; [10, 26] -> 101, 102, 11x, 12x, 2xx
+        assert_eq!(compressed.len(), 7);
+        for (prefix, payout) in &compressed {
+            for outcome in prefix.first_outcome(3)..=prefix.last_outcome(3) {
+                assert_eq!(payouts[outcome as usize], *payout);
+            }
+        }
+
+        let cets = cover_oracle_outcomes(&compressed, 3, 3, 2, difference(1, 0));
+        // Lead prefixes are those of compressed contract, each with 1 to 3 prefixes of the other oracle
+        assert!(cets.len() >= compressed.len() && cets.len() <= 3 * compressed.len());
+        for (prefixes, _) in cets {
+            assert!(compressed.iter().any(|(lead, _)| *lead == prefixes[0]));
+            assert_eq!(prefixes[1].base, 3);
+        }
+    }
+}
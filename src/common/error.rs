@@ -1,6 +1,9 @@
 // src/common/error.rs
 
-#[derive(Debug)]
+use crate::common::types;
+use crate::config::{MAX_NB_DIGITS, MAX_ORACLE_SUBSETS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContractError {
     InvalidFirstOutcome,
     MissingIntervals,
@@ -10,7 +13,15 @@ pub enum ContractError {
     InvalidPayout,
     TooHighFeeRate,
     EmptyContract,
-    NbDigitsMismatch,
+    InvalidNbDigits,
+    NonIncreasingOutcomes,
+    InvalidHyperbolaPiece,
+    InvalidRoundingIntervals,
+    InvalidOracleThreshold,
+    TooManyOracleSubsets,
+    InvalidAllowedDifference,
+    InvalidEnumeratedOutcomes,
+    InvalidBase,
 }
 
 impl std::fmt::Display for ContractError {
@@ -19,13 +30,13 @@ impl std::fmt::Display for ContractError {
             ContractError::InvalidFirstOutcome => write!(f, "First interval must start at 0"),
             ContractError::MissingIntervals => write!(f, "No intervals in contract descriptor"),
             ContractError::InvalidIntervalPoints => {
-                write!(f, "Each payout interval must contain exactly 2 points")
+                write!(f, "Each payout interval must contain at least 2 points")
             }
             ContractError::NonContinuousIntervals => {
                 write!(f, "Intervals are not continuous in eventOutcome")
             }
             ContractError::OutcomeRangeMismatch => {
-                write!(f, "Last outcome not matching 2^NB_DIGITS - 1")
+                write!(f, "Last outcome not matching base^nbDigits - 1")
             }
             ContractError::InvalidPayout => write!(
                 f,
@@ -33,11 +44,326 @@ impl std::fmt::Display for ContractError {
             ),
             ContractError::TooHighFeeRate => write!(f, "feeRate too high (> 25 * 250)"),
             ContractError::EmptyContract => write!(f, "Contract fields must be non-empty"),
-            ContractError::NbDigitsMismatch => {
-                write!(f, "nb_digits in input does not match NB_DIGITS constant")
+            ContractError::InvalidNbDigits => {
+                write!(
+                    f,
+                    "nbDigits must be between 1 and {} (fewer for base > 2, so that outcomes fit into u64) and not set for enumerated contracts",
+                    MAX_NB_DIGITS
+                )
+            }
+            ContractError::NonIncreasingOutcomes => {
+                write!(
+                    f,
+                    "eventOutcome of payout points must be strictly increasing"
+                )
             }
+            ContractError::InvalidHyperbolaPiece => write!(
+                f,
+                "Hyperbola piece must have a != 0, finite parameters and 2 payout points lying on it"
+            ),
+            ContractError::InvalidRoundingIntervals => write!(
+                f,
+                "Rounding intervals must begin at 0, be increasing and have roundingMod > 0"
+            ),
+            ContractError::InvalidOracleThreshold => write!(
+                f,
+                "Oracles must have distinct public keys and threshold between 1 and number of oracles"
+            ),
+            ContractError::TooManyOracleSubsets => write!(
+                f,
+                "Number of threshold-sized oracle subsets must not exceed {}",
+                MAX_ORACLE_SUBSETS
+            ),
+            ContractError::InvalidAllowedDifference => write!(
+                f,
+                "maxErrorExp and minSupportExp must be set together with minSupportExp < maxErrorExp < nbDigits"
+            ),
+            ContractError::InvalidEnumeratedOutcomes => write!(
+                f,
+                "Enumerated contract must have at least 2 distinct non-empty outcomes and no intervals"
+            ),
+            ContractError::InvalidBase => write!(
+                f,
+                "base must be at least 2 (and not set for enumerated contracts)"
+            ),
         }
     }
 }
 
 impl std::error::Error for ContractError {}
+
+/// Reason why attestations of oracles can't be used to adapt counterparty's signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttestationError {
+    /// Attested outcome isn't outcome of the contract (or of oracle's event).
+    OutcomeOutOfRange { outcome: u64 },
+    /// Attestations don't unlock anticipation point of attested outcome.
+    InvalidAttestation { outcome: u64 },
+    /// Broadcast CET isn't ours, or our signature in it wasn't adapted from our adaptor signature.
+    NotRecoverable,
+    /// No outcome is attested yet (neither `wait_attestation` nor `recover_attestation` succeeded).
+    NotAttested,
+}
+
+impl std::fmt::Display for AttestationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttestationError::OutcomeOutOfRange { outcome } => {
+                write!(f, "Attested outcome {} is out of contract range", outcome)
+            }
+            AttestationError::InvalidAttestation { outcome } => write!(
+                f,
+                "Oracle attestations don't match anticipation point of outcome {}",
+                outcome
+            ),
+            AttestationError::NotRecoverable => write!(
+                f,
+                "Attestation can't be recovered from broadcast CET, it isn't adapted from any of our adaptor signatures"
+            ),
+            AttestationError::NotAttested => write!(
+                f,
+                "No outcome is attested yet, attestation must be awaited or recovered first"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AttestationError {}
+
+/// Reason why two attestations don't prove that oracle equivocated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EquivocationError {
+    /// Attestations don't unlock anticipation point of their outcome under oracle's announcement.
+    InvalidAttestation { outcome: u64 },
+    /// Both attestations are of the same outcome.
+    SameOutcome { outcome: u64 },
+    /// Attestation method doesn't reveal private key (e.g. attestation sums attestations under several nonces).
+    KeyNotRevealed,
+}
+
+impl std::fmt::Display for EquivocationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EquivocationError::InvalidAttestation { outcome } => write!(
+                f,
+                "Attestation of outcome {} doesn't match oracle's announcement",
+                outcome
+            ),
+            EquivocationError::SameOutcome { outcome } => {
+                write!(f, "Both attestations are of the same outcome {}", outcome)
+            }
+            EquivocationError::KeyNotRevealed => write!(
+                f,
+                "Conflicting attestations don't reveal oracle's private key with this attestation method"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EquivocationError {}
+
+/// Failure of computing anticipation points or attestations of outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CryptoUtilsError {
+    /// Underlying secp256k1 library rejected a point or scalar (e.g. points summing to infinity).
+    Secp(secp256k1_zkp::Error),
+    /// Method attests outcome digits, but the outcome is enumerated, so it has none.
+    OutcomeHasNoDigits,
+    /// Outcome doesn't fit into number of digits announced by the oracle.
+    OutcomeOutOfRange,
+    /// Joint anticipation point is computed for no oracles.
+    NoOracles,
+}
+
+impl From<secp256k1_zkp::Error> for CryptoUtilsError {
+    fn from(e: secp256k1_zkp::Error) -> Self {
+        CryptoUtilsError::Secp(e)
+    }
+}
+
+impl From<secp256k1_zkp::UpstreamError> for CryptoUtilsError {
+    fn from(e: secp256k1_zkp::UpstreamError) -> Self {
+        CryptoUtilsError::Secp(e.into())
+    }
+}
+
+impl std::fmt::Display for CryptoUtilsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CryptoUtilsError::Secp(e) => write!(f, "secp256k1 error: {}", e),
+            CryptoUtilsError::OutcomeHasNoDigits => {
+                write!(f, "Enumerated outcome has no digits to attest")
+            }
+            CryptoUtilsError::OutcomeOutOfRange => {
+                write!(f, "Outcome doesn't fit into announced number of digits")
+            }
+            CryptoUtilsError::NoOracles => {
+                write!(f, "Joint anticipation point needs at least one oracle")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CryptoUtilsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CryptoUtilsError::Secp(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Failure of adaptor signature scheme operation, e.g. on malformed adaptor signature, signature or attestation from peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdaptorSignatureError {
+    /// Underlying secp256k1 library rejected adaptor signature, signature or attestation.
+    Secp(secp256k1_zkp::Error),
+    /// Adaptor signature is well-formed, but it isn't pre-signature of the message for given key and anticipation point.
+    InvalidPreSignature,
+    /// Signature wasn't adapted from adaptor signature, so it doesn't reveal secret of anticipation point.
+    NotAdapted,
+    /// Counterparty's adaptor signature of the CET isn't saved (adaptors weren't exchanged yet).
+    MissingAdaptor,
+    /// Batch has different numbers of messages, anticipation points and adaptor signatures.
+    LengthMismatch {
+        messages: usize,
+        anticipation_points: usize,
+        adaptor_signatures: usize,
+    },
+}
+
+impl From<secp256k1_zkp::Error> for AdaptorSignatureError {
+    fn from(e: secp256k1_zkp::Error) -> Self {
+        AdaptorSignatureError::Secp(e)
+    }
+}
+
+impl From<secp256k1_zkp::UpstreamError> for AdaptorSignatureError {
+    fn from(e: secp256k1_zkp::UpstreamError) -> Self {
+        AdaptorSignatureError::Secp(e.into())
+    }
+}
+
+impl std::fmt::Display for AdaptorSignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdaptorSignatureError::Secp(e) => write!(f, "secp256k1 error: {}", e),
+            AdaptorSignatureError::InvalidPreSignature => write!(
+                f,
+                "Adaptor signature doesn't commit to expected message, key and anticipation point"
+            ),
+            AdaptorSignatureError::NotAdapted => {
+                write!(f, "Signature wasn't adapted from the adaptor signature")
+            }
+            AdaptorSignatureError::MissingAdaptor => {
+                write!(f, "Counterparty adaptor signature of the CET is missing")
+            }
+            AdaptorSignatureError::LengthMismatch {
+                messages,
+                anticipation_points,
+                adaptor_signatures,
+            } => write!(
+                f,
+                "Batch has {} messages, {} anticipation points and {} adaptor signatures",
+                messages, anticipation_points, adaptor_signatures
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AdaptorSignatureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AdaptorSignatureError::Secp(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Reason why counterparty adaptors didn't pass verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdaptorVerificationFailure {
+    /// Counterparty sent different number of adaptors than is number of our CETs.
+    LengthMismatch { expected: usize, received: usize },
+    /// Adaptor at given index (in order of shared adaptors) isn't valid pre-signature of its CET.
+    InvalidPreSignature {
+        index: usize,
+        cet: types::Cet,
+        error: AdaptorSignatureError,
+    },
+}
+
+impl std::fmt::Display for AdaptorVerificationFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdaptorVerificationFailure::LengthMismatch { expected, received } => write!(
+                f,
+                "Expected {} counterparty adaptors, received {}",
+                expected, received
+            ),
+            AdaptorVerificationFailure::InvalidPreSignature { index, cet, error } => write!(
+                f,
+                "Counterparty adaptor #{} is not a valid pre-signature of CET \"{}\" ({})",
+                index, cet, error
+            ),
+        }
+    }
+}
+
+/// Result of counterparty adaptors verification, lists all failures (none if every adaptor is valid).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerificationReport {
+    pub failures: Vec<AdaptorVerificationFailure>,
+}
+
+impl VerificationReport {
+    pub fn length_mismatch(expected: usize, received: usize) -> Self {
+        Self {
+            failures: vec![AdaptorVerificationFailure::LengthMismatch { expected, received }],
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Indices of adaptors which are not valid pre-signatures of their CETs.
+    pub fn invalid_indices(&self) -> Vec<usize> {
+        self.failures
+            .iter()
+            .filter_map(|failure| match failure {
+                AdaptorVerificationFailure::InvalidPreSignature { index, .. } => Some(*index),
+                AdaptorVerificationFailure::LengthMismatch { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Adds failures of report of adaptors starting at index `offset` (e.g. of next storage).
+    pub fn append(&mut self, other: VerificationReport, offset: usize) {
+        self.failures
+            .extend(other.failures.into_iter().map(|failure| match failure {
+                AdaptorVerificationFailure::InvalidPreSignature { index, cet, error } => {
+                    AdaptorVerificationFailure::InvalidPreSignature {
+                        index: index + offset,
+                        cet,
+                        error,
+                    }
+                }
+                length_mismatch => length_mismatch,
+            }));
+    }
+}
+
+impl std::fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_valid() {
+            return write!(f, "All counterparty adaptors are valid");
+        }
+        let failures: Vec<String> = self
+            .failures
+            .iter()
+            .map(|failure| failure.to_string())
+            .collect();
+        write!(f, "{}", failures.join("\n"))
+    }
+}
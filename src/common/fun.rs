@@ -2,8 +2,10 @@
 
 use secp256k1_zkp::Message;
 use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 
 use super::types;
+use crate::adaptor_signature_scheme::AdaptorSignatureScheme;
 
 // Based on payout and total collateral (from contract descriptor), create a string that represents the CET
 // INFO: From https://github.com/discreetlogcontracts/dlcspecs/blob/master/PayoutCurve.md, 'payout' represents satoshis for offerer
@@ -16,9 +18,130 @@ pub fn create_cet(payout: types::PayoutT, total_collateral: types::PayoutT) -> S
     )
 }
 
+// Maximum outcome representable with `nb_digits` digits in given base (base^nb_digits - 1)
+pub fn max_outcome(nb_digits: u8, base: u8) -> u64 {
+    debug_assert!(
+        nb_digits > 0 && nb_digits <= max_nb_digits(base),
+        "Outcomes with nb_digits digits must fit into u64"
+    );
+    (nb_outcomes(nb_digits, base) - 1) as u64
+}
+
+// Number of outcomes representable with `nb_digits` digits in given base (base^nb_digits, which doesn't fit into u64
+// for 64 binary digits)
+pub fn nb_outcomes(nb_digits: u8, base: u8) -> u128 {
+    (base as u128).pow(nb_digits as u32)
+}
+
+// Largest number of digits in given base, whose outcomes still fit into u64 (64 for binary digits)
+pub fn max_nb_digits(base: u8) -> u8 {
+    debug_assert!(base >= 2, "Base must be at least 2");
+    let mut nb_digits = 0;
+    while nb_outcomes(nb_digits + 1, base) <= 1u128 << 64 {
+        nb_digits += 1;
+    }
+    nb_digits
+}
+
+// Digit of value at given position (0 is the least significant one) in given base, digits beyond value are 0
+pub fn digit_of(value: u64, position: u8, base: u8) -> u8 {
+    match (base as u128).checked_pow(position as u32) {
+        Some(weight) => ((value as u128 / weight) % base as u128) as u8,
+        None => 0,
+    }
+}
+
+// Rounds payout to nearest multiple of `rounding_mod` (halves rounded up), never exceeding `max_payout`
+pub fn round_payout(
+    payout: types::PayoutT,
+    rounding_mod: u64,
+    max_payout: types::PayoutT,
+) -> types::PayoutT {
+    let remainder = payout % rounding_mod;
+    let rounded = if 2 * remainder >= rounding_mod {
+        payout - remainder + rounding_mod
+    } else {
+        payout - remainder
+    };
+    rounded.min(max_payout)
+}
+
+// Number of distinct payouts in parsed contract, i.e. how many different CETs are needed to cover all outcomes
+pub fn count_unique_payouts<O>(parsed_contract: &types::ParsedContract<O>) -> usize {
+    parsed_contract
+        .iter()
+        .map(|(_, payout)| *payout)
+        .collect::<HashSet<types::PayoutT>>()
+        .len()
+}
+
+// Number of `threshold`-sized subsets of oracles, C(nb_oracles, threshold), None if it overflows usize.
+pub fn nb_oracle_subsets(nb_oracles: usize, threshold: usize) -> Option<usize> {
+    if threshold > nb_oracles {
+        return Some(0);
+    }
+    // Each partial product C(nb_oracles, i) * (nb_oracles - i) is divisible by i + 1
+    (0..threshold.min(nb_oracles - threshold)).try_fold(1usize, |count, i| {
+        Some(count.checked_mul(nb_oracles - i)? / (i + 1))
+    })
+}
+
+// All `threshold`-sized subsets of oracles (as sorted oracle indices), in lexicographic order.
+// Each subset gets its own anticipation points, so that any `threshold` oracles can settle the contract.
+// Contract validation caps their number at `MAX_ORACLE_SUBSETS`.
+pub fn oracle_subsets(nb_oracles: usize, threshold: usize) -> Vec<Vec<usize>> {
+    assert!(
+        threshold > 0 && threshold <= nb_oracles,
+        "threshold must be in range 1..=nb_oracles"
+    );
+    let mut subsets = Vec::new();
+    let mut subset: Vec<usize> = (0..threshold).collect();
+    loop {
+        subsets.push(subset.clone());
+        // Find rightmost index which can still be increased
+        let Some(i) = (0..threshold)
+            .rev()
+            .find(|&i| subset[i] < nb_oracles - threshold + i)
+        else {
+            return subsets;
+        };
+        subset[i] += 1;
+        for j in i + 1..threshold {
+            subset[j] = subset[j - 1] + 1;
+        }
+    }
+}
+
+// Index of first subset whose oracles all attested to the same outcome, if there is any
+pub fn find_agreeing_subset<O: PartialEq>(
+    attested_outcomes: &[O],
+    subsets: &[Vec<usize>],
+) -> Option<usize> {
+    subsets.iter().position(|subset| {
+        subset
+            .iter()
+            .all(|&i| attested_outcomes[i] == attested_outcomes[subset[0]])
+    })
+}
+
 // Create a message from the CET (might be of any type representable as bytes)
 pub fn create_message<T: AsRef<[u8]>>(cet: T) -> Result<Message, secp256k1_zkp::UpstreamError> {
     let hash = Sha256::digest(cet.as_ref());
     let hashed_msg: [u8; 32] = hash.into();
     Message::from_digest_slice(&hashed_msg)
 }
+
+// Validates (locally) final transaction which would be broadcasted to blockchain and by doing so it
+// simulates blockchain acceptance or rejection of final tx.
+pub fn finalized_tx_valid<ASigS: AdaptorSignatureScheme>(
+    finalized_tx: &types::FinalizedTx<ASigS::Signature>,
+    multisig: &types::MultisigFundAddress,
+) -> bool {
+    let msg = match create_message(finalized_tx.payload.as_bytes()) {
+        Ok(m) => m,
+        Err(_) => return false,
+    };
+
+    ASigS::verify(&multisig.offerer_pubkey, &msg, &finalized_tx.offerer_sig)
+        && ASigS::verify(&multisig.accepter_pubkey, &msg, &finalized_tx.accepter_sig)
+}
@@ -1,6 +1,8 @@
 // src/common/mod.rs
+pub mod digit_decomposition;
 pub mod error;
 pub mod fun;
+pub mod seed;
 pub mod types;
 
 pub use types::*;
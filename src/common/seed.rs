@@ -0,0 +1,91 @@
+// src/common/seed.rs
+
+use rand::{rngs::StdRng, SeedableRng};
+use sha2::{Digest, Sha256};
+use std::{fmt, io::Error, str::FromStr};
+
+// INFO: All randomness of a run (controller keys, oracle keys, nonces and outcomes, ECDSA pre-signature nonces) is
+// derived from one 64-bit seed, so that a failing run or a benchmark outlier can be replayed exactly with the same seed.
+// Each component draws from its own stream, labelled by its role (e.g. "offerer", "oracle/1"), so streams don't depend
+// on order in which components are created nor on number of threads (pre-signature nonces are labelled by what they sign).
+// ATTENTION: Everyone knowing the seed knows all keys, so seeded runs are meant for testing and benchmarking only.
+// Coefficients of batch verification are not seeded, they must stay unpredictable to the signer.
+
+/// Seed of all randomness of a run (random unless given, e.g. by `--seed`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seed(u64);
+
+impl Seed {
+    pub fn new(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// Random seed, print it (see `Display`) to be able to replay the run.
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    /// Seed of independent stream with given label.
+    pub fn derive(&self, label: &str) -> Self {
+        let hash = self.hash(label.as_bytes());
+        Self(u64::from_le_bytes(hash[..8].try_into().unwrap()))
+    }
+
+    /// Random number generator of stream with given label.
+    pub fn rng(&self, label: impl AsRef<[u8]>) -> StdRng {
+        StdRng::from_seed(self.hash(label.as_ref()))
+    }
+
+    fn hash(&self, label: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.0.to_le_bytes());
+        hasher.update(label);
+        hasher.finalize().into()
+    }
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Seed {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self).map_err(|e| {
+            Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid seed '{}': {}", s, e),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_seed_streams() {
+        let seed = Seed::new(42);
+        let draw = |seed: Seed, label: &str| seed.rng(label).gen::<[u8; 32]>();
+
+        // Same seed and label replay the same stream
+        assert_eq!(draw(seed, "offerer"), draw(Seed::new(42), "offerer"));
+        assert_eq!(seed.derive("oracle/0"), Seed::new(42).derive("oracle/0"));
+        // Different labels or seeds give independent streams
+        assert_ne!(draw(seed, "offerer"), draw(seed, "accepter"));
+        assert_ne!(draw(seed, "offerer"), draw(Seed::new(43), "offerer"));
+        assert_ne!(seed.derive("oracle/0"), seed.derive("oracle/1"));
+
+        assert_eq!("42".parse::<Seed>().unwrap(), seed);
+        assert!("forty-two".parse::<Seed>().is_err());
+    }
+}
@@ -3,9 +3,12 @@
 use secp256k1_zkp;
 use secp256k1_zkp::{PublicKey, SecretKey};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::common::error::ContractError;
-use crate::config::NB_DIGITS;
+use crate::common::fun;
+use crate::config::{DEFAULT_BASE, MAX_NB_DIGITS, MAX_ORACLE_SUBSETS};
+use crate::parser::payout_curve::{PayoutCurve, PolynomialPiece};
 
 /// -- Aliases for outcome types --
 pub type AnticipationPoint = PublicKey;
@@ -14,8 +17,8 @@ pub type Attestation = SecretKey;
 // Other
 pub type Cet = String; // Contract Execution Transaction (esentially not signed Tx)
 pub type PayoutT = u64; // Integer type for payout values (in satoshis). If will be using Bitcoin API, then Amount will be correct type
-pub type ParsedContract<O: Outcome> = Vec<(O, PayoutT)>; // Compiler checker says this might be implemented in future versions, so we leave it like this
-                                                         // It could be made as Vec<PayoutT> if we would consider just OutcomeU32, but we made it more robust, so it could be implemented also with different types of outcomes (eg. OutcomeBinStr).
+pub type ParsedContract<O> = Vec<(O, PayoutT)>; // O: Outcome (bounds on type aliases are not enforced by compiler, so we leave it unbounded)
+                                                // It could be made as Vec<PayoutT> if we would consider just OutcomeU32, but we made it more robust, so it could be implemented also with different types of outcomes (eg. OutcomeBinStr).
 
 /// The final Bitcoin transaction or any other on-chain transaction type
 /// that will be broadcasted after finalization.
@@ -51,13 +54,17 @@ impl MultisigFundAddress {
 
 // ------------------ Outcome trait and implementations ------------------
 pub trait Outcome {
-    type ValueType;
+    type ValueType: ToString;
 
     /// Return the value of this Outcome.
     fn get_value(&self) -> Self::ValueType;
 
-    /// Return bit at the given position.
-    fn get_bit(&self, position: u8) -> bool;
+    /// Return bit at the given position, None if outcome has no digits (enumerated outcome).
+    fn get_bit(&self, position: u8) -> Option<bool>;
+
+    /// Return digit at the given position (0 is the least significant one) of outcome written in given base,
+    /// None if outcome has no digits (enumerated outcome).
+    fn get_digit(&self, position: u8, base: u8) -> Option<u8>;
 
     /// Return true if the outcome is zero.
     fn is_zero(&self) -> bool;
@@ -66,6 +73,16 @@ pub trait Outcome {
     fn serialize(&self) -> Vec<u8>;
 }
 
+/// Outcome of a numeric event, convertible from and to its integer value, so that controllers, storages and
+/// oracles work with any outcome representation (parser always produces integer outcomes).
+pub trait NumericOutcome: Outcome + Clone + PartialEq {
+    /// Creates outcome with given value, represented with `nb_digits` binary digits.
+    fn from_u64(value: u64, nb_digits: u8) -> Self;
+
+    /// Return integer value of this Outcome.
+    fn to_u64(&self) -> u64;
+}
+
 // A simple integer-based outcome.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct OutcomeU32 {
@@ -79,9 +96,13 @@ impl Outcome for OutcomeU32 {
         self.value
     }
 
-    fn get_bit(&self, position: u8) -> bool {
-        debug_assert!(position < 32, "Position must be less than 32");
-        (self.value >> position) & 1 == 1
+    // Digits beyond 32 bits are leading zeros
+    fn get_bit(&self, position: u8) -> Option<bool> {
+        Some(position < 32 && (self.value >> position) & 1 == 1)
+    }
+
+    fn get_digit(&self, position: u8, base: u8) -> Option<u8> {
+        Some(fun::digit_of(self.value as u64, position, base))
     }
 
     fn is_zero(&self) -> bool {
@@ -105,7 +126,72 @@ impl From<OutcomeU32> for u32 {
     }
 }
 
-// A string-based binary outcome
+impl NumericOutcome for OutcomeU32 {
+    fn from_u64(value: u64, _nb_digits: u8) -> Self {
+        debug_assert!(value <= u32::MAX as u64, "Value must fit into u32");
+        Self {
+            value: value as u32,
+        }
+    }
+
+    fn to_u64(&self) -> u64 {
+        self.value as u64
+    }
+}
+
+// A 64-bit integer-based outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutcomeU64 {
+    value: u64,
+}
+
+impl Outcome for OutcomeU64 {
+    type ValueType = u64;
+
+    fn get_value(&self) -> u64 {
+        self.value
+    }
+
+    fn get_bit(&self, position: u8) -> Option<bool> {
+        Some(position < 64 && (self.value >> position) & 1 == 1)
+    }
+
+    fn get_digit(&self, position: u8, base: u8) -> Option<u8> {
+        Some(fun::digit_of(self.value, position, base))
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value == 0
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        self.value.to_le_bytes().to_vec()
+    }
+}
+
+impl From<u64> for OutcomeU64 {
+    fn from(value: u64) -> Self {
+        Self { value }
+    }
+}
+
+impl From<OutcomeU64> for u64 {
+    fn from(outcome: OutcomeU64) -> u64 {
+        outcome.value
+    }
+}
+
+impl NumericOutcome for OutcomeU64 {
+    fn from_u64(value: u64, _nb_digits: u8) -> Self {
+        Self { value }
+    }
+
+    fn to_u64(&self) -> u64 {
+        self.value
+    }
+}
+
+// A string-based binary outcome (most significant digit first)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OutcomeBinStr {
     value: String,
@@ -118,12 +204,21 @@ impl Outcome for OutcomeBinStr {
         self.value.clone()
     }
 
-    fn get_bit(&self, position: u8) -> bool {
-        debug_assert!(
-            position < self.value.len() as u8,
-            "Position must be less than the length of the string"
-        );
-        self.value.chars().nth(position as usize) == Some('1')
+    // Position is counted from the least significant (last) digit, as for integer outcomes.
+    // Digits beyond the length of the string are leading zeros.
+    fn get_bit(&self, position: u8) -> Option<bool> {
+        let len = self.value.len();
+        Some(
+            (position as usize) < len && self.value.as_bytes()[len - 1 - position as usize] == b'1',
+        )
+    }
+
+    // Binary digits are read directly, other bases from integer value
+    fn get_digit(&self, position: u8, base: u8) -> Option<u8> {
+        match base {
+            2 => self.get_bit(position).map(u8::from),
+            _ => Some(fun::digit_of(self.to_u64(), position, base)),
+        }
     }
 
     fn is_zero(&self) -> bool {
@@ -150,6 +245,65 @@ impl From<OutcomeBinStr> for String {
     }
 }
 
+impl NumericOutcome for OutcomeBinStr {
+    fn from_u64(value: u64, nb_digits: u8) -> Self {
+        Self {
+            value: format!("{:0width$b}", value, width = nb_digits as usize),
+        }
+    }
+
+    fn to_u64(&self) -> u64 {
+        debug_assert!(self.value.len() <= 64, "Value must fit into u64");
+        self.value
+            .bytes()
+            .fold(0, |value, digit| (value << 1) | (digit == b'1') as u64)
+    }
+}
+
+// An enumerated (non-numeric) outcome, e.g. "team_a_wins". Index is its position in the list of event outcomes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutcomeEnum {
+    index: u32,
+    label: String,
+}
+
+impl OutcomeEnum {
+    pub fn new(index: u32, label: String) -> Self {
+        Self { index, label }
+    }
+
+    /// Position of the outcome in the list of event outcomes.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+impl Outcome for OutcomeEnum {
+    type ValueType = String;
+
+    fn get_value(&self) -> String {
+        self.label.clone()
+    }
+
+    // Enumerated outcome is attested as a whole, so methods decomposing outcome into digits reject it
+    fn get_bit(&self, _position: u8) -> Option<bool> {
+        None
+    }
+
+    fn get_digit(&self, _position: u8, _base: u8) -> Option<u8> {
+        None
+    }
+
+    fn is_zero(&self) -> bool {
+        false
+    }
+
+    // Hashed label, so anticipation point doesn't depend on length of the label
+    fn serialize(&self) -> Vec<u8> {
+        Sha256::digest(self.label.as_bytes()).to_vec()
+    }
+}
+
 // ------------------ ContractInput and related structs ------------------
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -163,9 +317,17 @@ pub struct ContractInput {
 
 impl ContractInput {
     pub fn validate(&self) -> Result<(), ContractError> {
+        first_error(self.validate_all())
+    }
+
+    /// Runs all validation rules and returns every broken rule (each error kind reported once),
+    /// in the same order as `validate` would encounter them.
+    pub fn validate_all(&self) -> Vec<ContractError> {
+        let mut errors = Vec::new();
+
         // 10: Input contract must be non-empty
         if self.offer_collateral == 0 || self.accept_collateral == 0 || self.fee_rate == 0 {
-            return Err(ContractError::EmptyContract);
+            errors.push(ContractError::EmptyContract);
         }
 
         // 6: Collateral must be valid unsigned integer (secured by u64)
@@ -175,14 +337,28 @@ impl ContractInput {
 
         // 7: If feeRate > 25 * 250 => error
         if self.fee_rate > 25 * 250 {
-            return Err(ContractError::TooHighFeeRate);
+            errors.push(ContractError::TooHighFeeRate);
         }
 
         // Validate the rest
         let sum_collaterals = self.offer_collateral + self.accept_collateral;
-        self.contract_info.validate(sum_collaterals)?;
+        errors.extend(self.contract_info.validate_all(sum_collaterals));
 
-        Ok(())
+        let mut unique_errors = Vec::with_capacity(errors.len());
+        for error in errors {
+            if !unique_errors.contains(&error) {
+                unique_errors.push(error);
+            }
+        }
+        unique_errors
+    }
+}
+
+// Turns list of broken rules into result of single-error validation
+fn first_error(errors: Vec<ContractError>) -> Result<(), ContractError> {
+    match errors.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(()),
     }
 }
 
@@ -195,91 +371,292 @@ pub struct ContractInfo {
 
 impl ContractInfo {
     pub fn validate(&self, max_payout: u64) -> Result<(), ContractError> {
-        self.oracle.validate()?;
-        self.contract_descriptor
-            .validate(max_payout, self.oracle.nb_digits)?;
-        Ok(())
+        first_error(self.validate_all(max_payout))
+    }
+
+    pub fn validate_all(&self, max_payout: u64) -> Vec<ContractError> {
+        if self.contract_descriptor.is_enumerated() {
+            let mut errors = self.oracle.validate_enumerated_all();
+            errors.extend(self.contract_descriptor.validate_all(max_payout, None));
+            return errors;
+        }
+        let mut errors = self.oracle.validate_all();
+        // Outcome range can be checked only against valid number of digits and base
+        let max_outcome = errors
+            .is_empty()
+            .then(|| fun::max_outcome(self.oracle.nb_digits, self.oracle.base()));
+        errors.extend(
+            self.contract_descriptor
+                .validate_all(max_payout, max_outcome),
+        );
+        errors
     }
 }
 
+/// Payouts of the contract: either `payout_intervals` over numeric outcomes, or `enumerated_payouts`
+/// with payout of each enumerated outcome (as enumerated contract descriptor in dlcspecs).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ContractDescriptor {
+    #[serde(default)]
     pub payout_intervals: Vec<PayoutInterval>,
+    // Optional rounding of payouts, which reduces number of distinct payouts (and so CETs)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rounding_intervals: Vec<RoundingInterval>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub enumerated_payouts: Vec<EnumeratedPayout>,
 }
 
 impl ContractDescriptor {
-    pub fn validate(&self, max_payout: u64, nb_digits: u8) -> Result<(), ContractError> {
+    /// Returns true if contract is over enumerated outcomes instead of numeric ones.
+    pub fn is_enumerated(&self) -> bool {
+        !self.enumerated_payouts.is_empty()
+    }
+
+    /// Labels of enumerated outcomes, in order of contract (empty for numeric contract).
+    pub fn outcome_labels(&self) -> Vec<String> {
+        self.enumerated_payouts
+            .iter()
+            .map(|ep| ep.outcome.clone())
+            .collect()
+    }
+
+    /// Rounding modulus applied to payout of given outcome (1, i.e. no rounding, if there are no rounding intervals).
+    pub fn rounding_mod(&self, outcome: u64) -> u64 {
+        // Rounding intervals are sorted by `begin_interval`, so we take the last one starting before outcome
+        let idx = self
+            .rounding_intervals
+            .partition_point(|ri| ri.begin_interval <= outcome);
+        match idx {
+            0 => 1,
+            _ => self.rounding_intervals[idx - 1].rounding_mod,
+        }
+    }
+
+    pub fn validate(&self, max_payout: u64, max_outcome: u64) -> Result<(), ContractError> {
+        first_error(self.validate_all(max_payout, Some(max_outcome)))
+    }
+
+    /// Validates intervals of the descriptor. Outcome range check is skipped if `max_outcome` is `None`.
+    pub fn validate_all(&self, max_payout: u64, max_outcome: Option<u64>) -> Vec<ContractError> {
+        if self.is_enumerated() {
+            return self.validate_enumerated_all(max_payout);
+        }
+        let mut errors = Vec::new();
+
+        // 14: Rounding intervals (if any) must start at 0, be sorted and have positive modulus
+        if !self.rounding_intervals.is_empty() {
+            let starts_at_zero = self.rounding_intervals[0].begin_interval == 0;
+            let increasing = self
+                .rounding_intervals
+                .windows(2)
+                .all(|w| w[0].begin_interval < w[1].begin_interval);
+            let positive_mods = self.rounding_intervals.iter().all(|ri| ri.rounding_mod > 0);
+            if !(starts_at_zero && increasing && positive_mods) {
+                errors.push(ContractError::InvalidRoundingIntervals);
+            }
+        }
+
         // 9: must have at least one interval
         if self.payout_intervals.is_empty() {
-            return Err(ContractError::MissingIntervals);
+            errors.push(ContractError::MissingIntervals);
+            return errors;
         }
 
-        // 2^NB_DIGITS - 1 is expected final outcome
-        let expected_final_outcome = (1 << nb_digits) - 1;
-
         // 2: First point of first interval must start at zero
-        let first_interval = &self.payout_intervals[0];
-        let first_pt = first_interval
-            .payout_points
-            .first()
-            .ok_or(ContractError::InvalidIntervalPoints)?;
-        if first_pt.event_outcome != 0 {
-            return Err(ContractError::InvalidFirstOutcome);
+        match self.payout_intervals[0].payout_points.first() {
+            Some(first_pt) if first_pt.event_outcome != 0 => {
+                errors.push(ContractError::InvalidFirstOutcome)
+            }
+            Some(_) => {}
+            None => errors.push(ContractError::InvalidIntervalPoints),
         }
 
-        // 1. Validate each interval, check continuity
+        // 1. Validate each interval, check continuity (intervals without points are reported below)
         for w in self.payout_intervals.windows(2) {
-            let end_of_this = w[0].payout_points[1].event_outcome;
-            let start_of_next = w[1].payout_points[0].event_outcome;
-            if end_of_this != start_of_next {
-                return Err(ContractError::NonContinuousIntervals);
+            let end_of_this = w[0].payout_points.last().map(|pt| pt.event_outcome);
+            let start_of_next = w[1].payout_points.first().map(|pt| pt.event_outcome);
+            if let (Some(end_of_this), Some(start_of_next)) = (end_of_this, start_of_next) {
+                if end_of_this != start_of_next {
+                    errors.push(ContractError::NonContinuousIntervals);
+                }
             }
         }
 
-        // 3: Last point must end on 2^NB_DIGITS - 1
+        // 3: Last point must end on base^nb_digits - 1
         let last_interval = self.payout_intervals.last().unwrap();
-        let last_pt = last_interval
-            .payout_points
-            .last()
-            .ok_or(ContractError::InvalidIntervalPoints)?;
-        if last_pt.event_outcome != expected_final_outcome {
-            return Err(ContractError::OutcomeRangeMismatch);
+        match (last_interval.payout_points.last(), max_outcome) {
+            (Some(last_pt), Some(max_outcome)) => {
+                if last_pt.event_outcome != max_outcome {
+                    errors.push(ContractError::OutcomeRangeMismatch);
+                }
+            }
+            (Some(_), None) => {}
+            (None, _) => errors.push(ContractError::InvalidIntervalPoints),
         }
 
         // Validate intervals individually
         for interval in &self.payout_intervals {
-            interval.validate(max_payout)?;
+            errors.extend(interval.validate_all(max_payout));
         }
 
-        Ok(())
+        errors
     }
+
+    fn validate_enumerated_all(&self, max_payout: u64) -> Vec<ContractError> {
+        let mut errors = Vec::new();
+        // 17. Enumerated contract has no intervals and at least 2 distinct non-empty outcome labels
+        let has_duplicates = self.enumerated_payouts.iter().enumerate().any(|(i, ep)| {
+            self.enumerated_payouts[..i]
+                .iter()
+                .any(|other| other.outcome == ep.outcome)
+        });
+        if !self.payout_intervals.is_empty()
+            || !self.rounding_intervals.is_empty()
+            || self.enumerated_payouts.len() < 2
+            || self
+                .enumerated_payouts
+                .iter()
+                .any(|ep| ep.outcome.is_empty())
+            || has_duplicates
+        {
+            errors.push(ContractError::InvalidEnumeratedOutcomes);
+        }
+        // 4: outcomePayout <= sum of offerCollateral and acceptCollateral
+        if self
+            .enumerated_payouts
+            .iter()
+            .any(|ep| ep.outcome_payout > max_payout)
+        {
+            errors.push(ContractError::InvalidPayout);
+        }
+        errors
+    }
+}
+
+/// Payout of one enumerated outcome.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnumeratedPayout {
+    pub outcome: String,
+    pub outcome_payout: PayoutT, // Amount (btc cargo)
+}
+
+/// From `begin_interval` on (up to next rounding interval), payouts are rounded to multiple of `rounding_mod`.
+/// As in dlcspecs NumericOutcome.md.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoundingInterval {
+    pub begin_interval: u64,
+    pub rounding_mod: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PayoutInterval {
     pub payout_points: Vec<PayoutPoint>,
+    // If present, interval is a hyperbola piece and its 2 payout points are end points of the hyperbola
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hyperbola: Option<HyperbolaPiece>,
 }
 
 impl PayoutInterval {
+    /// Non-linear payout curve of the interval: hyperbola piece, or polynomial piece if interval has
+    /// more than 2 payout points. Returns `None` for linear (2 points) interval.
+    pub fn curve(&self) -> Option<Box<dyn PayoutCurve>> {
+        match &self.hyperbola {
+            Some(hyperbola) => Some(Box::new(hyperbola.clone())),
+            None if self.payout_points.len() > 2 => {
+                Some(Box::new(PolynomialPiece::new(&self.payout_points)))
+            }
+            None => None,
+        }
+    }
+
     pub fn validate(&self, max_payout: u64) -> Result<(), ContractError> {
-        // 8: Each interval should have exactly 2 points
-        if self.payout_points.len() != 2 {
-            return Err(ContractError::InvalidIntervalPoints);
+        first_error(self.validate_all(max_payout))
+    }
+
+    pub fn validate_all(&self, max_payout: u64) -> Vec<ContractError> {
+        let mut errors = Vec::new();
+        // 8: Each interval should have at least 2 points (2 points form linear piece, more points polynomial piece)
+        if self.payout_points.len() < 2 {
+            errors.push(ContractError::InvalidIntervalPoints);
+        }
+        // 12: Outcomes of payout points must be strictly increasing
+        if self
+            .payout_points
+            .windows(2)
+            .any(|w| w[0].event_outcome >= w[1].event_outcome)
+        {
+            errors.push(ContractError::NonIncreasingOutcomes);
         }
         // Validate each payout point
         for point in &self.payout_points {
-            point.validate(max_payout)?;
+            if let Err(e) = point.validate(max_payout) {
+                errors.push(e);
+            }
         }
-        Ok(())
+        // 13: Hyperbola piece must be well-defined and go through both of its end points
+        if let Some(hyperbola) = &self.hyperbola {
+            if !self.hyperbola_valid(hyperbola, max_payout) {
+                errors.push(ContractError::InvalidHyperbolaPiece);
+            }
+        }
+        if !errors.is_empty() {
+            return errors;
+        }
+
+        // 4: Non-linear curve may leave range of its payout points, so payouts at ends of each of its monotone
+        // ranges are checked (payouts inside the range lie between them)
+        if let Some(curve) = self.curve() {
+            let start = self.payout_points.first().unwrap().event_outcome;
+            let end = self.payout_points.last().unwrap().event_outcome;
+            if curve.monotone_ranges(start, end).iter().any(|&(from, to)| {
+                curve.rounded_payout(from, max_payout).is_none()
+                    || curve.rounded_payout(to, max_payout).is_none()
+            }) {
+                errors.push(ContractError::InvalidPayout);
+            }
+        }
+        errors
     }
+
+    fn hyperbola_valid(&self, hyperbola: &HyperbolaPiece, max_payout: u64) -> bool {
+        let params = [
+            hyperbola.translate_outcome,
+            hyperbola.translate_payout,
+            hyperbola.a,
+            hyperbola.b,
+            hyperbola.c,
+            hyperbola.d,
+        ];
+        self.payout_points.len() == 2
+            && hyperbola.a != 0.0
+            && params.iter().all(|param| param.is_finite())
+            && self.payout_points.iter().all(|pt| {
+                hyperbola.rounded_payout(pt.event_outcome, max_payout) == Some(pt.outcome_payout)
+            })
+    }
+}
+
+/// Parameters of hyperbola payout piece, as in dlcspecs PayoutCurve.md (evaluated in `crate::parser::payout_curve`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HyperbolaPiece {
+    pub use_positive_piece: bool,
+    pub translate_outcome: f64,
+    pub translate_payout: f64,
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PayoutPoint {
-    pub event_outcome: u32,
+    pub event_outcome: u64,
     pub outcome_payout: PayoutT, // Amount (btc cargo)
 }
 
@@ -298,20 +675,161 @@ impl PayoutPoint {
     }
 }
 
+/// Oracles of the contract. Contract settles when `threshold` of them attest to the same outcome,
+/// or to close enough outcomes if `max_error_exp` and `min_support_exp` are set (see `AllowedDifference`).
+/// Single oracle can still be given with `publicKey`, as before.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OracleInput {
-    pub public_key: PublicKey,
+    #[serde(alias = "publicKey", deserialize_with = "deserialize_public_keys")]
+    pub public_keys: Vec<PublicKey>,
     pub event_id: String,
-    pub nb_digits: u8,
+    #[serde(default)]
+    pub nb_digits: u8, // Not used (0) by enumerated contracts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<u8>, // Base of outcome digits, if not set, outcomes are binary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<u8>, // If not set, all oracles have to attest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_error_exp: Option<u8>, // If not set, oracles have to attest to exactly the same outcome
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_support_exp: Option<u8>, // Set together with max_error_exp
+}
+
+/// Allowed difference of oracle outcomes as in dlcspecs MultiOracle.md, given by exponents of outcome base.
+/// Outcomes differing by at most `base^min_support_exp` always settle the contract, and outcomes differing by
+/// `base^max_error_exp + base^min_support_exp` or more never do (see `digit_decomposition::cover_oracle_outcomes`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllowedDifference {
+    pub max_error_exp: u8,
+    pub min_support_exp: u8,
+}
+
+impl AllowedDifference {
+    /// Size of aligned outcome blocks other oracles' prefixes are built from.
+    pub fn max_error(&self, base: u8) -> u64 {
+        fun::nb_outcomes(self.max_error_exp, base) as u64
+    }
+
+    /// Difference of outcomes that is always supported.
+    pub fn min_support(&self, base: u8) -> u64 {
+        fun::nb_outcomes(self.min_support_exp, base) as u64
+    }
 }
 
 impl OracleInput {
+    pub fn nb_oracles(&self) -> usize {
+        self.public_keys.len()
+    }
+
+    /// Number of oracles that have to attest to the same outcome.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+            .map_or(self.nb_oracles(), |threshold| threshold as usize)
+    }
+
+    /// Base in which oracles decompose outcome into digits.
+    pub fn base(&self) -> u8 {
+        self.base.unwrap_or(DEFAULT_BASE)
+    }
+
+    /// Allowed difference between outcomes of oracles settling the contract (None if they have to agree exactly).
+    pub fn allowed_difference(&self) -> Option<AllowedDifference> {
+        Some(AllowedDifference {
+            max_error_exp: self.max_error_exp?,
+            min_support_exp: self.min_support_exp?,
+        })
+    }
+
     pub fn validate(&self) -> Result<(), ContractError> {
-        // 11. nb_digits from contract must match NB_DIGITS constant
-        if self.nb_digits != NB_DIGITS {
-            return Err(ContractError::NbDigitsMismatch);
+        first_error(self.validate_all())
+    }
+
+    pub fn validate_all(&self) -> Vec<ContractError> {
+        let mut errors = Vec::new();
+        // 18. Digits must have base at least 2
+        let base_valid = self.base() >= 2;
+        if !base_valid {
+            errors.push(ContractError::InvalidBase);
         }
-        Ok(())
+        // 11. nb_digits must be positive and outcomes must fit into u64
+        let nb_digits_valid = self.nb_digits > 0
+            && self.nb_digits <= MAX_NB_DIGITS
+            && (!base_valid || self.nb_digits <= fun::max_nb_digits(self.base()));
+        if !nb_digits_valid {
+            errors.push(ContractError::InvalidNbDigits);
+        }
+        errors.extend(self.validate_oracle_keys());
+        // 16. Allowed difference exponents are set together, min_support_exp < max_error_exp < nb_digits
+        if self.max_error_exp.is_some() || self.min_support_exp.is_some() {
+            let exps_valid = self.allowed_difference().is_some_and(|difference| {
+                difference.min_support_exp < difference.max_error_exp
+                    && difference.max_error_exp < self.nb_digits
+            });
+            if !exps_valid {
+                errors.push(ContractError::InvalidAllowedDifference);
+            }
+        }
+        errors
     }
+
+    /// Validates oracles of enumerated contract, which attest to outcome labels instead of digits.
+    pub fn validate_enumerated_all(&self) -> Vec<ContractError> {
+        let mut errors = Vec::new();
+        // 11. Enumerated outcomes have no digits
+        if self.nb_digits != 0 {
+            errors.push(ContractError::InvalidNbDigits);
+        }
+        // 18. ... so they have no base either
+        if self.base.is_some() {
+            errors.push(ContractError::InvalidBase);
+        }
+        errors.extend(self.validate_oracle_keys());
+        // 16. Enumerated outcomes can't differ by some amount
+        if self.max_error_exp.is_some() || self.min_support_exp.is_some() {
+            errors.push(ContractError::InvalidAllowedDifference);
+        }
+        errors
+    }
+
+    fn validate_oracle_keys(&self) -> Vec<ContractError> {
+        let mut errors = Vec::new();
+        // 15. There must be at least one oracle, no oracle twice, and threshold between 1 and number of oracles
+        let has_duplicates = self
+            .public_keys
+            .iter()
+            .enumerate()
+            .any(|(i, key)| self.public_keys[..i].contains(key));
+        if self.public_keys.is_empty()
+            || has_duplicates
+            || self.threshold() == 0
+            || self.threshold() > self.nb_oracles()
+        {
+            errors.push(ContractError::InvalidOracleThreshold);
+        } else if fun::nb_oracle_subsets(self.nb_oracles(), self.threshold())
+            .map_or(true, |nb_subsets| nb_subsets > MAX_ORACLE_SUBSETS)
+        {
+            // 19. Every oracle subset has its own adaptors, so their number must stay reasonable
+            errors.push(ContractError::TooManyOracleSubsets);
+        }
+        errors
+    }
+}
+
+// Accepts both single public key and list of public keys
+fn deserialize_public_keys<'de, D>(deserializer: D) -> Result<Vec<PublicKey>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(PublicKey),
+        Many(Vec<PublicKey>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(key) => vec![key],
+        OneOrMany::Many(keys) => keys,
+    })
 }
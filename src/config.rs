@@ -4,71 +4,53 @@ extern crate static_assertions;
 
 // In future, "relevant adaptors optimization" could be also implemented here as a constant, runtime parameter, or feature flag. The choice is flexible and depends on the design decision.
 
-#[cfg(all(feature = "ecdsa", feature = "schnorr"))]
-compile_error!("Features 'ecdsa' and 'schnorr' cannot be enabled at the same time.");
-
-#[cfg(not(any(feature = "ecdsa", feature = "schnorr")))]
-compile_error!("Either feature 'ecdsa' or 'schnorr' must be enabled.");
-
-#[cfg(all(feature = "basis-method", feature = "simple-method"))]
-compile_error!("Features 'basis-method' and 'simple-method' cannot be enabled at the same time.");
-
-#[cfg(not(any(feature = "basis-method", feature = "simple-method")))]
-compile_error!("Either feature 'basis-method' or 'simple-method' must be enabled.");
-
 pub mod constants {
-    // Configurable constants
-    pub const NB_DIGITS: u8 = 20; // Number of digits representing an outcome
-    pub const CONTRACT_INPUT_PATH: &str =
-        "./input_contracts/sample_contracts/numerical_contract_input.json";
-
     // Fixed constants (do not modify)
-    pub const NB_OUTCOMES: u32 = 1 << NB_DIGITS; // Total number of possible outcomes
-    pub const MAX_OUTCOME: u32 = NB_OUTCOMES - 1; // Maximum possible outcome value
-    pub const ZERO_OUTCOME_ATP: u32 = 1000; // Arbitrary value greater than NB_DIGITS, used as a zero outcome anticipation point value
-
-    // compile‑time check NB_DIGITS ≤ 32 (outcomes represented with u32)
+    // Number of digits representing an outcome is taken from each contract (`nbDigits`), up to this maximum (for binary
+    // digits, outcomes with digits of larger base fit into u64 with fewer digits)
+    pub const MAX_NB_DIGITS: u8 = 64;
+    pub const DEFAULT_BASE: u8 = 2; // Base of outcome digits, if contract doesn't set it (`base`)
+    pub const BASIS_WINDOW_BITS: u8 = 4; // Default window of basis method tables, each has up to max(2^BASIS_WINDOW_BITS, base) - 1 points (see `BasisCryptoUtils::with_window`), can be changed with `--basis-window`
+    pub const MAX_BASIS_WINDOW_BITS: u8 = 16; // Largest window, whose tables still fit into memory
+    pub const MAX_ORACLE_SUBSETS: usize = 1024; // Largest number of threshold-sized oracle subsets (C(nb_oracles, threshold)), each of them has its own anticipation points and adaptors
+    pub const ZERO_OUTCOME_ATP: u32 = 1000; // Arbitrary value greater than any digit value, used as a zero outcome anticipation point value
+
+    // compile‑time check MAX_NB_DIGITS ≤ 64 (outcomes represented with u64) and that zero outcome value can't collide with a digit value
     use static_assertions::const_assert;
-    const_assert!(NB_DIGITS <= 32);
+    const_assert!(MAX_NB_DIGITS <= 64);
+    const_assert!(ZERO_OUTCOME_ATP > u8::MAX as u32);
 }
 
 pub mod runparams {
-    // Use cargo features to select the appropriate implementation:
-    #[cfg(feature = "ecdsa")]
-    pub use crate::adaptor_signature_scheme::EcdsaAdaptorSignatureScheme as MyAdaptorSignatureScheme;
-    #[cfg(feature = "ecdsa")]
+    // Default (monomorphized) configuration used by the fast path and the microbenchmarks.
+    // Adaptor signature scheme and anticipation point method can also be selected at runtime,
+    // see `crate::dlc_session::SessionConfig`.
+    pub type MyAdaptorSignatureScheme =
+        crate::adaptor_signature_scheme::EcdsaAdaptorSignatureScheme;
     pub type MySignature = secp256k1_zkp::ecdsa::Signature;
-
-    #[cfg(feature = "schnorr")]
-    pub use crate::adaptor_signature_scheme::SchnorrAdaptorSignatureScheme as MyAdaptorSignatureScheme;
-    #[cfg(feature = "schnorr")]
-    pub type MySignature = secp256k1_zkp::schnorr::Signature;
-
-    // CryptoUtils implementation selection via feature flags
-    #[cfg(feature = "simple-method")]
     pub type MyCryptoUtils = crate::crypto_utils::simple_crypto_utils::SimpleCryptoUtils;
 
-    #[cfg(feature = "basis-method")]
-    pub type MyCryptoUtils = crate::crypto_utils::basis_crypto_utils::BasisCryptoUtils;
-
     // To switch between different implementations of Oracle and Parser,
     // modify the type aliases below.
     //
     // If you want to use a custom implementation of Oracle or Parser,
     // implement the respective trait and update the type alias here.
-    pub type MyOracle = crate::oracle::RandIntOracle<MyCryptoUtils>; // Oracle implementation
+    pub type MyOutcome = crate::common::types::OutcomeU64; // Outcome representation (OutcomeU64, OutcomeU32 for up to 32 digits or OutcomeBinStr)
+    pub type MyOracle = crate::oracle::RandIntOracle<MyCryptoUtils, MyOutcome>; // Oracle implementation
     pub type MyParser = crate::parser::parser_out_u32::SimpleOutU32Parser; // Parser implementation
+    pub type MyEnumParser = crate::parser::parser_enum::EnumOutcomeParser; // Parser of enumerated contracts
+    pub type MySparseParser = crate::parser::parser_sparse::SparseOutU64Parser; // Parser listing only outcomes where payout changes
 }
 
 /*
-Feature flags to configure the program:
-  - "ecdsa" or "schnorr": Specifies the type of adaptor signature scheme to use (one must be enabled).
-  - "simple-method" or "basis-method": Specifies the type of crypto utils implementation to use (one must be enabled).
-
-Additional optional features:
+Optional feature flags to configure the program:
   - "parallel-cpt": Enables parallel computation of anticipation points or adaptor signatures (serial if disabled).
   - "parallel-parser": Enables parallel parsing of input intervals (serial if disabled).
-  - "enable-benchmarks": Enables benchmark mode for performance evaluation.
+  - "schnorr-fun": Enables adaptor signature scheme of pure-Rust `schnorr_fun` crate ("schnorr-fun" scheme of session).
+
+Adaptor signature scheme ("ecdsa" or "schnorr"), anticipation point method ("simple", "basis", "digit" or "prefix")
+and window of basis method tables are runtime parameters of `crate::dlc_session::SessionConfig`. Contract path, benchmark mode and seed of the run
+(`crate::common::seed::Seed`) are command-line arguments of the binary (see `main.rs`).
 */
 
 // Re-export constants and runtime parameters for easier access by consumers
@@ -1,11 +1,10 @@
-use std::str::FromStr;
+use secp256k1_zkp::{PublicKey, Scalar, SecretKey};
 
-use bitcoin::secp256k1;
-use secp256k1_zkp::{Error, PublicKey, Scalar, SecretKey};
-
-use crate::common::types;
-use crate::config::{NB_DIGITS, ZERO_OUTCOME_ATP};
-use crate::crypto_utils::CryptoUtils;
+use crate::common::{error::CryptoUtilsError, types};
+use crate::config::{BASIS_WINDOW_BITS, MAX_BASIS_WINDOW_BITS, ZERO_OUTCOME_ATP};
+use crate::crypto_utils::{
+    compute_increments, outcome_digit, outcome_digits, sweep_anticipation_points, CryptoUtils,
+};
 
 use crate::common::types::OutcomeU32;
 use crate::crypto_utils::secp_utils::{
@@ -14,51 +13,143 @@ use crate::crypto_utils::secp_utils::{
 
 use secp256k1_zkp::SECP256K1;
 
+// INFO: Each digit position has its own nonce, so that partial attestations of different digit positions
+// don't share a nonce. Oracle still publishes only their sum, as revealing partial attestations would
+// also unlock every outcome whose non-zero digits are a subset of attested outcome's non-zero digits.
+// Non-zero digit value is signed with nonce of its position, zero digits are left out of the sum (as unset bits are).
+// Zero outcome (no digit set) is signed with nonce of digit 0, which is otherwise unused for this outcome.
+
+// Digits are split into windows of consecutive digits and sums of digit points are precomputed for all value combinations
+// of each window, so anticipation point needs one addition per window instead of one per non-zero digit.
+// Larger window means fewer additions, but table of each window grows exponentially with its size.
+
 pub struct BasisCryptoUtils {
     public_key: PublicKey,
-    public_nonce: PublicKey,
-    precomputed_points: Vec<PublicKey>,
+    public_nonces: Vec<PublicKey>,
+    nb_digits: u8,
+    base: u8,
+    window: u8,                              // Number of digits per window
+    precomputed_points: Vec<Vec<PublicKey>>, // precomputed_points[k][c - 1] is point of digits of window k forming value c > 0
+    increments: Vec<Vec<Option<PublicKey>>>, // increments[i][v] turns point of outcome into point of the next one (see `compute_increments`)
 }
 
 impl BasisCryptoUtils {
+    /// Number of digits per window.
+    pub fn window(&self) -> u8 {
+        self.window
+    }
+
+    /// Number of precomputed points in all window tables.
+    pub fn table_size(&self) -> usize {
+        self.precomputed_points.iter().map(Vec::len).sum()
+    }
+
     fn precompute_points(&mut self) {
-        for i in 0..NB_DIGITS {
-            let bit_position = OutcomeU32::from(i as u32); // We are using outcome type here, bcs schnorrsig_compute.. expects it.
-            let basis_atp_point = schnorrsig_compute_anticipation_point(
-                SECP256K1,
-                &self.public_key,
-                &self.public_nonce,
-                &bit_position,
-            )
-            .expect("Failed to compute basis anticipation point");
-            self.precomputed_points[i as usize] = basis_atp_point;
-        }
+        let digit_points: Vec<Vec<PublicKey>> = (0..self.nb_digits)
+            .map(|i| {
+                (1..self.base)
+                    .map(|value| {
+                        let digit_value = OutcomeU32::from(value as u32); // We are using outcome type here, bcs schnorrsig_compute.. expects it.
+                        schnorrsig_compute_anticipation_point(
+                            SECP256K1,
+                            &self.public_key,
+                            &self.public_nonces[i as usize],
+                            &digit_value,
+                        )
+                        .expect("Failed to compute basis anticipation point")
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Each combination is a smaller one plus point of its most significant digit
+        let base = self.base as usize;
+        self.precomputed_points = digit_points
+            .chunks(self.window as usize)
+            .map(|window_points| {
+                let table_len = base.pow(window_points.len() as u32);
+                let mut table: Vec<PublicKey> = Vec::with_capacity(table_len - 1);
+                for combination in 1..table_len {
+                    let position = combination.ilog(base);
+                    let scale = base.pow(position);
+                    let point = window_points[position as usize][combination / scale - 1];
+                    table.push(match combination % scale {
+                        0 => point,
+                        rest => table[rest - 1]
+                            .combine(&point)
+                            .expect("Failed to compute basis anticipation point"),
+                    });
+                }
+                table
+            })
+            .collect();
+
+        self.increments = compute_increments(self.nb_digits, self.base, |i, value| {
+            (value > 0).then(|| self.digit_point(i, value))
+        });
+    }
+
+    // Point of digit i having value v > 0 (stored in table of its window)
+    fn digit_point(&self, i: usize, value: u8) -> PublicKey {
+        let window = self.window as usize;
+        let scale = (self.base as usize).pow((i % window) as u32);
+        self.precomputed_points[i / window][value as usize * scale - 1]
     }
 }
 
 impl CryptoUtils for BasisCryptoUtils {
-    fn new(public_key: &PublicKey, public_nonce: &PublicKey) -> Self {
+    // One nonce per digit position
+    fn nb_nonces(nb_digits: u8) -> usize {
+        nb_digits as usize
+    }
+
+    // Windows of `BASIS_WINDOW_BITS` bits
+    fn new(public_key: &PublicKey, public_nonces: &[PublicKey], nb_digits: u8, base: u8) -> Self {
+        Self::with_window(
+            public_key,
+            public_nonces,
+            nb_digits,
+            base,
+            BASIS_WINDOW_BITS,
+        )
+    }
+
+    /// Creates crypto utils with windows of as many digits as fit into `window_bits` bits (at least one digit),
+    /// so each window table has at most max(2^window_bits, base) - 1 points (single digit of base larger than
+    /// 2^window_bits doesn't fit, its window still has all base - 1 digit points).
+    fn with_window(
+        public_key: &PublicKey,
+        public_nonces: &[PublicKey],
+        nb_digits: u8,
+        base: u8,
+        window_bits: u8,
+    ) -> Self {
+        debug_assert!(
+            (1..=MAX_BASIS_WINDOW_BITS).contains(&window_bits),
+            "Window must have between 1 and MAX_BASIS_WINDOW_BITS bits"
+        );
+        let window = (1..=window_bits)
+            .take_while(|&size| (base as u32).pow(size as u32) <= 1 << window_bits)
+            .last()
+            .unwrap_or(1);
         let mut instance = Self {
             public_key: *public_key,
-            public_nonce: *public_nonce,
-            precomputed_points: vec![
-                SecretKey::from_str(
-                    "0000000000000000000000000000000000000000000000000000000000000001"
-                )
-                .unwrap()
-                .public_key(SECP256K1);
-                NB_DIGITS as usize
-            ],
+            public_nonces: public_nonces.to_vec(),
+            nb_digits,
+            base,
+            window,
+            precomputed_points: Vec::new(),
+            increments: Vec::new(),
         };
         instance.precompute_points();
         instance
     }
 
-    // Will throw error if no bit is set in outcome (we catch zero outcome, so there must be some other problem like bigger number than 2^NB_DIGITS and it's first NB_DIGITS bits are not set)
+    // Will throw error if no digit is set in outcome (we catch zero outcome, so there must be some other problem like bigger number than base^nb_digits and it's first nb_digits digits are not set)
     fn compute_anticipation_point(
         &self,
         outcome: &impl types::Outcome,
-    ) -> Result<types::AnticipationPoint, Error> {
+    ) -> Result<types::AnticipationPoint, CryptoUtilsError> {
         use secp256k1_zkp::PublicKey;
 
         // If the outcome is zero, use exceptional anticipation point.
@@ -66,66 +157,97 @@ impl CryptoUtils for BasisCryptoUtils {
             return schnorrsig_compute_anticipation_point(
                 SECP256K1,
                 &self.public_key,
-                &self.public_nonce,
+                &self.public_nonces[0],
                 &OutcomeU32::from(ZERO_OUTCOME_ATP),
             );
         }
 
-        // Else if outcome is not zero: Select basis atp_points and combine them.
+        // Else if outcome is not zero: Select precomputed points of non-zero windows and combine them.
         let mut selected_basis_atps = Vec::new();
-        for i in 0..NB_DIGITS {
-            if outcome.get_bit(i) {
-                selected_basis_atps.push(&self.precomputed_points[i as usize]);
+        for (k, table) in self.precomputed_points.iter().enumerate() {
+            let first_digit = k as u8 * self.window;
+            let combination = (first_digit..self.nb_digits.min(first_digit + self.window))
+                .rev()
+                .try_fold(0, |combination, i| {
+                    Ok::<_, CryptoUtilsError>(
+                        combination * self.base as usize
+                            + outcome_digit(outcome, i, self.base)? as usize,
+                    )
+                })?;
+            if combination > 0 {
+                selected_basis_atps.push(&table[combination - 1]);
             }
         }
+        if selected_basis_atps.is_empty() {
+            return Err(CryptoUtilsError::OutcomeOutOfRange);
+        }
         let combined = PublicKey::combine_keys(&selected_basis_atps)?;
         Ok(combined)
     }
 
-    // Same as atp_point, will throw error if no bit is set in outcome (we throw same error to represent no bit set - InvalidPublicKeySum)
-    fn compute_attestation(
+    // Zero outcome has exceptional anticipation point, so outcome 1 is computed in full
+    fn compute_anticipation_points<'a, O: types::Outcome + 'a>(
+        &self,
+        outcomes: impl Iterator<Item = &'a O>,
+    ) -> Result<Vec<types::AnticipationPoint>, CryptoUtilsError> {
+        sweep_anticipation_points(
+            outcomes,
+            self.nb_digits,
+            self.base,
+            &self.increments,
+            |outcome| !outcome.is_zero(),
+            |outcome| self.compute_anticipation_point(outcome),
+        )
+    }
+
+    // Same as atp_point, will throw error if no digit is set in outcome (bigger number than base^nb_digits whose first
+    // nb_digits digits are not set)
+    // Returns single attestation, sum of partial attestations of set digits
+    fn compute_attestations(
         &self,
         private_key: &SecretKey,
-        private_nonce: &SecretKey,
+        private_nonces: &[SecretKey],
         outcome: &impl types::Outcome,
-    ) -> Result<types::Attestation, Error> {
+    ) -> Result<Vec<types::Attestation>, CryptoUtilsError> {
         // If the outcome is zero, use exceptional anticipation point.
         if outcome.is_zero() {
-            return schnorrsig_compute_oracle_attestation(
+            return Ok(vec![schnorrsig_compute_oracle_attestation(
                 SECP256K1,
                 private_key,
-                private_nonce,
+                &private_nonces[0],
                 &OutcomeU32::from(ZERO_OUTCOME_ATP),
-            );
+            )?]);
         }
 
-        // Else if outcome is not zero: Find first non-zero outcome bit
-        let first_index = (0..NB_DIGITS)
-            .find(|&i| outcome.get_bit(i))
-            .ok_or(secp256k1::Error::InvalidPublicKeySum)?;
+        // Else if outcome is not zero: Find first non-zero outcome digit
+        let digits = outcome_digits(outcome, self.nb_digits, self.base)?;
+        let first_index = digits
+            .iter()
+            .position(|&digit| digit > 0)
+            .ok_or(CryptoUtilsError::OutcomeOutOfRange)?;
 
-        // Use the partial attestation for the first set bit as the initial value.
+        // Use the partial attestation for the first set digit as the initial value.
         let mut combined = schnorrsig_compute_oracle_attestation(
             SECP256K1,
             private_key,
-            private_nonce,
-            &OutcomeU32::from(first_index as u32),
+            &private_nonces[first_index],
+            &OutcomeU32::from(digits[first_index] as u32),
         )?;
 
-        // For every subsequent set bit, compute partial attestation and add (tweak) it.
-        for i in (first_index + 1)..NB_DIGITS {
-            if outcome.get_bit(i) {
+        // For every subsequent set digit, compute partial attestation and add (tweak) it.
+        for (i, &digit) in digits.iter().enumerate().skip(first_index + 1) {
+            if digit > 0 {
                 let partial = schnorrsig_compute_oracle_attestation(
                     SECP256K1,
                     private_key,
-                    private_nonce,
-                    &OutcomeU32::from(i as u32),
+                    &private_nonces[i],
+                    &OutcomeU32::from(digit as u32),
                 )?;
                 let scalar = Scalar::from(partial);
                 combined = combined.add_tweak(&scalar)?;
             }
         }
-        Ok(combined)
+        Ok(vec![combined])
     }
 }
 
@@ -137,31 +259,82 @@ mod tests {
 
     use super::*;
 
+    const NB_DIGITS: u8 = 20;
+
     // Helper function to create a dummy instance of BasisCryptoUtils using a fixed secret key.
     fn create_dummy_utils() -> BasisCryptoUtils {
         // Create a fixed secret key (32 bytes all set to 1).
         let sk = SecretKey::from_slice(&[1u8; 32]).expect("32 bytes, within curve order");
         let pk = PublicKey::from_secret_key(SECP256K1, &sk);
-        BasisCryptoUtils::new(&pk, &pk)
+        BasisCryptoUtils::new(&pk, &[pk; NB_DIGITS as usize], NB_DIGITS, 2)
+    }
+
+    // Fixed private nonces, different for each bit position
+    fn create_nonces() -> Vec<SecretKey> {
+        (0..NB_DIGITS)
+            .map(|i| SecretKey::from_slice(&[i + 3; 32]).expect("32 bytes, within curve order"))
+            .collect()
     }
 
     #[test]
     fn test_precompute_points() {
         let utils = create_dummy_utils();
-        // Check that we have precomputed exactly NB_DIGITS points.
-        assert_eq!(utils.precomputed_points.len(), NB_DIGITS as usize);
+        // Check that we have precomputed one table per window of 4 digits, with all non-zero combinations.
+        assert_eq!(utils.window(), BASIS_WINDOW_BITS);
+        assert_eq!(utils.precomputed_points.len(), NB_DIGITS as usize / 4);
+        assert_eq!(utils.table_size(), NB_DIGITS as usize / 4 * 15);
         // Optionally, check that none of the points is the identity (or zero) value by serializing.
-        for &point in &utils.precomputed_points {
-            assert!(point.serialize()[..].iter().any(|&b| b != 0));
+        for points in &utils.precomputed_points {
+            assert_eq!(points.len(), 15);
+            assert!(points[0].serialize()[..].iter().any(|&b| b != 0));
         }
     }
 
+    #[test]
+    fn test_window_sizes() {
+        let sk = SecretKey::from_slice(&[2u8; 32]).unwrap();
+        let pk = PublicKey::from_secret_key(SECP256K1, &sk);
+        let public_nonces: Vec<PublicKey> = create_nonces()
+            .iter()
+            .map(|nonce| PublicKey::from_secret_key(SECP256K1, nonce))
+            .collect();
+        let outcomes = [1, 2, 5, 15, 16, 1000, 12345, (1 << NB_DIGITS) - 1].map(OutcomeU32::from);
+        for base in [2, 3] {
+            let single = BasisCryptoUtils::with_window(&pk, &public_nonces, NB_DIGITS, base, 1);
+            assert_eq!(single.window(), 1);
+            // Ternary digit doesn't fit into 1 bit, its tables have max(2^1, 3) - 1 points
+            assert_eq!(
+                single.table_size(),
+                NB_DIGITS as usize * (base as usize - 1)
+            );
+            for window_bits in [3, 4, 8] {
+                let windowed = BasisCryptoUtils::with_window(
+                    &pk,
+                    &public_nonces,
+                    NB_DIGITS,
+                    base,
+                    window_bits,
+                );
+                for outcome in &outcomes {
+                    assert_eq!(
+                        windowed.compute_anticipation_point(outcome).unwrap(),
+                        single.compute_anticipation_point(outcome).unwrap()
+                    );
+                }
+            }
+        }
+        // Ternary windows of 8 bits have 5 digits (3^5 = 243 combinations)
+        let ternary = BasisCryptoUtils::with_window(&pk, &public_nonces, NB_DIGITS, 3, 8);
+        assert_eq!(ternary.window(), 5);
+        assert_eq!(ternary.table_size(), 4 * 242);
+    }
+
     #[test]
     fn test_compute_anticipation_point() {
         let utils = create_dummy_utils();
         // Create an outcome with some bits set, e.g. outcome 3.
         let outcome = OutcomeU32::from(3);
-        let res: Result<types::AnticipationPoint, Error> =
+        let res: Result<types::AnticipationPoint, CryptoUtilsError> =
             utils.compute_anticipation_point(&outcome);
         assert!(
             res.is_ok(),
@@ -179,17 +352,26 @@ mod tests {
         let secp = Secp256k1::new();
         // Use different fixed secret keys for signing and nonce.
         let sk = SecretKey::from_slice(&[2u8; 32]).expect("32 bytes, within curve order");
-        let nonce = SecretKey::from_slice(&[3u8; 32]).expect("32 bytes, within curve order");
+        let nonces = create_nonces();
         let pk = PublicKey::from_secret_key(&secp, &sk);
-        let utils = BasisCryptoUtils::new(&pk, &pk);
+        let public_nonces: Vec<PublicKey> = nonces
+            .iter()
+            .map(|nonce| PublicKey::from_secret_key(&secp, nonce))
+            .collect();
+        let utils = BasisCryptoUtils::new(&pk, &public_nonces, NB_DIGITS, 2);
 
-        let outcome = OutcomeU32::from(5);
-        let attestation_res = utils.compute_attestation(&sk, &nonce, &outcome);
-        assert!(
-            attestation_res.is_ok(),
-            "Attestation computation should succeed"
-        );
-        let _attestation = attestation_res.unwrap();
+        for value in [0, 5, 1 << (NB_DIGITS - 1)] {
+            let outcome = OutcomeU32::from(value);
+            let attestations = utils
+                .compute_attestations(&sk, &nonces, &outcome)
+                .expect("Attestation computation should succeed");
+            // Partial attestations are never revealed separately
+            assert_eq!(attestations.len(), 1);
+            assert_eq!(
+                attestations[0].public_key(&secp),
+                utils.compute_anticipation_point(&outcome).unwrap()
+            );
+        }
     }
 
     #[test]
@@ -198,22 +380,68 @@ mod tests {
         // NB_DIGITS-bits limit exceeded:
         let outcome = OutcomeU32::from(1 << NB_DIGITS);
         let res = utils.compute_anticipation_point(&outcome);
-        assert!(res.is_err(), "Expected error for too-large outcome");
+        assert_eq!(res, Err(CryptoUtilsError::OutcomeOutOfRange));
     }
 
     #[test]
     fn test_compute_attestation_too_large() {
         let secp = Secp256k1::new();
         let sk = SecretKey::from_slice(&[2u8; 32]).unwrap();
-        let nonce = SecretKey::from_slice(&[3u8; 32]).unwrap();
+        let nonces = create_nonces();
         let pk = PublicKey::from_secret_key(&secp, &sk);
-        let utils = BasisCryptoUtils::new(&pk, &pk);
+        let utils = BasisCryptoUtils::new(&pk, &[pk; NB_DIGITS as usize], NB_DIGITS, 2);
 
         let outcome = OutcomeU32::from(1 << NB_DIGITS);
-        let attestation_res = utils.compute_attestation(&sk, &nonce, &outcome);
-        assert!(
-            attestation_res.is_err(),
-            "Expected error for too-large outcome"
-        );
+        let attestation_res = utils.compute_attestations(&sk, &nonces, &outcome);
+        assert_eq!(attestation_res, Err(CryptoUtilsError::OutcomeOutOfRange));
+    }
+
+    #[test]
+    fn test_compute_anticipation_points_incrementally() {
+        let sk = SecretKey::from_slice(&[2u8; 32]).unwrap();
+        let pk = PublicKey::from_secret_key(SECP256K1, &sk);
+        let public_nonces: Vec<PublicKey> = create_nonces()
+            .iter()
+            .map(|nonce| PublicKey::from_secret_key(SECP256K1, nonce))
+            .collect();
+        for base in [2, 3] {
+            let utils = BasisCryptoUtils::new(&pk, &public_nonces, NB_DIGITS, base);
+            // Sweep from zero outcome, with a gap and carries over several digits
+            let outcomes: Vec<OutcomeU32> =
+                (0..100).chain(240..260).map(OutcomeU32::from).collect();
+            let points = utils.compute_anticipation_points(outcomes.iter()).unwrap();
+            assert_eq!(points.len(), outcomes.len());
+            for (outcome, point) in outcomes.iter().zip(points) {
+                assert_eq!(point, utils.compute_anticipation_point(outcome).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_attestation_ternary() {
+        let secp = Secp256k1::new();
+        let sk = SecretKey::from_slice(&[2u8; 32]).unwrap();
+        let nonces = create_nonces();
+        let pk = PublicKey::from_secret_key(&secp, &sk);
+        let public_nonces: Vec<PublicKey> = nonces
+            .iter()
+            .map(|nonce| PublicKey::from_secret_key(&secp, nonce))
+            .collect();
+        let utils = BasisCryptoUtils::new(&pk, &public_nonces, NB_DIGITS, 3);
+
+        // Outcomes 1 (001) and 2 (002) differ only in value of the same digit, 3 (010) in its position
+        let atps: Vec<PublicKey> = [0, 1, 2, 3, 5]
+            .into_iter()
+            .map(|value| {
+                let outcome = OutcomeU32::from(value);
+                let attestations = utils.compute_attestations(&sk, &nonces, &outcome).unwrap();
+                let atp = utils.compute_anticipation_point(&outcome).unwrap();
+                assert_eq!(attestations[0].public_key(&secp), atp);
+                atp
+            })
+            .collect();
+        for (i, atp) in atps.iter().enumerate() {
+            assert!(!atps[..i].contains(atp));
+        }
     }
 }
@@ -0,0 +1,358 @@
+// src/crypto_utils/digit_crypto_utils.rs
+
+use secp256k1_zkp::{PublicKey, SecretKey, SECP256K1};
+
+use crate::common::digit_decomposition::DigitPrefix;
+use crate::common::error::CryptoUtilsError;
+use crate::common::types::{self, OutcomeU32};
+use crate::crypto_utils::secp_utils::{
+    schnorrsig_compute_anticipation_point, schnorrsig_compute_oracle_attestation,
+    schnorrsig_extract_private_key,
+};
+use crate::crypto_utils::{
+    compute_increments, outcome_digit, sweep_anticipation_points, CryptoUtils,
+};
+
+// INFO: Oracle signs each digit of the outcome separately with its own nonce (as in dlcspecs and `rust-dlc`).
+// Anticipation point of an outcome (or of a digit prefix) is sum of anticipation points of its digits, and
+// the corresponding secret is sum of digit attestations. Digit attestations may be published separately,
+// because every digit is signed under its own nonce, so they only unlock outcomes sharing the attested digits.
+
+/// Combines per-digit anticipation points, both for whole outcomes and for digit prefixes.
+pub struct DigitCryptoUtils {
+    // digit_points[i][v] is anticipation point of i-th digit (most significant first) having value v
+    digit_points: Vec<Vec<PublicKey>>,
+    base: u8,
+    increments: Vec<Vec<Option<PublicKey>>>, // increments[p][v] turns point of outcome into point of the next one (see `compute_increments`), p least significant first
+    public_key: PublicKey,
+    public_nonces: Vec<PublicKey>, // Kept to recover private key from equivocating digit attestations
+}
+
+impl DigitCryptoUtils {
+    pub fn nb_digits(&self) -> u8 {
+        self.digit_points.len() as u8
+    }
+
+    pub fn base(&self) -> u8 {
+        self.base
+    }
+
+    /// Anticipation point of all outcomes starting with given prefix.
+    pub fn compute_prefix_anticipation_point(
+        &self,
+        prefix: &DigitPrefix,
+    ) -> Result<types::AnticipationPoint, CryptoUtilsError> {
+        debug_assert_eq!(
+            prefix.base, self.base,
+            "Prefix must have digits of the same base"
+        );
+        let selected_points: Vec<&PublicKey> = (0..prefix.len)
+            .map(|i| &self.digit_points[i as usize][prefix.digit(i) as usize])
+            .collect();
+        Ok(PublicKey::combine_keys(&selected_points)?)
+    }
+
+    // Whether outcome has no more digits than nb_digits
+    fn fits_nb_digits(&self, outcome: &impl types::Outcome) -> bool {
+        (self.nb_digits()..64).all(|i| outcome.get_digit(i, self.base) == Some(0))
+    }
+}
+
+impl CryptoUtils for DigitCryptoUtils {
+    // One nonce per digit
+    fn nb_nonces(nb_digits: u8) -> usize {
+        nb_digits as usize
+    }
+
+    /// Precomputes anticipation points for every value of each digit.
+    fn new(public_key: &PublicKey, public_nonces: &[PublicKey], nb_digits: u8, base: u8) -> Self {
+        let digit_points = public_nonces[..nb_digits as usize]
+            .iter()
+            .map(|nonce| {
+                (0..base)
+                    .map(|digit| digit_anticipation_point(public_key, nonce, digit))
+                    .collect()
+            })
+            .collect::<Vec<Vec<PublicKey>>>();
+        let increments = compute_increments(nb_digits, base, |position, digit| {
+            Some(digit_points[nb_digits as usize - 1 - position][digit as usize])
+        });
+        Self {
+            digit_points,
+            base,
+            increments,
+            public_key: *public_key,
+            public_nonces: public_nonces[..nb_digits as usize].to_vec(),
+        }
+    }
+
+    // Will throw error if outcome has more digits than nb_digits
+    fn compute_anticipation_point(
+        &self,
+        outcome: &impl types::Outcome,
+    ) -> Result<types::AnticipationPoint, CryptoUtilsError> {
+        let nb_digits = self.nb_digits();
+        if !self.fits_nb_digits(outcome) {
+            // Enumerated outcomes have no digits at all
+            outcome_digit(outcome, 0, self.base)?;
+            return Err(CryptoUtilsError::OutcomeOutOfRange);
+        }
+        let selected_points = (0..nb_digits)
+            .map(|i| {
+                let digit = outcome_digit(outcome, nb_digits - 1 - i, self.base)?;
+                Ok(&self.digit_points[i as usize][digit as usize])
+            })
+            .collect::<Result<Vec<&PublicKey>, CryptoUtilsError>>()?;
+        Ok(PublicKey::combine_keys(&selected_points)?)
+    }
+
+    fn compute_anticipation_points<'a, O: types::Outcome + 'a>(
+        &self,
+        outcomes: impl Iterator<Item = &'a O>,
+    ) -> Result<Vec<types::AnticipationPoint>, CryptoUtilsError> {
+        sweep_anticipation_points(
+            outcomes,
+            self.nb_digits(),
+            self.base,
+            &self.increments,
+            |outcome| self.fits_nb_digits(outcome),
+            |outcome| self.compute_anticipation_point(outcome),
+        )
+    }
+
+    /// Attestations of each digit of outcome (most significant first), one per digit nonce.
+    /// (JUST FOR TESTING! IN REAL SCENARIO, ORACLE SHOULD DO THIS)
+    fn compute_attestations(
+        &self,
+        private_key: &SecretKey,
+        private_nonces: &[SecretKey],
+        outcome: &impl types::Outcome,
+    ) -> Result<Vec<types::Attestation>, CryptoUtilsError> {
+        let nb_digits = self.nb_digits();
+        private_nonces[..nb_digits as usize]
+            .iter()
+            .enumerate()
+            .map(|(i, nonce)| {
+                let digit = outcome_digit(outcome, nb_digits - 1 - i as u8, self.base)?;
+                schnorrsig_compute_oracle_attestation(
+                    SECP256K1,
+                    private_key,
+                    nonce,
+                    &OutcomeU32::from(digit as u32),
+                )
+            })
+            .collect()
+    }
+
+    // The same digit position attested with different values reveals private key (its nonce was used twice)
+    fn extract_private_key(
+        &self,
+        (attestations_a, outcome_a): (&[types::Attestation], &impl types::Outcome),
+        (attestations_b, outcome_b): (&[types::Attestation], &impl types::Outcome),
+    ) -> Option<SecretKey> {
+        let nb_digits = self.nb_digits();
+        (0..nb_digits).find_map(|i| {
+            let digit_a = outcome_a.get_digit(nb_digits - 1 - i, self.base)?;
+            let digit_b = outcome_b.get_digit(nb_digits - 1 - i, self.base)?;
+            if digit_a == digit_b {
+                return None;
+            }
+            schnorrsig_extract_private_key(
+                &self.public_key,
+                &self.public_nonces[i as usize],
+                (
+                    attestations_a.get(i as usize)?,
+                    &OutcomeU32::from(digit_a as u32),
+                ),
+                (
+                    attestations_b.get(i as usize)?,
+                    &OutcomeU32::from(digit_b as u32),
+                ),
+            )
+        })
+    }
+}
+
+// Digit value is signed as one-digit outcome, nonce makes it unique for its position
+fn digit_anticipation_point(
+    public_key: &PublicKey,
+    digit_nonce: &PublicKey,
+    digit: u8,
+) -> types::AnticipationPoint {
+    schnorrsig_compute_anticipation_point(
+        SECP256K1,
+        public_key,
+        digit_nonce,
+        &OutcomeU32::from(digit as u32),
+    )
+    .expect("Failed to compute digit anticipation point")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::digit_decomposition::decompose_range;
+    use crate::common::{Outcome, OutcomeEnum};
+    use crate::crypto_utils::{
+        combine_attestations, compute_joint_anticipation_point, compute_joint_anticipation_points,
+    };
+    use secp256k1_zkp::rand::thread_rng;
+
+    const NB_DIGITS: u8 = 10;
+
+    fn oracle_keys(nb_digits: u8, base: u8) -> (SecretKey, Vec<SecretKey>, DigitCryptoUtils) {
+        let mut rng = thread_rng();
+        let private_key = SecretKey::new(&mut rng);
+        let private_nonces: Vec<SecretKey> =
+            (0..nb_digits).map(|_| SecretKey::new(&mut rng)).collect();
+        let public_nonces: Vec<PublicKey> = private_nonces
+            .iter()
+            .map(|nonce| nonce.public_key(SECP256K1))
+            .collect();
+        let utils = DigitCryptoUtils::new(
+            &private_key.public_key(SECP256K1),
+            &public_nonces,
+            nb_digits,
+            base,
+        );
+        (private_key, private_nonces, utils)
+    }
+
+    #[test]
+    fn test_outcome_attestation_matches_anticipation_point() {
+        let (private_key, private_nonces, utils) = oracle_keys(NB_DIGITS, 2);
+        assert_eq!(utils.nb_digits(), NB_DIGITS);
+
+        for value in [0, 1, 517, 1023] {
+            let outcome = OutcomeU32::from(value);
+            let attestations = utils
+                .compute_attestations(&private_key, &private_nonces, &outcome)
+                .unwrap();
+            assert_eq!(attestations.len(), NB_DIGITS as usize);
+            let secret = combine_attestations(&attestations).unwrap();
+            assert_eq!(
+                secret.public_key(SECP256K1),
+                utils.compute_anticipation_point(&outcome).unwrap()
+            );
+        }
+        assert_eq!(
+            utils.compute_anticipation_point(&OutcomeU32::from(1 << NB_DIGITS)),
+            Err(CryptoUtilsError::OutcomeOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_prefix_attestation_matches_anticipation_point() {
+        let (private_key, private_nonces, utils) = oracle_keys(NB_DIGITS, 2);
+
+        let outcome = OutcomeU32::from(517);
+        let attestations = utils
+            .compute_attestations(&private_key, &private_nonces, &outcome)
+            .unwrap();
+
+        // Every prefix covering the outcome can be unlocked by attestation of outcome
+        for prefix in decompose_range(500, 1023, NB_DIGITS, 2) {
+            let atp = utils.compute_prefix_anticipation_point(&prefix).unwrap();
+            let covers = (prefix.first_outcome(NB_DIGITS)..=prefix.last_outcome(NB_DIGITS))
+                .contains(&(outcome.get_value() as u64));
+            let secret = combine_attestations(&attestations[..prefix.len as usize]).unwrap();
+            assert_eq!(secret.public_key(SECP256K1) == atp, covers);
+        }
+    }
+
+    #[test]
+    fn test_full_prefix_is_outcome_anticipation_point() {
+        let (_, _, utils) = oracle_keys(NB_DIGITS, 2);
+        let full = |value| DigitPrefix {
+            value,
+            len: NB_DIGITS,
+            base: 2,
+        };
+        let atp_a = utils.compute_prefix_anticipation_point(&full(1)).unwrap();
+        let atp_b = utils.compute_prefix_anticipation_point(&full(2)).unwrap();
+        assert_ne!(atp_a, atp_b);
+        assert_eq!(
+            atp_a,
+            utils
+                .compute_anticipation_point(&OutcomeU32::from(1))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_compute_anticipation_points_incrementally() {
+        for base in [2, 10] {
+            let (_, _, utils) = oracle_keys(3, base);
+            let max_outcome = base as u32 * base as u32 * base as u32 - 1;
+            let outcomes: Vec<OutcomeU32> = (0..=max_outcome).map(OutcomeU32::from).collect();
+            let points = utils.compute_anticipation_points(outcomes.iter()).unwrap();
+            for (outcome, point) in outcomes.iter().zip(points) {
+                assert_eq!(point, utils.compute_anticipation_point(outcome).unwrap());
+            }
+            // Outcome right after the last one doesn't fit into digits
+            let too_large = [max_outcome, max_outcome + 1].map(OutcomeU32::from);
+            assert!(utils.compute_anticipation_points(too_large.iter()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_decimal_digits() {
+        // 3 decimal digits, e.g. price 0..=999
+        let (private_key, private_nonces, utils) = oracle_keys(3, 10);
+        assert_eq!(utils.base(), 10);
+
+        let outcome = OutcomeU32::from(517);
+        let attestations = utils
+            .compute_attestations(&private_key, &private_nonces, &outcome)
+            .unwrap();
+        assert_eq!(attestations.len(), 3);
+        assert_eq!(
+            combine_attestations(&attestations)
+                .unwrap()
+                .public_key(SECP256K1),
+            utils.compute_anticipation_point(&outcome).unwrap()
+        );
+        assert!(utils
+            .compute_anticipation_point(&OutcomeU32::from(1000))
+            .is_err());
+
+        // Of prefixes 505..509, 51..59 and 6..9, attestation of 517 unlocks only prefix 51
+        for prefix in decompose_range(505, 999, 3, 10) {
+            let atp = utils.compute_prefix_anticipation_point(&prefix).unwrap();
+            let secret = combine_attestations(&attestations[..prefix.len as usize]).unwrap();
+            assert_eq!(secret.public_key(SECP256K1) == atp, prefix.contains(517, 3));
+        }
+    }
+
+    #[test]
+    fn test_enumerated_outcome_has_no_digits() {
+        let (private_key, private_nonces, utils) = oracle_keys(NB_DIGITS, 2);
+        let outcome = OutcomeEnum::new(0, "team_a_wins".to_string());
+        assert_eq!(outcome.get_digit(0, 2), None);
+        assert_eq!(
+            utils.compute_anticipation_point(&outcome),
+            Err(CryptoUtilsError::OutcomeHasNoDigits)
+        );
+        assert_eq!(
+            utils.compute_anticipation_points([outcome.clone()].iter()),
+            Err(CryptoUtilsError::OutcomeHasNoDigits)
+        );
+        assert_eq!(
+            utils.compute_attestations(&private_key, &private_nonces, &outcome),
+            Err(CryptoUtilsError::OutcomeHasNoDigits)
+        );
+    }
+
+    #[test]
+    fn test_joint_anticipation_point_needs_oracles() {
+        let outcome = OutcomeU32::from(1);
+        assert_eq!(
+            compute_joint_anticipation_point::<DigitCryptoUtils>(&[], &outcome),
+            Err(CryptoUtilsError::NoOracles)
+        );
+        assert_eq!(
+            compute_joint_anticipation_points::<DigitCryptoUtils, _>(&[], [outcome].iter()),
+            Err(CryptoUtilsError::NoOracles)
+        );
+    }
+}
@@ -0,0 +1,483 @@
+// src/crypto_utils/dlcspecs_crypto_utils.rs
+
+use bitcoin::hashes::{sha256t_hash_newtype, Hash};
+use secp256k1_zkp::{Parity, PublicKey, Scalar, SecretKey, XOnlyPublicKey, SECP256K1};
+
+use crate::common::{error::CryptoUtilsError, types};
+use crate::crypto_utils::secp_utils::{hash_to_scalar, solve_private_key, BIP340Hash};
+use crate::crypto_utils::{
+    compute_increments, outcome_digit, sweep_anticipation_points, CryptoUtils,
+};
+
+// INFO: Anticipation points and attestations following dlcspecs oracle message format, so that they match those of
+// external DLC oracles. Oracle public key and nonces are x-only (BIP340), attestation is `s` part of BIP340 signature
+// of outcome string hashed with tag `DLC/oracle/attestation/v0`, so anticipation point is R + H(R, P, m) * P
+// (with R and P lifted to even Y). Numeric outcome is attested digit-by-digit (most significant first), each digit
+// as its decimal string under its own nonce, as with `DigitCryptoUtils`. Enumerated outcome is attested by its label.
+// Sessions use it with `MethodKind::DlcSpecs`, whose `RandIntOracle` attests with these utilities as well.
+// NOTE: `rust-dlc` (up to 0.7) hashes outcome strings with untagged SHA256, so its anticipation points differ.
+
+sha256t_hash_newtype! {
+    /// dlcspecs oracle attestation hash tag
+    pub struct AttestationHashTag = hash_str("DLC/oracle/attestation/v0");
+
+    /// dlcspecs oracle attestation hash
+    #[hash_newtype(forward)]
+    pub struct AttestationHash(_);
+}
+
+/// Combines per-digit anticipation points of dlcspecs oracle attestations.
+pub struct DlcSpecsCryptoUtils {
+    // digit_points[i][v] is anticipation point of i-th digit (most significant first) having value v
+    digit_points: Vec<Vec<PublicKey>>,
+    base: u8,
+    increments: Vec<Vec<Option<PublicKey>>>, // As in `DigitCryptoUtils`
+    public_key: PublicKey,
+    public_nonces: Vec<XOnlyPublicKey>,
+}
+
+/// Message oracle signs to attest to outcome string (digit or label).
+pub fn attestation_message(outcome: &str) -> [u8; 32] {
+    AttestationHash::hash(outcome.as_bytes()).to_byte_array()
+}
+
+// BIP340 challenge H(R, P, m) of message under x-only nonce and public key
+fn challenge(
+    public_key: &XOnlyPublicKey,
+    public_nonce: &XOnlyPublicKey,
+    message: &[u8; 32],
+) -> Scalar {
+    let mut buf = Vec::<u8>::with_capacity(96);
+    buf.extend(public_nonce.serialize());
+    buf.extend(public_key.serialize());
+    buf.extend(message);
+    hash_to_scalar(BIP340Hash::hash(&buf).to_byte_array())
+}
+
+// Point R + H(R, P, m) * P, i.e. s * G of BIP340 signature (R.x, s) of message
+fn compute_message_anticipation_point(
+    public_key: &XOnlyPublicKey,
+    public_nonce: &XOnlyPublicKey,
+    message: &[u8; 32],
+) -> Result<types::AnticipationPoint, CryptoUtilsError> {
+    let scalar = challenge(public_key, public_nonce, message);
+    let tweaked = public_key
+        .public_key(Parity::Even)
+        .mul_tweak(SECP256K1, &scalar)?;
+    Ok(public_nonce.public_key(Parity::Even).combine(&tweaked)?)
+}
+
+fn compute_outcome_anticipation_point(
+    public_key: &XOnlyPublicKey,
+    public_nonce: &XOnlyPublicKey,
+    outcome: &str,
+) -> Result<types::AnticipationPoint, CryptoUtilsError> {
+    compute_message_anticipation_point(public_key, public_nonce, &attestation_message(outcome))
+}
+
+// `s` part of BIP340 signature of message with given nonce (JUST FOR TESTING! IN REAL SCENARIO, ORACLE SHOULD DO THIS)
+fn compute_message_attestation(
+    private_key: &SecretKey,
+    private_nonce: &SecretKey,
+    message: &[u8; 32],
+) -> Result<types::Attestation, CryptoUtilsError> {
+    // BIP340 signs with secrets of even-Y points
+    let even_secret = |secret: &SecretKey| {
+        let (xonly, parity) = secret.x_only_public_key(SECP256K1);
+        match parity {
+            Parity::Even => (*secret, xonly),
+            Parity::Odd => (secret.negate(), xonly),
+        }
+    };
+    let (private_key, public_key) = even_secret(private_key);
+    let (private_nonce, public_nonce) = even_secret(private_nonce);
+    let scalar = challenge(&public_key, &public_nonce, message);
+    let tweaked = Scalar::from(private_key.mul_tweak(&scalar)?);
+    Ok(private_nonce.add_tweak(&tweaked)?)
+}
+
+// (JUST FOR TESTING! IN REAL SCENARIO, ORACLE SHOULD DO THIS)
+fn compute_outcome_attestation(
+    private_key: &SecretKey,
+    private_nonce: &SecretKey,
+    outcome: &str,
+) -> Result<types::Attestation, CryptoUtilsError> {
+    compute_message_attestation(private_key, private_nonce, &attestation_message(outcome))
+}
+
+impl DlcSpecsCryptoUtils {
+    pub fn nb_digits(&self) -> u8 {
+        self.digit_points.len() as u8
+    }
+
+    fn xonly_public_key(&self) -> XOnlyPublicKey {
+        self.public_key.x_only_public_key().0
+    }
+
+    // Strings oracle signs for outcome, one per digit (enumerated outcome has no digits, its label is signed instead)
+    fn outcome_strings(
+        &self,
+        outcome: &impl types::Outcome,
+    ) -> Result<Vec<String>, CryptoUtilsError> {
+        let nb_digits = self.nb_digits();
+        if nb_digits == 0 {
+            return Ok(vec![outcome.get_value().to_string()]);
+        }
+        (0..nb_digits)
+            .map(|i| Ok(outcome_digit(outcome, nb_digits - 1 - i, self.base)?.to_string()))
+            .collect()
+    }
+
+    // Whether outcome has no more digits than nb_digits
+    fn fits_nb_digits(&self, outcome: &impl types::Outcome) -> bool {
+        (self.nb_digits()..64).all(|i| outcome.get_digit(i, self.base) == Some(0))
+    }
+}
+
+impl CryptoUtils for DlcSpecsCryptoUtils {
+    // One nonce per digit, enumerated outcome is signed under single nonce
+    fn nb_nonces(nb_digits: u8) -> usize {
+        (nb_digits as usize).max(1)
+    }
+
+    /// Precomputes anticipation points for every value of each digit.
+    fn new(public_key: &PublicKey, public_nonces: &[PublicKey], nb_digits: u8, base: u8) -> Self {
+        let xonly_public_key = public_key.x_only_public_key().0;
+        let public_nonces: Vec<XOnlyPublicKey> = public_nonces[..Self::nb_nonces(nb_digits)]
+            .iter()
+            .map(|nonce| nonce.x_only_public_key().0)
+            .collect();
+        let digit_points = public_nonces[..nb_digits as usize]
+            .iter()
+            .map(|nonce| {
+                (0..base)
+                    .map(|digit| {
+                        compute_outcome_anticipation_point(
+                            &xonly_public_key,
+                            nonce,
+                            &digit.to_string(),
+                        )
+                        .expect("Failed to compute digit anticipation point")
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<PublicKey>>>();
+        let increments = compute_increments(nb_digits, base, |position, digit| {
+            Some(digit_points[nb_digits as usize - 1 - position][digit as usize])
+        });
+        Self {
+            digit_points,
+            base,
+            increments,
+            public_key: *public_key,
+            public_nonces,
+        }
+    }
+
+    // Will throw error if outcome has more digits than nb_digits
+    fn compute_anticipation_point(
+        &self,
+        outcome: &impl types::Outcome,
+    ) -> Result<types::AnticipationPoint, CryptoUtilsError> {
+        let nb_digits = self.nb_digits();
+        if nb_digits == 0 {
+            return compute_outcome_anticipation_point(
+                &self.xonly_public_key(),
+                &self.public_nonces[0],
+                &outcome.get_value().to_string(),
+            );
+        }
+        if !self.fits_nb_digits(outcome) {
+            // Enumerated outcomes have no digits at all
+            outcome_digit(outcome, 0, self.base)?;
+            return Err(CryptoUtilsError::OutcomeOutOfRange);
+        }
+        let selected_points = (0..nb_digits)
+            .map(|i| {
+                let digit = outcome_digit(outcome, nb_digits - 1 - i, self.base)?;
+                Ok(&self.digit_points[i as usize][digit as usize])
+            })
+            .collect::<Result<Vec<&PublicKey>, CryptoUtilsError>>()?;
+        Ok(PublicKey::combine_keys(&selected_points)?)
+    }
+
+    fn compute_anticipation_points<'a, O: types::Outcome + 'a>(
+        &self,
+        outcomes: impl Iterator<Item = &'a O>,
+    ) -> Result<Vec<types::AnticipationPoint>, CryptoUtilsError> {
+        sweep_anticipation_points(
+            outcomes,
+            self.nb_digits(),
+            self.base,
+            &self.increments,
+            |outcome| self.nb_digits() > 0 && self.fits_nb_digits(outcome),
+            |outcome| self.compute_anticipation_point(outcome),
+        )
+    }
+
+    /// Attestations of each digit of outcome (most significant first), one per digit nonce, or of enumerated outcome.
+    /// (JUST FOR TESTING! IN REAL SCENARIO, ORACLE SHOULD DO THIS)
+    fn compute_attestations(
+        &self,
+        private_key: &SecretKey,
+        private_nonces: &[SecretKey],
+        outcome: &impl types::Outcome,
+    ) -> Result<Vec<types::Attestation>, CryptoUtilsError> {
+        self.outcome_strings(outcome)?
+            .iter()
+            .zip(private_nonces)
+            .map(|(outcome, nonce)| compute_outcome_attestation(private_key, nonce, outcome))
+            .collect()
+    }
+
+    // The same nonce used for different strings reveals private key (with even Y, so we may need to negate it)
+    fn extract_private_key(
+        &self,
+        (attestations_a, outcome_a): (&[types::Attestation], &impl types::Outcome),
+        (attestations_b, outcome_b): (&[types::Attestation], &impl types::Outcome),
+    ) -> Option<SecretKey> {
+        let xonly_public_key = self.xonly_public_key();
+        let private_key = self
+            .outcome_strings(outcome_a)
+            .ok()?
+            .iter()
+            .zip(self.outcome_strings(outcome_b).ok()?.iter())
+            .zip(&self.public_nonces)
+            .enumerate()
+            .find_map(|(i, ((string_a, string_b), nonce))| {
+                if string_a == string_b {
+                    return None;
+                }
+                solve_private_key(
+                    (
+                        attestations_a.get(i)?,
+                        &challenge(&xonly_public_key, nonce, &attestation_message(string_a)),
+                    ),
+                    (
+                        attestations_b.get(i)?,
+                        &challenge(&xonly_public_key, nonce, &attestation_message(string_b)),
+                    ),
+                )
+            })?;
+        if private_key.public_key(SECP256K1) == self.public_key {
+            Some(private_key)
+        } else {
+            Some(private_key.negate())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{OutcomeEnum, OutcomeU32};
+    use crate::crypto_utils::combine_attestations;
+    use secp256k1_zkp::{rand::thread_rng, schnorr, Message};
+    use std::str::FromStr;
+
+    // BIP340 signing test vectors 0-3, verbatim from `bip-0340/test-vectors.csv` of the BIPs repository:
+    // (secret key, x-only public key, message, signature). dlcspecs attestation is `s` of such a signature of
+    // tagged outcome hash, with `R.x` being the announced nonce, so these check our challenge and even-Y handling.
+    const BIP340_VECTORS: [(&str, &str, &str, &str); 4] = [
+        (
+            "0000000000000000000000000000000000000000000000000000000000000003",
+            "F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA821525F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0",
+        ),
+        (
+            "B7E151628AED2A6ABF7158809CF4F3C762E7160F38B4DA56A784D9045190CFEF",
+            "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+            "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+            "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE33418906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A",
+        ),
+        (
+            "C90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B14E5C9",
+            "DD308AFEC5777E13121FA72B9CC1B7CC0139715309B086C960E18FD969774EB8",
+            "7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C",
+            "5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1BAB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7",
+        ),
+        (
+            "0B432B2677937381AEF05BB02A66ECD012773062CF3FA2549E44F58ED2401710",
+            "25D1DFF95105F5253C4022F628A996AD3A0D95FBF21D468A1B33F8C160D8F517",
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+            "7EB0509757E246F19449885651611CB965ECC1A187DD51B64FDA1EDC9637D5EC97582B9CB13DB3933705B32BA982AF5AF25FD78881EBB32771FC5922EFC66EA3",
+        ),
+    ];
+
+    fn from_hex<const N: usize>(hex: &str) -> [u8; N] {
+        let mut bytes = [0u8; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_bip340_vectors() {
+        for (private_key, public_key, message, signature) in BIP340_VECTORS {
+            let private_key = SecretKey::from_str(private_key).unwrap();
+            let public_key = XOnlyPublicKey::from_str(public_key).unwrap();
+            let message: [u8; 32] = from_hex(message);
+            let signature: [u8; 64] = from_hex(signature);
+            let public_nonce = XOnlyPublicKey::from_slice(&signature[..32]).unwrap();
+            let attestation = SecretKey::from_slice(&signature[32..]).unwrap();
+            assert_eq!(private_key.x_only_public_key(SECP256K1).0, public_key);
+
+            // Anticipation point of the message is s * G
+            assert_eq!(
+                compute_message_anticipation_point(&public_key, &public_nonce, &message).unwrap(),
+                attestation.public_key(SECP256K1)
+            );
+
+            // Nonce secret k = s - e * d (d of even-Y key) has R as its point, and attesting with it gives s back
+            let even_private_key = match private_key.x_only_public_key(SECP256K1).1 {
+                Parity::Even => private_key,
+                Parity::Odd => private_key.negate(),
+            };
+            let e_d = even_private_key
+                .mul_tweak(&challenge(&public_key, &public_nonce, &message))
+                .unwrap();
+            let private_nonce = attestation.add_tweak(&Scalar::from(e_d.negate())).unwrap();
+            assert_eq!(
+                private_nonce.x_only_public_key(SECP256K1),
+                (public_nonce, Parity::Even)
+            );
+            assert_eq!(
+                compute_message_attestation(&private_key, &private_nonce, &message).unwrap(),
+                attestation
+            );
+        }
+    }
+
+    #[test]
+    fn test_attestation_message_is_tagged_hash() {
+        // SHA256(SHA256(tag) || SHA256(tag) || outcome), see BIP340 tagged hashes
+        use sha2::{Digest, Sha256};
+        let tag_hash = Sha256::digest(b"DLC/oracle/attestation/v0");
+        for outcome in ["0", "1", "team_a_wins"] {
+            let expected: [u8; 32] = Sha256::new()
+                .chain_update(tag_hash)
+                .chain_update(tag_hash)
+                .chain_update(outcome.as_bytes())
+                .finalize()
+                .into();
+            assert_eq!(attestation_message(outcome), expected);
+        }
+    }
+
+    #[test]
+    fn test_attestations_are_bip340_signatures() {
+        let mut rng = thread_rng();
+        let nb_digits = 5;
+        let private_key = SecretKey::new(&mut rng);
+        let private_nonces: Vec<SecretKey> =
+            (0..nb_digits).map(|_| SecretKey::new(&mut rng)).collect();
+        let public_nonces: Vec<PublicKey> = private_nonces
+            .iter()
+            .map(|nonce| nonce.public_key(SECP256K1))
+            .collect();
+        let xonly_public_key = private_key.x_only_public_key(SECP256K1).0;
+        let utils = DlcSpecsCryptoUtils::new(
+            &private_key.public_key(SECP256K1),
+            &public_nonces,
+            nb_digits,
+            10,
+        );
+
+        // 7 0 4 1 9 in decimal digits, each is (R.x, s) signature of external oracle
+        let outcome = OutcomeU32::from(70419);
+        let attestations = utils
+            .compute_attestations(&private_key, &private_nonces, &outcome)
+            .unwrap();
+        for ((attestation, nonce), digit) in
+            attestations.iter().zip(&public_nonces).zip("70419".chars())
+        {
+            let mut signature = [0u8; 64];
+            signature[..32].copy_from_slice(&nonce.x_only_public_key().0.serialize());
+            signature[32..].copy_from_slice(&attestation.secret_bytes());
+            let message =
+                Message::from_digest_slice(&attestation_message(&digit.to_string())).unwrap();
+            assert!(SECP256K1
+                .verify_schnorr(
+                    &schnorr::Signature::from_slice(&signature).unwrap(),
+                    &message,
+                    &xonly_public_key
+                )
+                .is_ok());
+        }
+        assert_eq!(
+            combine_attestations(&attestations)
+                .unwrap()
+                .public_key(SECP256K1),
+            utils.compute_anticipation_point(&outcome).unwrap()
+        );
+
+        // Incremental sweep gives the same points as computing them one by one
+        let outcomes: Vec<OutcomeU32> = (70380..70420).map(OutcomeU32::from).collect();
+        let points = utils.compute_anticipation_points(outcomes.iter()).unwrap();
+        for (outcome, point) in outcomes.iter().zip(points) {
+            assert_eq!(point, utils.compute_anticipation_point(outcome).unwrap());
+        }
+
+        // Enumerated outcome is signed by its label under the first nonce
+        let utils = DlcSpecsCryptoUtils::new(
+            &private_key.public_key(SECP256K1),
+            &public_nonces[..1],
+            0,
+            2,
+        );
+        let outcome = OutcomeEnum::new(0, "team_a_wins".to_string());
+        let attestations = utils
+            .compute_attestations(&private_key, &private_nonces[..1], &outcome)
+            .unwrap();
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&public_nonces[0].x_only_public_key().0.serialize());
+        signature[32..].copy_from_slice(&attestations[0].secret_bytes());
+        assert!(SECP256K1
+            .verify_schnorr(
+                &schnorr::Signature::from_slice(&signature).unwrap(),
+                &Message::from_digest(attestation_message("team_a_wins")),
+                &xonly_public_key
+            )
+            .is_ok());
+        assert_eq!(
+            attestations[0].public_key(SECP256K1),
+            utils.compute_anticipation_point(&outcome).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_extract_private_key() {
+        let mut rng = thread_rng();
+        let private_key = SecretKey::new(&mut rng);
+        // Oracle key with odd Y
+        let private_key = match private_key.x_only_public_key(SECP256K1).1 {
+            Parity::Odd => private_key,
+            Parity::Even => private_key.negate(),
+        };
+        let private_nonces: Vec<SecretKey> = (0..2).map(|_| SecretKey::new(&mut rng)).collect();
+        let public_nonces: Vec<PublicKey> = private_nonces
+            .iter()
+            .map(|nonce| nonce.public_key(SECP256K1))
+            .collect();
+        let utils =
+            DlcSpecsCryptoUtils::new(&private_key.public_key(SECP256K1), &public_nonces, 2, 2);
+        let (outcome_a, outcome_b) = (OutcomeU32::from(2), OutcomeU32::from(3));
+        let attestations_a = utils
+            .compute_attestations(&private_key, &private_nonces, &outcome_a)
+            .unwrap();
+        let attestations_b = utils
+            .compute_attestations(&private_key, &private_nonces, &outcome_b)
+            .unwrap();
+        // Oracle key has odd Y, so it is recovered negated and flipped back
+        assert_eq!(
+            utils.extract_private_key((&attestations_a, &outcome_a), (&attestations_b, &outcome_b)),
+            Some(private_key)
+        );
+        assert_eq!(
+            utils.extract_private_key((&attestations_a, &outcome_a), (&attestations_a, &outcome_a)),
+            None
+        );
+    }
+}
@@ -1,8 +1,8 @@
 // src/crypto_utils/mod.rs
 
-use secp256k1_zkp::{PublicKey, SecretKey};
+use secp256k1_zkp::{PublicKey, Scalar, SecretKey, SECP256K1};
 
-use crate::common::types;
+use crate::common::{error::CryptoUtilsError, types};
 
 // INFO: This module should be created probably as some independent crate from client and oracle code. It will be included both in client and oracle.
 // By doing this, it will be ensured, that both client and oracle use same crypto utility to cumpute anticipation point and to compute attestation on oracle side.
@@ -11,23 +11,222 @@ use crate::common::types;
 // This means, CryptoUtils must be same on Oracle and client side, should this project ever be extended to more than just benchmarking usecase.
 
 pub trait CryptoUtils {
-    fn new(public_key: &PublicKey, public_nonce: &PublicKey) -> Self
+    /// Number of nonces oracle has to announce for an event whose outcomes have `nb_digits` digits.
+    fn nb_nonces(nb_digits: u8) -> usize
     where
         Self: Sized;
 
+    /// Creates crypto utils for an oracle event whose outcomes are represented with `nb_digits` digits in given base.
+    /// Expects `nb_nonces(nb_digits)` public nonces.
+    fn new(public_key: &PublicKey, public_nonces: &[PublicKey], nb_digits: u8, base: u8) -> Self
+    where
+        Self: Sized;
+
+    /// Same as `new`, with precomputed tables of `window_bits` bits for methods precomputing sums of digit points
+    /// (see `BasisCryptoUtils`). Methods without such tables ignore the window.
+    fn with_window(
+        public_key: &PublicKey,
+        public_nonces: &[PublicKey],
+        nb_digits: u8,
+        base: u8,
+        _window_bits: u8,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(public_key, public_nonces, nb_digits, base)
+    }
+
     fn compute_anticipation_point(
         &self,
         outcome: &impl types::Outcome,
-    ) -> Result<types::AnticipationPoint, secp256k1_zkp::Error>;
+    ) -> Result<types::AnticipationPoint, CryptoUtilsError>;
+
+    /// Anticipation points of given outcomes (in the same order), meant for sweeps over contiguous outcome ranges.
+    /// Methods summing per-digit points override it, so that point of outcome right after the previous one
+    /// is derived from the previous point with a single point addition. Range can be split into chunks
+    /// computed independently (e.g. in parallel), each chunk pays only for computing its first point in full.
+    fn compute_anticipation_points<'a, O: types::Outcome + 'a>(
+        &self,
+        outcomes: impl Iterator<Item = &'a O>,
+    ) -> Result<Vec<types::AnticipationPoint>, CryptoUtilsError> {
+        outcomes
+            .map(|outcome| self.compute_anticipation_point(outcome))
+            .collect()
+    }
 
-    fn compute_attestation(
+    /// Attestations oracle publishes for the outcome (either one for whole outcome, or one per digit).
+    /// Their sum (see `combine_attestations`) is the secret of outcome's anticipation point.
+    fn compute_attestations(
         &self,
         private_key: &SecretKey,
-        private_nonce: &SecretKey,
+        private_nonces: &[SecretKey],
         outcome: &impl types::Outcome,
-    ) -> Result<types::Attestation, secp256k1_zkp::Error>;
+    ) -> Result<Vec<types::Attestation>, CryptoUtilsError>;
+
+    /// Oracle private key revealed by attestations of two different outcomes reusing the same nonces (equivocation).
+    /// None if attestations don't reveal it, e.g. when each of them sums attestations under several nonces.
+    fn extract_private_key(
+        &self,
+        _attestations_a: (&[types::Attestation], &impl types::Outcome),
+        _attestations_b: (&[types::Attestation], &impl types::Outcome),
+    ) -> Option<SecretKey> {
+        None
+    }
+}
+
+/// Anticipation point of outcome attested jointly by several oracles (sum of their anticipation points).
+pub fn compute_joint_anticipation_point<CU: CryptoUtils>(
+    crypto_utils_engines: &[&CU],
+    outcome: &impl types::Outcome,
+) -> Result<types::AnticipationPoint, CryptoUtilsError> {
+    match crypto_utils_engines {
+        [] => return Err(CryptoUtilsError::NoOracles),
+        [engine] => return engine.compute_anticipation_point(outcome),
+        _ => {}
+    }
+    let points = crypto_utils_engines
+        .iter()
+        .map(|engine| engine.compute_anticipation_point(outcome))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(PublicKey::combine_keys(&points.iter().collect::<Vec<_>>())?)
+}
+
+/// Anticipation points of outcomes attested jointly by several oracles (see `compute_anticipation_points`).
+pub fn compute_joint_anticipation_points<'a, CU: CryptoUtils, O: types::Outcome + 'a>(
+    crypto_utils_engines: &[&CU],
+    outcomes: impl Iterator<Item = &'a O> + Clone,
+) -> Result<Vec<types::AnticipationPoint>, CryptoUtilsError> {
+    let (first, rest) = crypto_utils_engines
+        .split_first()
+        .ok_or(CryptoUtilsError::NoOracles)?;
+    let mut points = first.compute_anticipation_points(outcomes.clone())?;
+    for engine in rest {
+        for (point, other) in points
+            .iter_mut()
+            .zip(engine.compute_anticipation_points(outcomes.clone())?)
+        {
+            *point = point.combine(&other)?;
+        }
+    }
+    Ok(points)
+}
+
+// Anticipation points of outcomes, where point of outcome right after the previous one is previous point plus increment point
+// of the incremented digit (see `compute_increments`). Outcomes whose point isn't a sum of their digit points (`is_digit_sum`),
+// outcomes following them and outcomes not following the previous one have their point computed in full.
+fn sweep_anticipation_points<'a, O: types::Outcome + 'a>(
+    outcomes: impl Iterator<Item = &'a O>,
+    nb_digits: u8,
+    base: u8,
+    increments: &[Vec<Option<PublicKey>>],
+    is_digit_sum: impl Fn(&O) -> bool,
+    compute_point: impl Fn(&O) -> Result<types::AnticipationPoint, CryptoUtilsError>,
+) -> Result<Vec<types::AnticipationPoint>, CryptoUtilsError> {
+    let mut points = Vec::with_capacity(outcomes.size_hint().0);
+    let mut previous: Option<(Vec<u8>, types::AnticipationPoint)> = None;
+    for outcome in outcomes {
+        let digits = outcome_digits(outcome, nb_digits, base)?;
+        let digit_sum = is_digit_sum(outcome);
+        let increment = match &previous {
+            Some((previous_digits, previous_point)) if digit_sum => {
+                incremented_digit(previous_digits, &digits, base).and_then(|position| {
+                    let increment_point = increments[position][previous_digits[position] as usize];
+                    increment_point.map(|increment_point| (previous_point, increment_point))
+                })
+            }
+            _ => None,
+        };
+        let point = match increment {
+            Some((previous_point, increment_point)) => previous_point.combine(&increment_point)?,
+            None => compute_point(outcome)?,
+        };
+        previous = digit_sum.then_some((digits, point));
+        points.push(point);
+    }
+    Ok(points)
+}
+
+// increments[p][v] is point to add to anticipation point of outcome, whose digit p (least significant first) has value v
+// and lower digits have value base - 1, to get point of the next outcome. `digit_point(p, v)` is point of digit p having
+// value v, None if digit isn't part of the sum (as zero digits of basis method). Increment is None if it sums to point
+// at infinity (e.g. oracle reusing nonce for several digits), such outcomes are then computed in full.
+fn compute_increments(
+    nb_digits: u8,
+    base: u8,
+    digit_point: impl Fn(usize, u8) -> Option<PublicKey>,
+) -> Vec<Vec<Option<PublicKey>>> {
+    let combine =
+        |keys: &[PublicKey]| PublicKey::combine_keys(&keys.iter().collect::<Vec<_>>()).ok();
+    let mut carry: Vec<PublicKey> = Vec::new(); // Added when all lower digits wrap from base - 1 to 0 (empty if infinity)
+    (0..nb_digits as usize)
+        .map(|position| {
+            let row = (0..base - 1)
+                .map(|value| {
+                    let mut keys = carry.clone();
+                    keys.extend(digit_point(position, value + 1));
+                    keys.extend(digit_point(position, value).map(|point| point.negate(SECP256K1)));
+                    combine(&keys)
+                })
+                .collect();
+            carry.extend(digit_point(position, 0));
+            carry.extend(digit_point(position, base - 1).map(|point| point.negate(SECP256K1)));
+            carry = combine(&carry).into_iter().collect();
+            row
+        })
+        .collect()
+}
+
+// Digit of outcome at given position, enumerated outcomes have no digits
+fn outcome_digit(
+    outcome: &impl types::Outcome,
+    position: u8,
+    base: u8,
+) -> Result<u8, CryptoUtilsError> {
+    outcome
+        .get_digit(position, base)
+        .ok_or(CryptoUtilsError::OutcomeHasNoDigits)
+}
+
+// Digits of outcome, least significant first
+fn outcome_digits(
+    outcome: &impl types::Outcome,
+    nb_digits: u8,
+    base: u8,
+) -> Result<Vec<u8>, CryptoUtilsError> {
+    (0..nb_digits)
+        .map(|position| outcome_digit(outcome, position, base))
+        .collect()
+}
+
+// Position of digit incremented when counting from `previous` to `next` outcome (digits below it wrap from base - 1 to 0),
+// or None if `next` doesn't directly follow `previous`
+fn incremented_digit(previous: &[u8], next: &[u8], base: u8) -> Option<usize> {
+    let position = previous.iter().position(|&digit| digit < base - 1)?;
+    (next[..position].iter().all(|&digit| digit == 0)
+        && next[position] == previous[position] + 1
+        && previous[position + 1..] == next[position + 1..])
+        .then_some(position)
+}
+
+/// Combines published attestations into adaptor secret of attested outcome by summing them.
+pub fn combine_attestations(
+    attestations: &[types::Attestation],
+) -> Result<types::Attestation, secp256k1_zkp::Error> {
+    let (first, rest) = attestations
+        .split_first()
+        .ok_or(secp256k1_zkp::UpstreamError::InvalidSecretKey)?;
+    let mut combined = *first;
+    for attestation in rest {
+        combined = combined.add_tweak(&Scalar::from(*attestation))?;
+    }
+    Ok(combined)
 }
 
 pub mod basis_crypto_utils;
+pub mod digit_crypto_utils;
+pub mod dlcspecs_crypto_utils;
 pub mod secp_utils;
 pub mod simple_crypto_utils;
+#[cfg(feature = "schnorr-fun")]
+pub mod simple_fun_crypto_utils;
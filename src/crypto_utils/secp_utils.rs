@@ -1,7 +1,9 @@
 use bitcoin::hashes::{sha256t_hash_newtype, Hash};
-use secp256k1_zkp::{PublicKey, Scalar, Secp256k1, SecretKey, Signing, Verification};
+use secp256k1_zkp::{
+    constants::CURVE_ORDER, PublicKey, Scalar, Secp256k1, SecretKey, Signing, Verification,
+};
 
-use crate::common::types;
+use crate::common::{error::CryptoUtilsError, types};
 
 const BIP340_MIDSTATE: [u8; 32] = [
     0x9c, 0xec, 0xba, 0x11, 0x23, 0x92, 0x53, 0x81, 0x11, 0x67, 0x91, 0x12, 0xd1, 0x62, 0x7e, 0x0f,
@@ -23,9 +25,9 @@ pub(in crate::crypto_utils) fn schnorrsig_compute_anticipation_point<C: Verifica
     public_key: &PublicKey,
     public_nonce: &PublicKey,
     outcome: &impl types::Outcome,
-) -> Result<types::AnticipationPoint, secp256k1_zkp::Error> {
-    let hash = create_schnorr_hash(public_key, public_nonce, outcome);
-    let scalar = Scalar::from_be_bytes(hash).unwrap();
+) -> Result<types::AnticipationPoint, CryptoUtilsError> {
+    let hash = create_schnorr_hash(&public_key.serialize(), &public_nonce.serialize(), outcome);
+    let scalar = hash_to_scalar(hash);
     let tweaked = public_key.mul_tweak(secp, &scalar)?;
     Ok(public_nonce.combine(&tweaked)?)
 }
@@ -36,26 +38,188 @@ pub(in crate::crypto_utils) fn schnorrsig_compute_oracle_attestation<C: Verifica
     private_key: &SecretKey,
     private_nonce: &SecretKey,
     outcome: &impl types::Outcome,
-) -> Result<types::Attestation, secp256k1_zkp::Error> {
+) -> Result<types::Attestation, CryptoUtilsError> {
     let hash = create_schnorr_hash(
-        &PublicKey::from_secret_key(secp, private_key),
-        &PublicKey::from_secret_key(secp, private_nonce),
+        &PublicKey::from_secret_key(secp, private_key).serialize(),
+        &PublicKey::from_secret_key(secp, private_nonce).serialize(),
         outcome,
     );
-    let scalar = Scalar::from_be_bytes(hash).unwrap();
+    let scalar = hash_to_scalar(hash);
     let tweaked = Scalar::from(private_key.mul_tweak(&scalar)?);
     Ok(private_nonce.add_tweak(&tweaked)?)
 }
 
-/// Create a BIP340 hash for the given digit index (which is 1), nonce and public key.
-fn create_schnorr_hash(
+/// Recover oracle private key from attestations of two different outcomes under the same nonce (see `solve_private_key`).
+pub(in crate::crypto_utils) fn schnorrsig_extract_private_key(
     public_key: &PublicKey,
     public_nonce: &PublicKey,
+    (attestation_a, outcome_a): (&types::Attestation, &impl types::Outcome),
+    (attestation_b, outcome_b): (&types::Attestation, &impl types::Outcome),
+) -> Option<SecretKey> {
+    let challenge_a = outcome_challenge(public_key, public_nonce, outcome_a);
+    let challenge_b = outcome_challenge(public_key, public_nonce, outcome_b);
+    solve_private_key((attestation_a, &challenge_a), (attestation_b, &challenge_b))
+}
+
+/// Private key x from attestations s = k + e * x of two challenges e under the same nonce, x = (s_a - s_b) / (e_a - e_b).
+/// None if challenges (or attestations) are the same, i.e. if either difference is zero.
+pub(in crate::crypto_utils) fn solve_private_key(
+    (attestation_a, challenge_a): (&types::Attestation, &Scalar),
+    (attestation_b, challenge_b): (&types::Attestation, &Scalar),
+) -> Option<SecretKey> {
+    // `SecretKey` can't be zero, so zero differences fail here rather than in the inversion
+    let attestation_diff = attestation_a
+        .add_tweak(&Scalar::from(attestation_b.negate()))
+        .ok()?;
+    let challenge_b = SecretKey::from_slice(&challenge_b.to_be_bytes()).ok()?;
+    let challenge_diff = SecretKey::from_slice(&challenge_a.to_be_bytes())
+        .ok()?
+        .add_tweak(&Scalar::from(challenge_b.negate()))
+        .ok()?;
+    attestation_diff
+        .mul_tweak(&Scalar::from(invert(&challenge_diff)?))
+        .ok()
+}
+
+// Challenge of outcome, the scalar its attestation multiplies private key with
+fn outcome_challenge(
+    public_key: &PublicKey,
+    public_nonce: &PublicKey,
+    outcome: &impl types::Outcome,
+) -> Scalar {
+    hash_to_scalar(create_schnorr_hash(
+        &public_key.serialize(),
+        &public_nonce.serialize(),
+        outcome,
+    ))
+}
+
+/// Hash as scalar reduced modulo group order n, as BIP340 computes challenges.
+/// Hash is below 2^256 < 2n, so at most one subtraction of n is needed.
+pub(crate) fn hash_to_scalar(hash: [u8; 32]) -> Scalar {
+    Scalar::from_be_bytes(hash).unwrap_or_else(|_| {
+        let mut reduced = [0u8; 32];
+        let mut borrow = false;
+        for i in (0..32).rev() {
+            let (diff, borrow_a) = hash[i].overflowing_sub(CURVE_ORDER[i]);
+            let (diff, borrow_b) = diff.overflowing_sub(borrow as u8);
+            reduced[i] = diff;
+            borrow = borrow_a || borrow_b;
+        }
+        Scalar::from_be_bytes(reduced).expect("Hash minus n is below n")
+    })
+}
+
+// Group order minus 2
+const N_MINUS_2: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x3f,
+];
+
+// Modular inverse as a^(n-2) (Fermat), as secp256k1 doesn't expose scalar inversion.
+// Products of non-zero scalars modulo prime n are non-zero, so None is never returned in practice.
+fn invert(scalar: &SecretKey) -> Option<SecretKey> {
+    let mut inverse: Option<SecretKey> = None;
+    for byte in N_MINUS_2 {
+        for bit in (0..8).rev() {
+            if let Some(x) = inverse {
+                inverse = Some(x.mul_tweak(&Scalar::from(x)).ok()?);
+            }
+            if (byte >> bit) & 1 == 1 {
+                inverse = Some(match inverse {
+                    None => *scalar,
+                    Some(x) => x.mul_tweak(&Scalar::from(*scalar)).ok()?,
+                });
+            }
+        }
+    }
+    inverse
+}
+
+/// Create a BIP340 hash for the given digit index (which is 1), nonce and public key (both compressed).
+pub(in crate::crypto_utils) fn create_schnorr_hash(
+    public_key: &[u8; 33],
+    public_nonce: &[u8; 33],
     outcome: &impl types::Outcome,
 ) -> [u8; 32] {
     let mut buf = Vec::<u8>::new();
-    buf.extend(public_nonce.serialize());
-    buf.extend(public_key.serialize());
+    buf.extend(public_nonce);
+    buf.extend(public_key);
     buf.extend(outcome.serialize());
     BIP340Hash::hash(&buf).to_byte_array()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invert() {
+        let mut rng = rand::thread_rng();
+        let mut one_bytes = [0; 32];
+        one_bytes[31] = 1;
+        let one = SecretKey::from_slice(&one_bytes).unwrap();
+        for _ in 0..4 {
+            let scalar = SecretKey::new(&mut rng);
+            let inverse = invert(&scalar).unwrap();
+            assert_eq!(scalar.mul_tweak(&Scalar::from(inverse)).unwrap(), one);
+        }
+        assert_eq!(invert(&one), Some(one));
+    }
+
+    #[test]
+    fn test_solve_private_key() {
+        let mut rng = rand::thread_rng();
+        let (private_key, nonce) = (SecretKey::new(&mut rng), SecretKey::new(&mut rng));
+        let attest = |challenge: &Scalar| {
+            nonce
+                .add_tweak(&Scalar::from(private_key.mul_tweak(challenge).unwrap()))
+                .unwrap()
+        };
+        let (challenge_a, challenge_b) = (Scalar::random(), Scalar::random());
+        let (attestation_a, attestation_b) = (attest(&challenge_a), attest(&challenge_b));
+        assert_eq!(
+            solve_private_key(
+                (&attestation_a, &challenge_a),
+                (&attestation_b, &challenge_b)
+            ),
+            Some(private_key)
+        );
+
+        // Zero challenge difference (with equal or different attestations) has no solution
+        assert_eq!(
+            solve_private_key(
+                (&attestation_a, &challenge_a),
+                (&attestation_a, &challenge_a)
+            ),
+            None
+        );
+        assert_eq!(
+            solve_private_key(
+                (&attestation_a, &challenge_a),
+                (&attestation_b, &challenge_a)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_hash_to_scalar() {
+        let hash = [0x42; 32];
+        assert_eq!(hash_to_scalar(hash), Scalar::from_be_bytes(hash).unwrap());
+        // Hashes of n and above wrap around
+        assert_eq!(hash_to_scalar(CURVE_ORDER), Scalar::ZERO);
+        let mut n_plus_one = CURVE_ORDER;
+        n_plus_one[31] += 1;
+        assert_eq!(hash_to_scalar(n_plus_one), Scalar::ONE);
+        let mut expected = [0; 32];
+        expected[15..].copy_from_slice(&[
+            0x01, 0x45, 0x51, 0x23, 0x19, 0x50, 0xb7, 0x5f, 0xc4, 0x40, 0x2d, 0xa1, 0x73, 0x2f,
+            0xc9, 0xbe, 0xbe,
+        ]);
+        assert_eq!(
+            hash_to_scalar([0xff; 32]),
+            Scalar::from_be_bytes(expected).unwrap()
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{common::types, crypto_utils::secp_utils::*};
+use crate::{
+    common::{error::CryptoUtilsError, types},
+    crypto_utils::secp_utils::*,
+};
 use secp256k1_zkp::{PublicKey, SecretKey, SECP256K1};
 
 use super::CryptoUtils;
@@ -9,17 +12,23 @@ pub struct SimpleCryptoUtils {
 }
 
 impl CryptoUtils for SimpleCryptoUtils {
-    fn new(public_key: &PublicKey, public_nonce: &PublicKey) -> Self {
+    // Whole outcome is signed at once, so one nonce is enough
+    fn nb_nonces(_nb_digits: u8) -> usize {
+        1
+    }
+
+    // Simple method hashes whole outcome, so number of digits and their base are irrelevant here
+    fn new(public_key: &PublicKey, public_nonces: &[PublicKey], _nb_digits: u8, _base: u8) -> Self {
         Self {
             public_key: *public_key,
-            public_nonce: *public_nonce,
+            public_nonce: public_nonces[0],
         }
     }
 
     fn compute_anticipation_point(
         &self,
         outcome: &impl types::Outcome,
-    ) -> Result<types::AnticipationPoint, secp256k1_zkp::Error> {
+    ) -> Result<types::AnticipationPoint, CryptoUtilsError> {
         schnorrsig_compute_anticipation_point(
             SECP256K1,
             &self.public_key,
@@ -28,12 +37,31 @@ impl CryptoUtils for SimpleCryptoUtils {
         )
     }
 
-    fn compute_attestation(
+    fn compute_attestations(
         &self,
         private_key: &SecretKey,
-        private_nonce: &SecretKey,
+        private_nonces: &[SecretKey],
         outcome: &impl types::Outcome,
-    ) -> Result<types::Attestation, secp256k1_zkp::Error> {
-        schnorrsig_compute_oracle_attestation(SECP256K1, private_key, private_nonce, outcome)
+    ) -> Result<Vec<types::Attestation>, CryptoUtilsError> {
+        Ok(vec![schnorrsig_compute_oracle_attestation(
+            SECP256K1,
+            private_key,
+            &private_nonces[0],
+            outcome,
+        )?])
+    }
+
+    // Both outcomes are attested under the only nonce
+    fn extract_private_key(
+        &self,
+        (attestations_a, outcome_a): (&[types::Attestation], &impl types::Outcome),
+        (attestations_b, outcome_b): (&[types::Attestation], &impl types::Outcome),
+    ) -> Option<SecretKey> {
+        schnorrsig_extract_private_key(
+            &self.public_key,
+            &self.public_nonce,
+            (attestations_a.first()?, outcome_a),
+            (attestations_b.first()?, outcome_b),
+        )
     }
 }
@@ -0,0 +1,146 @@
+use crate::{
+    common::{error::CryptoUtilsError, types},
+    crypto_utils::secp_utils::create_schnorr_hash,
+};
+use schnorr_fun::fun::{g, marker::*, s, Point, Scalar, G};
+use secp256k1_zkp::{PublicKey, SecretKey};
+
+use super::CryptoUtils;
+
+// INFO: Simple method computed with `secp256kfun` points instead of `secp256k1_zkp` (pure-Rust stack, together with
+// `SchnorrFunAdaptorSignatureScheme`). Anticipation points and attestations are the same as of `SimpleCryptoUtils`,
+// so both can be used with the same oracle. Only resulting points are converted to `secp256k1_zkp` types.
+
+pub struct SimpleFunCryptoUtils {
+    public_key: Point,
+    public_nonce: Point,
+    // Compressed encodings hashed together with outcome
+    public_key_bytes: [u8; 33],
+    public_nonce_bytes: [u8; 33],
+}
+
+// Hash of outcome as scalar (as in `schnorrsig_compute_anticipation_point`)
+fn outcome_challenge(
+    public_key_bytes: &[u8; 33],
+    public_nonce_bytes: &[u8; 33],
+    outcome: &impl types::Outcome,
+) -> Scalar<Public, Zero> {
+    Scalar::from_bytes(create_schnorr_hash(
+        public_key_bytes,
+        public_nonce_bytes,
+        outcome,
+    ))
+    .expect("Hash is a valid scalar")
+}
+
+fn to_fun_point(public_key: &PublicKey) -> Point {
+    Point::from_bytes_uncompressed(public_key.serialize_uncompressed())
+        .expect("Public key is a valid point")
+}
+
+fn to_fun_scalar(secret_key: &SecretKey) -> Scalar {
+    Scalar::from_bytes(secret_key.secret_bytes()).expect("Secret key is a valid non-zero scalar")
+}
+
+impl CryptoUtils for SimpleFunCryptoUtils {
+    // Whole outcome is signed at once, so one nonce is enough
+    fn nb_nonces(_nb_digits: u8) -> usize {
+        1
+    }
+
+    // Simple method hashes whole outcome, so number of digits and their base are irrelevant here
+    fn new(public_key: &PublicKey, public_nonces: &[PublicKey], _nb_digits: u8, _base: u8) -> Self {
+        Self {
+            public_key: to_fun_point(public_key),
+            public_nonce: to_fun_point(&public_nonces[0]),
+            public_key_bytes: public_key.serialize(),
+            public_nonce_bytes: public_nonces[0].serialize(),
+        }
+    }
+
+    fn compute_anticipation_point(
+        &self,
+        outcome: &impl types::Outcome,
+    ) -> Result<types::AnticipationPoint, CryptoUtilsError> {
+        let challenge =
+            outcome_challenge(&self.public_key_bytes, &self.public_nonce_bytes, outcome);
+        let point = g!(self.public_nonce + challenge * self.public_key)
+            .normalize()
+            .non_zero()
+            .ok_or(secp256k1_zkp::UpstreamError::InvalidPublicKeySum)?;
+        Ok(PublicKey::from_slice(&point.to_bytes_uncompressed())?)
+    }
+
+    /// (JUST FOR TESTING! IN REAL SCENARIO, ORACLE SHOULD DO THIS)
+    fn compute_attestations(
+        &self,
+        private_key: &SecretKey,
+        private_nonces: &[SecretKey],
+        outcome: &impl types::Outcome,
+    ) -> Result<Vec<types::Attestation>, CryptoUtilsError> {
+        let private_key = to_fun_scalar(private_key);
+        let private_nonce = to_fun_scalar(&private_nonces[0]);
+        let challenge = outcome_challenge(
+            &g!(private_key * G).normalize().to_bytes(),
+            &g!(private_nonce * G).normalize().to_bytes(),
+            outcome,
+        );
+        let attestation = s!(private_nonce + challenge * private_key);
+        Ok(vec![SecretKey::from_slice(&attestation.to_bytes())?])
+    }
+
+    // As s = k + e * x, private key is x = (s_a - s_b) / (e_a - e_b)
+    fn extract_private_key(
+        &self,
+        (attestations_a, outcome_a): (&[types::Attestation], &impl types::Outcome),
+        (attestations_b, outcome_b): (&[types::Attestation], &impl types::Outcome),
+    ) -> Option<SecretKey> {
+        let attestation_a = to_fun_scalar(attestations_a.first()?);
+        let attestation_b = to_fun_scalar(attestations_b.first()?);
+        let challenge_a =
+            outcome_challenge(&self.public_key_bytes, &self.public_nonce_bytes, outcome_a);
+        let challenge_b =
+            outcome_challenge(&self.public_key_bytes, &self.public_nonce_bytes, outcome_b);
+        let challenge_diff = s!(challenge_a - challenge_b).non_zero()?.invert();
+        let private_key = s!((attestation_a - attestation_b) * challenge_diff).non_zero()?;
+        SecretKey::from_slice(&private_key.to_bytes()).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::types::OutcomeU32;
+    use crate::crypto_utils::simple_crypto_utils::SimpleCryptoUtils;
+    use secp256k1_zkp::{rand::thread_rng, SECP256K1};
+
+    #[test]
+    fn test_same_as_simple_crypto_utils() {
+        let mut rng = thread_rng();
+        let private_key = SecretKey::new(&mut rng);
+        let private_nonce = SecretKey::new(&mut rng);
+        let public_key = private_key.public_key(SECP256K1);
+        let public_nonces = [private_nonce.public_key(SECP256K1)];
+        let fun_utils = SimpleFunCryptoUtils::new(&public_key, &public_nonces, 10, 2);
+        let zkp_utils = SimpleCryptoUtils::new(&public_key, &public_nonces, 10, 2);
+
+        for value in [0, 1, 517, 1023] {
+            let outcome = OutcomeU32::from(value);
+            let point = fun_utils.compute_anticipation_point(&outcome).unwrap();
+            assert_eq!(
+                point,
+                zkp_utils.compute_anticipation_point(&outcome).unwrap()
+            );
+            let attestations = fun_utils
+                .compute_attestations(&private_key, &[private_nonce], &outcome)
+                .unwrap();
+            assert_eq!(
+                attestations,
+                zkp_utils
+                    .compute_attestations(&private_key, &[private_nonce], &outcome)
+                    .unwrap()
+            );
+            assert_eq!(attestations[0].public_key(SECP256K1), point);
+        }
+    }
+}
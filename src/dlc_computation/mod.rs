@@ -1,12 +1,29 @@
 // src/dlc_computation/mod.rs
 
-use secp256k1_zkp::{Keypair, PublicKey};
+use rand::rngs::StdRng;
+use secp256k1_zkp::{Keypair, Message, PublicKey};
 
 use crate::{
-    adaptor_signature_scheme::AdaptorSignatureScheme, common::types, crypto_utils::CryptoUtils,
+    adaptor_signature_scheme::AdaptorSignatureScheme,
+    common::{
+        self,
+        error::{
+            AdaptorSignatureError, AdaptorVerificationFailure, CryptoUtilsError, VerificationReport,
+        },
+        seed::Seed,
+        types,
+    },
+    crypto_utils::CryptoUtils,
     dlc_storage::StorageElement,
 };
 
+#[cfg(feature = "parallel-cpt")]
+use rayon::prelude::*;
+
+// Counterparty adaptors verified together in one batch (by one thread)
+#[cfg(feature = "parallel-cpt")]
+const VERIFY_CHUNK_SIZE: usize = 1 << 10;
+
 pub trait DlcComputation<ASigS, CU, Out>
 where
     ASigS: AdaptorSignatureScheme,
@@ -17,15 +34,98 @@ where
         parsed_contract: &types::ParsedContract<Out>,
         total_collateral: types::PayoutT,
         signing_keypair: &Keypair,
-        oracle_public_key: &PublicKey,
-        oracle_public_nonce: &PublicKey,
-    ) -> Vec<StorageElement<ASigS>>;
+        crypto_utils_engines: &[&CU], // One per oracle whose attestations unlock the adaptors
+        seed: &Seed,                  // Of pre-signature nonces
+    ) -> Result<Vec<StorageElement<ASigS>>, CryptoUtilsError>;
 
     fn verify_cp_adaptors(
         verification_key: &PublicKey,
         cp_adaptors: &[ASigS::AdaptorSignature],
         storage_elements_vec: &[StorageElement<ASigS>],
-    ) -> bool;
+    ) -> VerificationReport;
+}
+
+// Rng of pre-signature nonce, labelled by what is signed, so that nonces don't depend on order of pre-signing
+fn pre_sign_rng(seed: &Seed, message: &Message, anticipation_point: &PublicKey) -> StdRng {
+    seed.rng([&message.as_ref()[..], &anticipation_point.serialize()[..]].concat())
+}
+
+// Batch verification of counterparty adaptors against our storage elements (anticipation points and CETs),
+// chunks of them are verified in parallel with `parallel-cpt` feature. Adaptors of failed batch are then
+// pinpointed and reported together with their CETs.
+fn batch_verify_cp_adaptors<ASigS: AdaptorSignatureScheme>(
+    verification_key: &PublicKey,
+    cp_adaptors: &[ASigS::AdaptorSignature],
+    storage_elements_vec: &[StorageElement<ASigS>],
+) -> VerificationReport
+where
+    ASigS::AdaptorSignature: Send + Sync,
+{
+    if cp_adaptors.len() != storage_elements_vec.len() {
+        return VerificationReport::length_mismatch(storage_elements_vec.len(), cp_adaptors.len());
+    }
+
+    // Returns indices (within batch) of invalid adaptors with reason
+    let verify_batch = |cp_adaptors: &[ASigS::AdaptorSignature],
+                        storage_elements: &[StorageElement<ASigS>]|
+     -> Result<Vec<(usize, AdaptorSignatureError)>, AdaptorSignatureError> {
+        let messages: Vec<Message> = storage_elements
+            .iter()
+            .map(|storage_element| common::fun::create_message(&storage_element.cet).unwrap())
+            .collect();
+        let anticipation_points: Vec<PublicKey> = storage_elements
+            .iter()
+            .map(|storage_element| storage_element.anticipation_point)
+            .collect();
+        if ASigS::batch_pre_verify(
+            verification_key,
+            &messages,
+            &anticipation_points,
+            cp_adaptors,
+        ) {
+            return Ok(Vec::new());
+        }
+        ASigS::find_invalid_pre_signatures(
+            verification_key,
+            &messages,
+            &anticipation_points,
+            cp_adaptors,
+        )
+    };
+
+    #[cfg(feature = "parallel-cpt")]
+    let invalid: Result<Vec<_>, _> = cp_adaptors
+        .par_chunks(VERIFY_CHUNK_SIZE)
+        .zip(storage_elements_vec.par_chunks(VERIFY_CHUNK_SIZE))
+        .enumerate()
+        .map(|(chunk_index, (cp_adaptors, storage_elements))| {
+            Ok(verify_batch(cp_adaptors, storage_elements)?
+                .into_iter()
+                .map(|(index, error)| (chunk_index * VERIFY_CHUNK_SIZE + index, error))
+                .collect::<Vec<_>>())
+        })
+        .collect::<Result<Vec<_>, AdaptorSignatureError>>()
+        .map(|chunks| chunks.into_iter().flatten().collect());
+    #[cfg(not(feature = "parallel-cpt"))]
+    let invalid = verify_batch(cp_adaptors, storage_elements_vec);
+    // Batches are equally long slices of adaptors and storage elements, whose lengths are checked above
+    let Ok(invalid) = invalid else {
+        return VerificationReport::length_mismatch(storage_elements_vec.len(), cp_adaptors.len());
+    };
+
+    VerificationReport {
+        failures: invalid
+            .into_iter()
+            .map(
+                |(index, error)| AdaptorVerificationFailure::InvalidPreSignature {
+                    index,
+                    cet: storage_elements_vec[index].cet.clone(),
+                    error,
+                },
+            )
+            .collect(),
+    }
 }
 
+pub mod prefix_dlc_computation;
 pub mod unified_dlc_computation;
@@ -0,0 +1,128 @@
+// src/dlc_computation/prefix_dlc_computation.rs
+
+use crate::{
+    adaptor_signature_scheme::AdaptorSignatureScheme,
+    common::{
+        self,
+        digit_decomposition::{DigitPrefix, OraclePrefixes},
+        error::{CryptoUtilsError, VerificationReport},
+        seed::Seed,
+        types,
+    },
+    crypto_utils::digit_crypto_utils::DigitCryptoUtils,
+    dlc_computation::{batch_verify_cp_adaptors, pre_sign_rng},
+    dlc_storage::StorageElement,
+};
+use secp256k1_zkp::{Keypair, PublicKey};
+use std::marker::PhantomData;
+
+#[cfg(feature = "parallel-cpt")]
+use rayon::prelude::*;
+
+// INFO: Doesn't implement `DlcComputation`, because it works with compressed contract (digit prefixes)
+// instead of outcomes, and its anticipation points are computed for prefixes (see `DigitCryptoUtils`).
+
+/// DLC computation over compressed contract, one storage element per digit prefix (one for each oracle of the subset).
+/// Serial or parallel depending on `parallel-cpt` feature.
+pub struct PrefixDlcComputation<ASigS: AdaptorSignatureScheme> {
+    _phantom: PhantomData<ASigS>,
+}
+
+impl<ASigS> PrefixDlcComputation<ASigS>
+where
+    ASigS: AdaptorSignatureScheme,
+    ASigS::AdaptorSignature: Send + Sync,
+{
+    // Sum of anticipation points of each oracle's prefix
+    fn compute_joint_prefix_anticipation_point(
+        prefixes: &[DigitPrefix],
+        crypto_utils_engines: &[&DigitCryptoUtils],
+    ) -> Result<PublicKey, CryptoUtilsError> {
+        debug_assert_eq!(
+            prefixes.len(),
+            crypto_utils_engines.len(),
+            "There must be one prefix per oracle"
+        );
+        let points = crypto_utils_engines
+            .iter()
+            .zip(prefixes)
+            .map(|(engine, prefix)| engine.compute_prefix_anticipation_point(prefix))
+            .collect::<Result<Vec<PublicKey>, _>>()?;
+        match points.as_slice() {
+            [] => Err(CryptoUtilsError::NoOracles),
+            [point] => Ok(*point),
+            _ => Ok(PublicKey::combine_keys(&points.iter().collect::<Vec<_>>())?),
+        }
+    }
+
+    fn create_storage_element(
+        prefixes: &[DigitPrefix],
+        payout: types::PayoutT,
+        total_collateral: types::PayoutT,
+        signing_keypair: &Keypair,
+        crypto_utils_engines: &[&DigitCryptoUtils],
+        seed: &Seed,
+    ) -> Result<StorageElement<ASigS>, CryptoUtilsError> {
+        let cet = common::fun::create_cet(payout, total_collateral);
+        let msg = common::fun::create_message(&cet)?;
+        let anticipation_point =
+            Self::compute_joint_prefix_anticipation_point(prefixes, crypto_utils_engines)?;
+        let mut rng = pre_sign_rng(seed, &msg, &anticipation_point);
+        let my_adaptor = ASigS::pre_sign(signing_keypair, &msg, &anticipation_point, &mut rng);
+        Ok(StorageElement {
+            cet,
+            anticipation_point,
+            my_adaptor_signature: Some(my_adaptor),
+            cp_adaptor_signature: None,
+        })
+    }
+
+    pub fn compute_storage_elements_vec(
+        oracle_prefixes: &[OraclePrefixes], // CETs with prefix of each oracle in the subset
+        total_collateral: types::PayoutT,
+        signing_keypair: &Keypair,
+        crypto_utils_engines: &[&DigitCryptoUtils], // One per oracle whose attestations unlock the adaptors
+        seed: &Seed,                                // Of pre-signature nonces
+    ) -> Result<Vec<StorageElement<ASigS>>, CryptoUtilsError> {
+        #[cfg(feature = "parallel-cpt")]
+        {
+            oracle_prefixes
+                .par_iter()
+                .map(|(prefixes, payout)| {
+                    Self::create_storage_element(
+                        prefixes,
+                        *payout,
+                        total_collateral,
+                        signing_keypair,
+                        crypto_utils_engines,
+                        seed,
+                    )
+                })
+                .collect()
+        }
+        #[cfg(not(feature = "parallel-cpt"))]
+        {
+            oracle_prefixes
+                .iter()
+                .map(|(prefixes, payout)| {
+                    Self::create_storage_element(
+                        prefixes,
+                        *payout,
+                        total_collateral,
+                        signing_keypair,
+                        crypto_utils_engines,
+                        seed,
+                    )
+                })
+                .collect()
+        }
+    }
+
+    pub fn verify_cp_adaptors(
+        verification_key: &PublicKey,
+        cp_adaptors: &[ASigS::AdaptorSignature],
+        storage_elements_vec: &[StorageElement<ASigS>],
+    ) -> VerificationReport {
+        batch_verify_cp_adaptors::<ASigS>(verification_key, cp_adaptors, storage_elements_vec)
+    }
+}
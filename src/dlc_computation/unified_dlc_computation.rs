@@ -2,9 +2,14 @@
 
 use crate::{
     adaptor_signature_scheme::AdaptorSignatureScheme,
-    common::{self, types},
-    crypto_utils::CryptoUtils,
-    dlc_computation::DlcComputation,
+    common::{
+        self,
+        error::{CryptoUtilsError, VerificationReport},
+        seed::Seed,
+        types,
+    },
+    crypto_utils::{compute_joint_anticipation_points, CryptoUtils},
+    dlc_computation::{batch_verify_cp_adaptors, pre_sign_rng, DlcComputation},
     dlc_storage::StorageElement,
 };
 use secp256k1_zkp::{Keypair, PublicKey};
@@ -13,6 +18,10 @@ use std::marker::PhantomData;
 #[cfg(feature = "parallel-cpt")]
 use rayon::prelude::*;
 
+// Consecutive outcomes whose anticipation points are computed together (incrementally) by one thread
+#[cfg(feature = "parallel-cpt")]
+const ATP_CHUNK_SIZE: usize = 1 << 10;
+
 /// Implements both serial and parallel DLC computation flows depending on `parallel-cpt` feature.
 pub struct UnifiedDlcComputation<ASigS: AdaptorSignatureScheme, CU: CryptoUtils> {
     _phantom1: PhantomData<ASigS>,
@@ -35,54 +44,72 @@ impl<ASigS: AdaptorSignatureScheme, CU: CryptoUtils> UnifiedDlcComputation<ASigS
     }
 }
 
-impl<ASigS, CU> DlcComputation<ASigS, CU, types::OutcomeU32> for UnifiedDlcComputation<ASigS, CU>
+impl<ASigS, CU, Out> DlcComputation<ASigS, CU, Out> for UnifiedDlcComputation<ASigS, CU>
 where
     ASigS: AdaptorSignatureScheme,
     CU: CryptoUtils + Sync,
     ASigS::AdaptorSignature: Send + Sync,
+    Out: types::Outcome + Sync,
 {
     fn compute_storage_elements_vec(
-        parsed_contract: &types::ParsedContract<types::OutcomeU32>,
+        parsed_contract: &types::ParsedContract<Out>,
         total_collateral: types::PayoutT,
         signing_keypair: &Keypair,
-        oracle_public_key: &PublicKey,
-        oracle_public_nonce: &PublicKey,
-    ) -> Vec<StorageElement<ASigS>> {
-        let crypto_utils_engine = CU::new(oracle_public_key, oracle_public_nonce);
-
+        crypto_utils_engines: &[&CU],
+        seed: &Seed,
+    ) -> Result<Vec<StorageElement<ASigS>>, CryptoUtilsError> {
         #[cfg(feature = "parallel-cpt")]
         {
+            // 1. Compute anticipation points (summed over oracles), chunks of consecutive outcomes in parallel
+            let atp_points: Vec<PublicKey> = parsed_contract
+                .par_chunks(ATP_CHUNK_SIZE)
+                .map(|chunk| {
+                    compute_joint_anticipation_points(
+                        crypto_utils_engines,
+                        chunk.iter().map(|(outcome, _)| outcome),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .flatten()
+                .collect();
             // Use parallel iteration:
             parsed_contract
                 .par_iter()
-                .map(|(outcome, payout)| {
-                    // 1. Create CET (string, BTC tx, ...)
+                .zip(atp_points)
+                .map(|((_, payout), atp_point)| {
+                    // 2. Create CET (string, BTC tx, ...)
                     let cet_str = common::fun::create_cet(*payout, total_collateral);
-                    // 2. Create message from CET which will be used later for all math operations
-                    let msg = common::fun::create_message(&cet_str).unwrap();
-                    // 3. Compute anticipation point in parallel
-                    let atp_point = crypto_utils_engine
-                        .compute_anticipation_point(outcome)
-                        .unwrap();
+                    // 3. Create message from CET which will be used later for all math operations
+                    let msg = common::fun::create_message(&cet_str)?;
                     // 4. Pre-sign
-                    let my_adaptor = ASigS::pre_sign(signing_keypair, &msg, &atp_point);
+                    let mut rng = pre_sign_rng(seed, &msg, &atp_point);
+                    let my_adaptor = ASigS::pre_sign(signing_keypair, &msg, &atp_point, &mut rng);
                     // 5. Create storage element
-                    Self::create_storage_element(cet_str, atp_point, my_adaptor)
+                    Ok::<_, CryptoUtilsError>(Self::create_storage_element(
+                        cet_str, atp_point, my_adaptor,
+                    ))
                 })
                 .collect() // gather results into a Vec
         }
         #[cfg(not(feature = "parallel-cpt"))]
         {
+            // Anticipation points of consecutive outcomes are derived one from another
+            let atp_points = compute_joint_anticipation_points(
+                crypto_utils_engines,
+                parsed_contract.iter().map(|(outcome, _)| outcome),
+            )?;
             parsed_contract
                 .iter()
-                .map(|(outcome, payout)| {
+                .zip(atp_points)
+                .map(|((_, payout), atp_point)| {
                     let cet_str = common::fun::create_cet(*payout, total_collateral);
-                    let msg = common::fun::create_message(&cet_str).unwrap();
-                    let atp_point = crypto_utils_engine
-                        .compute_anticipation_point(outcome)
-                        .unwrap();
-                    let my_adaptor = ASigS::pre_sign(signing_keypair, &msg, &atp_point);
-                    Self::create_storage_element(cet_str, atp_point, my_adaptor)
+                    let msg = common::fun::create_message(&cet_str)?;
+                    let mut rng = pre_sign_rng(seed, &msg, &atp_point);
+                    let my_adaptor = ASigS::pre_sign(signing_keypair, &msg, &atp_point, &mut rng);
+                    Ok::<_, CryptoUtilsError>(Self::create_storage_element(
+                        cet_str, atp_point, my_adaptor,
+                    ))
                 })
                 .collect()
         }
@@ -92,46 +119,8 @@ where
         verification_key: &PublicKey,
         cp_adaptors: &[ASigS::AdaptorSignature],
         storage_elements_vec: &[StorageElement<ASigS>],
-    ) -> bool {
-        // Check lengths (ATTENTION: If we were to use relevant adaptor signature optimization, then this would probably not hold true)
-        assert_eq!(
-            cp_adaptors.len(),
-            storage_elements_vec.len(),
-            "cp_adaptors and storage_elements_vec must have the same length"
-        );
-
-        #[cfg(feature = "parallel-cpt")]
-        {
-            // Parallel iteration
-            cp_adaptors
-                .par_iter() // parallel iterator over &Vec<ASigS::AdaptorSignature>
-                .zip(storage_elements_vec.par_iter()) // zip with parallel iterator over &Vec<StorageElement<ASigS>>
-                .all(|(cp_adaptor, storage_element)| {
-                    // For each pair, do the logic in parallel:
-                    let msg = common::fun::create_message(&storage_element.cet).unwrap();
-                    ASigS::pre_verify(
-                        verification_key,
-                        &msg,
-                        &storage_element.anticipation_point,
-                        cp_adaptor,
-                    )
-                })
-        }
-        #[cfg(not(feature = "parallel-cpt"))]
-        {
-            for (cp_adaptor, storage_element) in cp_adaptors.iter().zip(storage_elements_vec.iter())
-            {
-                let msg = common::fun::create_message(&storage_element.cet).unwrap();
-                if !ASigS::pre_verify(
-                    verification_key,
-                    &msg,
-                    &storage_element.anticipation_point,
-                    cp_adaptor,
-                ) {
-                    return false;
-                }
-            }
-            true
-        }
+    ) -> VerificationReport {
+        // Lengths are checked as well, mismatch is reported (ATTENTION: If we were to use relevant adaptor signature optimization, then they would probably not match)
+        batch_verify_cp_adaptors::<ASigS>(verification_key, cp_adaptors, storage_elements_vec)
     }
 }
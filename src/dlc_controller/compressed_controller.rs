@@ -0,0 +1,409 @@
+use crate::common::digit_decomposition::{self, OraclePrefixes};
+use crate::common::{
+    self,
+    error::{AdaptorSignatureError, AttestationError, VerificationReport},
+    fun,
+    seed::Seed,
+    types, NumericOutcome, Outcome, ParsedContract,
+};
+use crate::parser::Parser;
+use secp256k1_zkp::{Keypair, PublicKey, SecretKey, SECP256K1};
+
+use crate::config::{MySparseParser, DEFAULT_BASE};
+use crate::crypto_utils::{digit_crypto_utils::DigitCryptoUtils, CryptoUtils};
+use crate::dlc_computation::prefix_dlc_computation::PrefixDlcComputation;
+use crate::dlc_controller::{
+    adaptor_signature_error, attestation_error, check_announcement, check_nb_oracles,
+    recover_adaptor_secret, storage_computation_error, verify_attestations, ControllerType,
+};
+use crate::dlc_storage::{prefix_storage::PrefixStorage, DlcStorage};
+use crate::oracle::{Oracle, OracleAnnouncement, OracleAttestation};
+use crate::{
+    adaptor_signature_scheme::AdaptorSignatureScheme,
+    dlc_controller::{
+        ControllerType::{Accepter, Offerer},
+        DlcController,
+    },
+};
+
+use std::io::Error;
+
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use std::sync::Arc;
+
+// INFO: Instead of one CET per outcome, consecutive outcomes with the same payout are grouped into ranges,
+// and each range is covered by a minimal set of digit prefixes (see `digit_decomposition`). We then create
+// one CET, anticipation point and adaptor signature per prefix, so number of CETs depends on the shape of
+// payout curve rather than on 2^nb_digits. Oracle has to attest digit-by-digit (`DigitCryptoUtils`).
+// With multiple oracles, each CET has a prefix for every oracle of the subset. If the contract allows oracle outcomes
+// to differ (`maxErrorExp`, `minSupportExp`), other oracles get the aligned block around lead oracle's prefix
+// and its margins, at most 3 prefixes each (see `cover_oracle_outcomes`).
+
+/// Controller using CET compression via digit prefix decomposition of outcome ranges.
+pub struct CompressedController<ASigS, O>
+where
+    ASigS: AdaptorSignatureScheme,
+    O: Oracle,
+{
+    controller_type: ControllerType,
+    oracles: Vec<Arc<O>>,
+    keypair: Keypair,
+    seed: Seed,                                      // Of pre-signature nonces
+    storages: Vec<PrefixStorage<ASigS, O::Outcome>>, // One storage per oracle subset, in order of `oracle_subsets`
+    parsed_contract: ParsedContract<O::Outcome>,
+    oracle_prefixes: Vec<OraclePrefixes>, // CETs of each oracle subset, with prefix of each oracle in the subset
+    total_collateral: types::PayoutT,
+    nb_digits: u8,
+    base: u8,
+    oracle_subsets: Vec<Vec<usize>>, // All threshold-sized subsets of oracles
+
+    cp_verification_key: PublicKey,
+    cp_adaptors: Vec<ASigS::AdaptorSignature>,
+    oracle_attestations: Vec<OracleAttestation<O::Outcome>>,
+    attesting_subset: usize, // Index of oracle subset whose attested outcomes are covered by some CET
+    attested_cet: usize,     // Index of that CET in the subset's storage
+    adaptor_secret: Option<types::Attestation>, // Verified sum of attestations of the CET's prefix digits
+}
+
+impl<ASigS, O> CompressedController<ASigS, O>
+where
+    ASigS: AdaptorSignatureScheme,
+    O: Oracle,
+{
+    /// (outcome, payout) pairs of contract loaded by `load_input`, only for outcomes where payout changes.
+    pub fn parsed_contract(&self) -> &ParsedContract<O::Outcome> {
+        &self.parsed_contract
+    }
+
+    /// Number of CETs (and adaptor signatures) of the contract, one per digit prefix and oracle subset.
+    pub fn nb_cets(&self) -> usize {
+        self.oracle_prefixes.len() * self.oracle_subsets.len()
+    }
+
+    // Number of storage elements over all oracle subsets
+    fn nb_storage_elements(&self) -> usize {
+        self.storages
+            .iter()
+            .map(|storage| storage.get_all_elements_vec_ref().len())
+            .sum()
+    }
+
+    // CET whose prefixes cover outcomes attested by oracles of the subset (storage is keyed by lead oracle's prefix)
+    fn find_covering_cet(&self, subset_idx: usize) -> Option<usize> {
+        let subset = &self.oracle_subsets[subset_idx];
+        let lead_outcome = &self.oracle_attestations[subset[0]].outcome;
+        self.storages[subset_idx]
+            .find_indices(lead_outcome)
+            .find(|&cet_idx| {
+                let (prefixes, _) = &self.oracle_prefixes[cet_idx];
+                subset.iter().zip(prefixes).all(|(&i, prefix)| {
+                    prefix.contains(self.oracle_attestations[i].outcome.to_u64(), self.nb_digits)
+                })
+            })
+    }
+}
+
+impl<ASigS, O> DlcController<ASigS, O> for CompressedController<ASigS, O>
+where
+    ASigS: AdaptorSignatureScheme,
+    ASigS::AdaptorSignature: Send + Sync,
+    O: Oracle,
+{
+    fn with_seed(ctype: ControllerType, oracles: Vec<Arc<O>>, seed: Seed) -> Self {
+        let seed = ctype.seed(&seed);
+        let keypair = Keypair::new(SECP256K1, &mut seed.rng("keys"));
+        let cp_verification_key =
+            SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap()
+                .public_key(SECP256K1);
+
+        Self {
+            controller_type: ctype,
+            oracles,
+            keypair,
+            seed,
+            storages: Vec::new(), // Created later, when we know prefixes and oracle subsets from contract
+            parsed_contract: ParsedContract::new(),
+            oracle_prefixes: Vec::new(),
+            total_collateral: 0,
+            nb_digits: 0,
+            base: DEFAULT_BASE,
+            oracle_subsets: Vec::new(),
+            cp_verification_key,
+            cp_adaptors: Vec::new(),
+            oracle_attestations: Vec::new(),
+            attesting_subset: 0,
+            attested_cet: 0,
+            adaptor_secret: None,
+        }
+    }
+
+    fn load_input(&mut self, input_path: &str) -> Result<(), Error> {
+        let contract_input = MySparseParser::read_input(input_path)?;
+        if contract_input
+            .contract_info
+            .contract_descriptor
+            .is_enumerated()
+        {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Enumerated outcomes can be attested only as a whole (simple method)",
+            ));
+        }
+        self.total_collateral = contract_input.accept_collateral + contract_input.offer_collateral;
+        let oracle_input = &contract_input.contract_info.oracle;
+        self.nb_digits = oracle_input.nb_digits;
+        self.base = oracle_input.base();
+        check_nb_oracles(self.oracles.len(), oracle_input.nb_oracles())?;
+        self.oracle_subsets =
+            fun::oracle_subsets(oracle_input.nb_oracles(), oracle_input.threshold());
+        let (threshold, difference) = (oracle_input.threshold(), oracle_input.allowed_difference());
+        // Sparse contract, so that contracts with more than 32 digits don't need one entry per outcome
+        self.parsed_contract = MySparseParser::parse_contract_input(contract_input)?
+            .into_iter()
+            .map(|(outcome, payout)| {
+                (
+                    O::Outcome::from_u64(outcome.get_value(), self.nb_digits),
+                    payout,
+                )
+            })
+            .collect();
+        let compressed_contract = digit_decomposition::compress_contract(
+            &self.parsed_contract,
+            self.nb_digits,
+            self.base,
+        );
+        self.oracle_prefixes = digit_decomposition::cover_oracle_outcomes(
+            &compressed_contract,
+            self.nb_digits,
+            self.base,
+            threshold,
+            difference,
+        );
+        Ok(())
+    }
+
+    fn init_storage(&mut self) -> Result<(), Error> {
+        let event_anncmts: Vec<OracleAnnouncement> = self
+            .oracles
+            .iter()
+            .map(|oracle| oracle.get_event_announcement(0))
+            .collect();
+        for event_anncmt in &event_anncmts {
+            check_announcement(
+                event_anncmt,
+                self.nb_digits,
+                self.base,
+                &[],
+                DigitCryptoUtils::nb_nonces(self.nb_digits),
+            )?;
+        }
+        let crypto_utils_engines: Vec<DigitCryptoUtils> = event_anncmts
+            .iter()
+            .map(|anncmt| {
+                DigitCryptoUtils::new(
+                    &anncmt.public_key,
+                    &anncmt.public_nonces,
+                    self.nb_digits,
+                    self.base,
+                )
+            })
+            .collect();
+
+        // create cet -> prefixes atp point (sum over subset oracles) -> adaptor sig -> storage element
+        self.storages = Vec::with_capacity(self.oracle_subsets.len());
+        for subset in &self.oracle_subsets {
+            let subset_engines: Vec<&DigitCryptoUtils> =
+                subset.iter().map(|&i| &crypto_utils_engines[i]).collect();
+            let storage_elements_vec = PrefixDlcComputation::<ASigS>::compute_storage_elements_vec(
+                &self.oracle_prefixes,
+                self.total_collateral,
+                &self.keypair,
+                &subset_engines,
+                &self.seed,
+            )
+            .map_err(storage_computation_error)?;
+
+            // Elements are keyed by first outcome covered by prefix of lead (first) oracle of the subset
+            let mut storage = PrefixStorage::new(self.oracle_prefixes.len());
+            for ((prefixes, _), element) in self.oracle_prefixes.iter().zip(storage_elements_vec) {
+                let range_start =
+                    O::Outcome::from_u64(prefixes[0].first_outcome(self.nb_digits), self.nb_digits);
+                storage.put_element(&range_start, element)?;
+            }
+            self.storages.push(storage);
+        }
+        Ok(())
+    }
+
+    fn share_verification_key(&self) -> PublicKey {
+        self.keypair.public_key()
+    }
+
+    fn share_adaptors(&self) -> Vec<ASigS::AdaptorSignature> {
+        self.storages
+            .iter()
+            .flat_map(|storage| storage.get_all_my_adaptors())
+            .collect()
+    }
+
+    fn save_cp_verification_key(&mut self, cp_verification_key: PublicKey) {
+        self.cp_verification_key = cp_verification_key;
+    }
+
+    fn save_cp_adaptors(&mut self, cp_adaptors: Vec<ASigS::AdaptorSignature>) {
+        self.cp_adaptors = cp_adaptors;
+    }
+
+    // Counterparty adaptors are ordered the same way as ours, storage by storage
+    fn verify_cp_adaptors(&self) -> VerificationReport {
+        if self.cp_adaptors.len() != self.nb_storage_elements() {
+            return VerificationReport::length_mismatch(
+                self.nb_storage_elements(),
+                self.cp_adaptors.len(),
+            );
+        }
+
+        let mut report = VerificationReport::default();
+        let mut cp_adaptors = self.cp_adaptors.as_slice();
+        let mut offset = 0;
+        for storage in &self.storages {
+            let storage_elements_vec = storage.get_all_elements_vec_ref();
+            let (storage_cp_adaptors, rest) = cp_adaptors.split_at(storage_elements_vec.len());
+            cp_adaptors = rest;
+            let storage_report = PrefixDlcComputation::<ASigS>::verify_cp_adaptors(
+                &self.cp_verification_key,
+                storage_cp_adaptors,
+                storage_elements_vec,
+            );
+            report.append(storage_report, offset);
+            offset += storage_elements_vec.len();
+        }
+        report
+    }
+
+    fn update_cp_adaptors(&mut self) -> Result<(), Error> {
+        if self.cp_adaptors.len() != self.nb_storage_elements() {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Invalid number of cp adaptors",
+            ));
+        }
+        let mut cp_adaptors = self.cp_adaptors.iter().cloned();
+        for storage in &mut self.storages {
+            let nb_elements = storage.get_all_elements_vec_ref().len();
+            storage.update_cp_adaptors(cp_adaptors.by_ref().take(nb_elements).collect())?;
+        }
+        Ok(())
+    }
+
+    fn wait_attestation(&mut self) -> Result<(), Error> {
+        self.oracle_attestations = self
+            .oracles
+            .iter()
+            .map(|oracle| oracle.get_event_attestation(0))
+            .collect();
+        if self
+            .oracle_attestations
+            .iter()
+            .any(|attestation| attestation.attestations.len() != self.nb_digits as usize)
+        {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Oracle attestation must contain one attestation per digit",
+            ));
+        }
+        let max_outcome = fun::max_outcome(self.nb_digits, self.base);
+        if let Some(attestation) = self
+            .oracle_attestations
+            .iter()
+            .find(|attestation| attestation.outcome.to_u64() > max_outcome)
+        {
+            return Err(attestation_error(AttestationError::OutcomeOutOfRange {
+                outcome: attestation.outcome.to_u64(),
+            }));
+        }
+        (self.attesting_subset, self.attested_cet) = (0..self.oracle_subsets.len())
+            .find_map(|subset_idx| {
+                self.find_covering_cet(subset_idx)
+                    .map(|cet_idx| (subset_idx, cet_idx))
+            })
+            .ok_or_else(|| {
+                Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "Not enough oracles attested to the same outcome (or outcomes within allowed difference)",
+                )
+            })?;
+
+        // Adaptor secret of the covering CET is sum of attestations of each oracle's prefix digits over the subset
+        let subset = &self.oracle_subsets[self.attesting_subset];
+        let (prefixes, _) = &self.oracle_prefixes[self.attested_cet];
+        let prefix_attestations: Vec<types::Attestation> = subset
+            .iter()
+            .zip(prefixes)
+            .flat_map(|(&i, prefix)| {
+                &self.oracle_attestations[i].attestations[..prefix.len as usize]
+            })
+            .copied()
+            .collect();
+        let outcome_element =
+            &self.storages[self.attesting_subset].get_all_elements_vec_ref()[self.attested_cet];
+        self.adaptor_secret = Some(verify_attestations(
+            &prefix_attestations,
+            &outcome_element.anticipation_point,
+            self.oracle_attestations[subset[0]].outcome.to_u64(),
+        )?);
+        Ok(())
+    }
+
+    fn finalize_tx(&self) -> Result<types::FinalizedTx<ASigS::Signature>, Error> {
+        let adaptor_secret = self
+            .adaptor_secret
+            .ok_or_else(|| attestation_error(AttestationError::NotAttested))?;
+        let outcome_element = self
+            .storages
+            .get(self.attesting_subset)
+            .and_then(|storage| storage.get_all_elements_vec_ref().get(self.attested_cet))
+            .ok_or_else(|| attestation_error(AttestationError::NotAttested))?;
+
+        let msg = common::fun::create_message(outcome_element.cet.as_bytes())
+            .map_err(|e| adaptor_signature_error(e.into()))?;
+
+        let my_sig = ASigS::sign(&self.keypair, &msg);
+
+        let cp_adaptor_signature = outcome_element
+            .cp_adaptor_signature
+            .as_ref()
+            .ok_or_else(|| adaptor_signature_error(AdaptorSignatureError::MissingAdaptor))?;
+        let cp_sig =
+            ASigS::adapt(cp_adaptor_signature, &adaptor_secret).map_err(adaptor_signature_error)?;
+
+        let cet = outcome_element.cet.clone();
+        Ok(match self.controller_type {
+            Offerer => types::FinalizedTx::<ASigS::Signature>::new(cet, my_sig, cp_sig),
+            Accepter => types::FinalizedTx::<ASigS::Signature>::new(cet, cp_sig, my_sig),
+        })
+    }
+
+    // Adaptor secret is sum of prefix attestations, so we learn only range of outcomes covered by the CET
+    fn recover_attestation(
+        &mut self,
+        cp_finalized_tx: &types::FinalizedTx<ASigS::Signature>,
+    ) -> Result<RangeInclusive<u64>, Error> {
+        let (subset_idx, cet_idx, adaptor_secret) = recover_adaptor_secret(
+            cp_finalized_tx,
+            self.controller_type,
+            &self.keypair.public_key(),
+            &self.cp_verification_key,
+            self.storages
+                .iter()
+                .map(|storage| storage.get_all_elements_vec_ref()),
+        )?;
+        self.attesting_subset = subset_idx;
+        self.attested_cet = cet_idx;
+        self.adaptor_secret = Some(adaptor_secret);
+        let (prefixes, _) = &self.oracle_prefixes[cet_idx];
+        Ok(prefixes[0].first_outcome(self.nb_digits)..=prefixes[0].last_outcome(self.nb_digits))
+    }
+}
@@ -1,12 +1,20 @@
 // src/dlc_controller/mod.rs
 
-use secp256k1_zkp::PublicKey;
+use secp256k1_zkp::{PublicKey, SECP256K1};
 
 use crate::{
-    adaptor_signature_scheme::AdaptorSignatureScheme, common::types, config::MySignature,
-    crypto_utils::CryptoUtils, oracle::Oracle,
+    adaptor_signature_scheme::AdaptorSignatureScheme,
+    common::{
+        error::{AdaptorSignatureError, AttestationError, CryptoUtilsError, VerificationReport},
+        fun,
+        seed::Seed,
+        types,
+    },
+    crypto_utils::combine_attestations,
+    dlc_storage::StorageElement,
+    oracle::{Oracle, OracleAnnouncement, OracleAttestation},
 };
-use std::{io::Error, sync::Arc};
+use std::{io::Error, ops::RangeInclusive, sync::Arc};
 
 /// Role of a DLC participant: Offerer or Accepter.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -15,18 +23,36 @@ pub enum ControllerType {
     Accepter,
 }
 
+impl ControllerType {
+    /// Stream of controller's randomness, so that parties sharing run seed get different keys.
+    pub fn seed(&self, seed: &Seed) -> Seed {
+        match self {
+            ControllerType::Offerer => seed.derive("offerer"),
+            ControllerType::Accepter => seed.derive("accepter"),
+        }
+    }
+}
+
 /// Discreet Log Contract controller interface.
-/// Parameterized by adaptor‐signature scheme `ASigS`, crypto engine `CU`, and oracle `O`.
+/// Parameterized by adaptor‐signature scheme `ASigS` and oracle `O` (anticipation point computation is up to the implementation).
+/// Outcomes are represented the same way as oracle attests them (`O::Outcome`).
 /// Implements the core protocol steps: initialization, input loading, storage setup,
 /// key/adaptor exchange, verification, attestation handling, and final transaction finalization.
-pub trait DlcController<ASigS, CU, O>
+pub trait DlcController<ASigS, O>
 where
     ASigS: AdaptorSignatureScheme,
-    CU: CryptoUtils,
     O: Oracle,
 {
-    /// Creates a new controller with a given name and oracle.
-    fn new(ctype: ControllerType, oracle: Arc<O>) -> Self;
+    /// Creates a new controller with given role and oracles (in the same order as their public keys in contract).
+    fn new(ctype: ControllerType, oracles: Vec<Arc<O>>) -> Self
+    where
+        Self: Sized,
+    {
+        Self::with_seed(ctype, oracles, Seed::random())
+    }
+
+    /// Same as `new`, but keys and pre-signature nonces are derived from run seed, so that the run can be replayed.
+    fn with_seed(ctype: ControllerType, oracles: Vec<Arc<O>>, seed: Seed) -> Self;
 
     /// Loads DLC input from a file.
     fn load_input(&mut self, input_path: &str) -> Result<(), Error>;
@@ -46,17 +72,175 @@ where
     /// Saves the counterparty's adaptors.
     fn save_cp_adaptors(&mut self, cp_adaptors: Vec<ASigS::AdaptorSignature>);
 
-    /// Verifies the counterparty's adaptors.
-    fn verify_cp_adaptors(&self) -> bool;
+    /// Verifies the counterparty's adaptors, reporting those that failed (by their position in shared adaptors).
+    fn verify_cp_adaptors(&self) -> VerificationReport;
 
     /// Updates the stored adaptors with verified counterparty information.
     fn update_cp_adaptors(&mut self) -> Result<(), Error>;
 
-    /// Waits for oracle attestation to proceed with finalizing the DLC.
+    /// Waits for attestations of threshold oracles to proceed with finalizing the DLC.
+    /// Fails (with `AttestationError` inside) if attested outcome is out of range or attestations don't unlock its anticipation point.
     fn wait_attestation(&mut self) -> Result<(), Error>;
 
     /// Finalizes the transaction using the relevant signatures.
-    fn finalize_tx(&self) -> types::FinalizedTx<MySignature>;
+    /// Fails (with `AttestationError` inside) if no outcome is attested yet, or (with `AdaptorSignatureError` inside)
+    /// if counterparty's adaptor signature is missing or can't be adapted.
+    fn finalize_tx(&self) -> Result<types::FinalizedTx<ASigS::Signature>, Error>;
+
+    /// Recovers attestation from CET finalized and broadcast by counterparty (instead of `wait_attestation`),
+    /// so that we can finalize the same CET. Returns outcomes (of lead oracle of attesting subset) the CET covers.
+    /// Fails (with `AttestationError` inside) if the CET wasn't adapted from any of our adaptor signatures.
+    fn recover_attestation(
+        &mut self,
+        cp_finalized_tx: &types::FinalizedTx<ASigS::Signature>,
+    ) -> Result<RangeInclusive<u64>, Error>;
+}
+
+// Controller must get as many oracles as the contract lists
+pub(crate) fn check_nb_oracles(nb_oracles: usize, nb_contract_oracles: usize) -> Result<(), Error> {
+    if nb_oracles != nb_contract_oracles {
+        return Err(Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "Contract lists {} oracles, but controller was given {}",
+                nb_contract_oracles, nb_oracles
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// Oracle must announce event with as many digits in the same base (or the same outcome labels) as contract expects
+// and with as many nonces as the method needs
+pub(crate) fn check_announcement(
+    event_anncmt: &OracleAnnouncement,
+    nb_digits: u8,
+    base: u8,
+    outcome_labels: &[String],
+    nb_nonces: usize,
+) -> Result<(), Error> {
+    if event_anncmt.nb_digits != nb_digits {
+        return Err(Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "Oracle attests outcomes with {} digits, but contract expects {} digits",
+                event_anncmt.nb_digits, nb_digits
+            ),
+        ));
+    }
+    if event_anncmt.base != base {
+        return Err(Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "Oracle attests digits in base {}, but contract expects base {}",
+                event_anncmt.base, base
+            ),
+        ));
+    }
+    if event_anncmt.outcomes != outcome_labels {
+        return Err(Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Oracle announced different outcomes than contract expects",
+        ));
+    }
+    if event_anncmt.public_nonces.len() != nb_nonces {
+        return Err(Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Oracle announced different number of nonces than the anticipation point method needs",
+        ));
+    }
+    Ok(())
+}
+
+// Index of first oracle subset whose oracles all attested to the same outcome
+pub(crate) fn find_attesting_subset<Out: types::NumericOutcome>(
+    oracle_attestations: &[OracleAttestation<Out>],
+    oracle_subsets: &[Vec<usize>],
+) -> Result<usize, Error> {
+    let attested_outcomes: Vec<&Out> = oracle_attestations
+        .iter()
+        .map(|attestation| &attestation.outcome)
+        .collect();
+    fun::find_agreeing_subset(&attested_outcomes, oracle_subsets).ok_or_else(|| {
+        Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Not enough oracles attested to the same outcome",
+        )
+    })
+}
+
+// Adaptor secret (sum of attestations) of attested outcome, it must be secret key of outcome's anticipation point
+pub(crate) fn verify_attestations(
+    attestations: &[types::Attestation],
+    anticipation_point: &PublicKey,
+    outcome: u64,
+) -> Result<types::Attestation, Error> {
+    combine_attestations(attestations)
+        .ok()
+        .filter(|secret| secret.public_key(SECP256K1) == *anticipation_point)
+        .ok_or_else(|| attestation_error(AttestationError::InvalidAttestation { outcome }))
+}
+
+// Position (subset, element) of our CET broadcast by counterparty, with adaptor secret recovered from it.
+// Counterparty adapted our adaptor signature of the CET, so our signature in it reveals the secret.
+pub(crate) fn recover_adaptor_secret<'a, ASigS>(
+    cp_finalized_tx: &types::FinalizedTx<ASigS::Signature>,
+    controller_type: ControllerType,
+    verification_key: &PublicKey,
+    cp_verification_key: &PublicKey,
+    storages: impl IntoIterator<Item = &'a Vec<StorageElement<ASigS>>>,
+) -> Result<(usize, usize, types::Attestation), Error>
+where
+    ASigS: AdaptorSignatureScheme + 'a,
+{
+    let not_recoverable = || attestation_error(AttestationError::NotRecoverable);
+    let (my_sig, cp_sig) = match controller_type {
+        ControllerType::Offerer => (&cp_finalized_tx.offerer_sig, &cp_finalized_tx.accepter_sig),
+        ControllerType::Accepter => (&cp_finalized_tx.accepter_sig, &cp_finalized_tx.offerer_sig),
+    };
+    let msg =
+        fun::create_message(cp_finalized_tx.payload.as_bytes()).map_err(|_| not_recoverable())?;
+    if !ASigS::verify(verification_key, &msg, my_sig)
+        || !ASigS::verify(cp_verification_key, &msg, cp_sig)
+    {
+        return Err(not_recoverable());
+    }
+
+    // CETs with the same payout have the same payload, but only adaptor signature of the broadcast one matches
+    storages
+        .into_iter()
+        .enumerate()
+        .find_map(|(subset_idx, storage_elements_vec)| {
+            storage_elements_vec
+                .iter()
+                .enumerate()
+                .filter(|(_, element)| element.cet == cp_finalized_tx.payload)
+                .find_map(|(cet_idx, element)| {
+                    ASigS::extract(
+                        my_sig,
+                        element.my_adaptor_signature.as_ref()?,
+                        &element.anticipation_point,
+                    )
+                    .ok()
+                    .map(|secret| (subset_idx, cet_idx, secret))
+                })
+        })
+        .ok_or_else(not_recoverable)
+}
+
+// Typed error stays accessible through `Error::get_ref`
+pub(crate) fn attestation_error(e: AttestationError) -> Error {
+    Error::new(std::io::ErrorKind::InvalidData, e)
+}
+
+pub(crate) fn adaptor_signature_error(e: AdaptorSignatureError) -> Error {
+    Error::new(std::io::ErrorKind::InvalidData, e)
+}
+
+// Anticipation points or CET messages can't be computed, e.g. from malformed oracle announcement
+pub(crate) fn storage_computation_error(e: CryptoUtilsError) -> Error {
+    Error::new(std::io::ErrorKind::InvalidData, e)
 }
 
+pub mod compressed_controller;
 pub mod very_simple_controller;
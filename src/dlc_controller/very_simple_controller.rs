@@ -1,13 +1,23 @@
-use crate::common::{self, types, OutcomeU32, ParsedContract};
+use crate::common::{
+    self,
+    error::{AdaptorSignatureError, AttestationError, VerificationReport},
+    fun,
+    seed::Seed,
+    types, NumericOutcome, Outcome, OutcomeEnum, ParsedContract,
+};
 use crate::parser::Parser;
 use secp256k1_zkp::{Keypair, PublicKey, SecretKey, SECP256K1};
 
-use crate::config::{MyParser, MySignature, NB_OUTCOMES};
+use crate::config::{MyEnumParser, MyParser, BASIS_WINDOW_BITS, DEFAULT_BASE};
 use crate::crypto_utils::CryptoUtils;
 use crate::dlc_computation::{unified_dlc_computation::UnifiedDlcComputation, DlcComputation};
-use crate::dlc_controller::ControllerType;
+use crate::dlc_controller::{
+    adaptor_signature_error, attestation_error, check_announcement, check_nb_oracles,
+    find_attesting_subset, recover_adaptor_secret, storage_computation_error, verify_attestations,
+    ControllerType,
+};
 use crate::dlc_storage::{simple_array_storage::SimpleArrayStorage, DlcStorage};
-use crate::oracle::{Oracle, OracleAttestation};
+use crate::oracle::{Oracle, OracleAnnouncement, OracleAttestation};
 use crate::{
     adaptor_signature_scheme::AdaptorSignatureScheme,
     dlc_controller::{
@@ -16,16 +26,16 @@ use crate::{
     },
 };
 
-use secp256k1_zkp::rand;
 use std::io::Error;
 
 use std::marker::PhantomData;
+use std::ops::RangeInclusive;
 use std::str::FromStr;
 use std::sync::Arc;
 
 // To use different implementations of DlcStorage and MyDlcComputation for this specific controller,
 // just change the type aliases below
-type MyDlcStorage<T> = SimpleArrayStorage<T>;
+type MyDlcStorage<T, Out> = SimpleArrayStorage<T, Out>;
 type MyDlcComputation<A, C> = UnifiedDlcComputation<A, C>;
 
 pub struct VerySimpleController<ASigS, CU, O>
@@ -35,30 +45,84 @@ where
     O: Oracle,
 {
     controller_type: ControllerType,
-    oracle: Arc<O>,
+    oracles: Vec<Arc<O>>,
     keypair: Keypair,
-    storage: MyDlcStorage<ASigS>,
-    parsed_contract: ParsedContract<OutcomeU32>,
+    seed: Seed,                                       // Of pre-signature nonces
+    storages: Vec<MyDlcStorage<ASigS, O::Outcome>>, // One storage per oracle subset, in order of `oracle_subsets`
+    parsed_contract: ParsedContract<O::Outcome>, // For enumerated contract, outcomes are indices of the labels
+    enumerated_contract: ParsedContract<OutcomeEnum>, // Empty for numeric contract
     total_collateral: types::PayoutT,
+    nb_digits: u8,
+    base: u8,
+    window_bits: u8, // Of crypto utils precomputing tables (basis method)
+    oracle_subsets: Vec<Vec<usize>>, // All threshold-sized subsets of oracles
 
     cp_verification_key: PublicKey,
     cp_adaptors: Vec<ASigS::AdaptorSignature>,
-    oracle_attestation: OracleAttestation,
+    oracle_attestations: Vec<OracleAttestation<O::Outcome>>,
+    attesting_subset: usize, // Index of oracle subset which attested to the same outcome
+    attested_outcome: Option<O::Outcome>, // Outcome whose CET we finalize
+    adaptor_secret: Option<types::Attestation>, // Verified sum of attestations of the subset
 
     _phantom_asig: PhantomData<ASigS>,
     _phantom_cu: PhantomData<CU>,
 }
 
-impl<ASigS, CU, O> DlcController<ASigS, CU, O> for VerySimpleController<ASigS, CU, O>
+impl<ASigS, CU, O> VerySimpleController<ASigS, CU, O>
+where
+    ASigS: AdaptorSignatureScheme,
+    CU: CryptoUtils,
+    O: Oracle,
+{
+    /// Uses precomputed tables of `window_bits` bits for anticipation points (see `CryptoUtils::with_window`).
+    pub fn with_basis_window(mut self, window_bits: u8) -> Self {
+        self.window_bits = window_bits;
+        self
+    }
+
+    pub fn basis_window(&self) -> u8 {
+        self.window_bits
+    }
+
+    /// (outcome, payout) pairs of contract loaded by `load_input`.
+    pub fn parsed_contract(&self) -> &ParsedContract<O::Outcome> {
+        &self.parsed_contract
+    }
+
+    // Labels of enumerated outcomes (empty for numeric contract)
+    fn outcome_labels(&self) -> Vec<String> {
+        self.enumerated_contract
+            .iter()
+            .map(|(outcome, _)| outcome.get_value())
+            .collect()
+    }
+
+    /// Number of CETs (and adaptor signatures) of the contract, one per outcome and oracle subset.
+    pub fn nb_cets(&self) -> usize {
+        self.parsed_contract.len() * self.oracle_subsets.len()
+    }
+
+    // Number of storage elements over all oracle subsets
+    fn nb_storage_elements(&self) -> usize {
+        self.storages
+            .iter()
+            .map(|storage| storage.get_all_elements_vec_ref().len())
+            .sum()
+    }
+}
+
+impl<ASigS, CU, O> DlcController<ASigS, O> for VerySimpleController<ASigS, CU, O>
 where
-    ASigS: AdaptorSignatureScheme<Signature = MySignature>,
+    ASigS: AdaptorSignatureScheme,
     ASigS::AdaptorSignature: Send + Sync,
     CU: CryptoUtils + Sync,
     O: Oracle,
+    O::Outcome: Sync,
 {
-    fn new(ctype: ControllerType, oracle: Arc<O>) -> Self {
-        let keypair = Keypair::new(SECP256K1, &mut rand::thread_rng());
-        let storage = MyDlcStorage::new(NB_OUTCOMES);
+    fn with_seed(ctype: ControllerType, oracles: Vec<Arc<O>>, seed: Seed) -> Self {
+        let seed = ctype.seed(&seed);
+        let keypair = Keypair::new(SECP256K1, &mut seed.rng("keys"));
+        let storages = Vec::new(); // Created later, when we know number of outcomes and oracle subsets from contract
         let parsed_contract = ParsedContract::new();
         let cp_verification_key =
             SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000001")
@@ -66,21 +130,27 @@ where
                 .public_key(SECP256K1);
         let cp_adaptors = Vec::new();
         let total_collateral: types::PayoutT = 0;
-        let oracle_attestation = OracleAttestation {
-            outcome: OutcomeU32::from(u32::MAX),
-            attestation: SecretKey::new(&mut rand::thread_rng()),
-        };
+        let nb_digits = 0;
 
         Self {
             controller_type: ctype,
-            oracle,
+            oracles,
             keypair,
-            storage,
+            seed,
+            storages,
             parsed_contract,
+            enumerated_contract: ParsedContract::new(),
             total_collateral,
+            nb_digits,
+            base: DEFAULT_BASE,
+            window_bits: BASIS_WINDOW_BITS,
+            oracle_subsets: Vec::new(),
             cp_verification_key,
             cp_adaptors,
-            oracle_attestation,
+            oracle_attestations: Vec::new(),
+            attesting_subset: 0,
+            attested_outcome: None,
+            adaptor_secret: None,
             _phantom_asig: PhantomData,
             _phantom_cu: PhantomData,
         }
@@ -90,27 +160,121 @@ where
         let contract_input = MyParser::read_input(input_path)?;
         // We created this small hack where we take out total_collateral instead of whole ContractInput. However, it can be changed, but for now it seems to be fine.
         self.total_collateral = contract_input.accept_collateral + contract_input.offer_collateral;
-        self.parsed_contract = MyParser::parse_contract_input(contract_input)?;
+        let oracle_input = &contract_input.contract_info.oracle;
+        self.nb_digits = oracle_input.nb_digits;
+        self.base = oracle_input.base();
+        check_nb_oracles(self.oracles.len(), oracle_input.nb_oracles())?;
+        // One CET per outcome can't tell which other outcomes an oracle may attest to
+        if oracle_input.allowed_difference().is_some() && oracle_input.threshold() > 1 {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Allowed difference of oracle outcomes is supported only with CET compression (prefix method)",
+            ));
+        }
+        self.oracle_subsets =
+            fun::oracle_subsets(oracle_input.nb_oracles(), oracle_input.threshold());
+
+        if !contract_input
+            .contract_info
+            .contract_descriptor
+            .is_enumerated()
+        {
+            // Parser produces integer outcomes, so we convert them to outcome representation of the oracle
+            self.parsed_contract = MyParser::parse_contract_input(contract_input)?
+                .into_iter()
+                .map(|(outcome, payout)| {
+                    (
+                        O::Outcome::from_u64(outcome.get_value() as u64, self.nb_digits),
+                        payout,
+                    )
+                })
+                .collect();
+            return Ok(());
+        }
+        // Methods attesting digit-by-digit announce nonce per digit, but enumerated outcome has no digits
+        if CU::nb_nonces(0) != 1 {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Enumerated outcomes can be attested only as a whole (simple method)",
+            ));
+        }
+        self.enumerated_contract = MyEnumParser::parse_contract_input(contract_input)?;
+        self.parsed_contract = self
+            .enumerated_contract
+            .iter()
+            .map(|(outcome, payout)| {
+                (
+                    O::Outcome::from_u64(outcome.index() as u64, self.nb_digits),
+                    *payout,
+                )
+            })
+            .collect();
         Ok(())
     }
 
     fn init_storage(&mut self) -> Result<(), Error> {
-        // Get (announcement) public key, public nonces and next attestation time from the oracle
-        let event_anncmt = self.oracle.get_event_announcement(0);
-
-        // Compute storage elements vector for all outcomes
-        // create cet -> atp point -> adaptor sig -> storage element
-        let storage_elements_vec = MyDlcComputation::<ASigS, CU>::compute_storage_elements_vec(
-            &self.parsed_contract,
-            self.total_collateral,
-            &self.keypair,
-            &event_anncmt.public_key,
-            &event_anncmt.public_nonce,
-        );
-
-        // Put all elements into storage
-        for ((outcome, _), element) in self.parsed_contract.iter().zip(storage_elements_vec) {
-            self.storage.put_element(outcome, element)?;
+        // Get (announcement) public key, public nonces and next attestation time from each oracle
+        let event_anncmts: Vec<OracleAnnouncement> = self
+            .oracles
+            .iter()
+            .map(|oracle| oracle.get_event_announcement(0))
+            .collect();
+        let outcome_labels = self.outcome_labels();
+        for event_anncmt in &event_anncmts {
+            check_announcement(
+                event_anncmt,
+                self.nb_digits,
+                self.base,
+                &outcome_labels,
+                CU::nb_nonces(self.nb_digits),
+            )?;
+        }
+        let crypto_utils_engines: Vec<CU> = event_anncmts
+            .iter()
+            .map(|anncmt| {
+                CU::with_window(
+                    &anncmt.public_key,
+                    &anncmt.public_nonces,
+                    self.nb_digits,
+                    self.base,
+                    self.window_bits,
+                )
+            })
+            .collect();
+
+        // Compute storage elements vector for all outcomes, separately for each oracle subset
+        // create cet -> atp point (sum over subset oracles) -> adaptor sig -> storage element
+        self.storages = Vec::with_capacity(self.oracle_subsets.len());
+        for subset in &self.oracle_subsets {
+            let subset_engines: Vec<&CU> =
+                subset.iter().map(|&i| &crypto_utils_engines[i]).collect();
+            // Anticipation points of enumerated outcomes are computed from their (hashed) labels
+            let storage_elements_vec = if self.enumerated_contract.is_empty() {
+                MyDlcComputation::<ASigS, CU>::compute_storage_elements_vec(
+                    &self.parsed_contract,
+                    self.total_collateral,
+                    &self.keypair,
+                    &subset_engines,
+                    &self.seed,
+                )
+                .map_err(storage_computation_error)?
+            } else {
+                MyDlcComputation::<ASigS, CU>::compute_storage_elements_vec(
+                    &self.enumerated_contract,
+                    self.total_collateral,
+                    &self.keypair,
+                    &subset_engines,
+                    &self.seed,
+                )
+                .map_err(storage_computation_error)?
+            };
+
+            // Put all elements into storage (enumerated outcomes by index of their label)
+            let mut storage = MyDlcStorage::new(self.parsed_contract.len());
+            for ((outcome, _), element) in self.parsed_contract.iter().zip(storage_elements_vec) {
+                storage.put_element(outcome, element)?;
+            }
+            self.storages.push(storage);
         }
         Ok(())
     }
@@ -120,7 +284,10 @@ where
     }
 
     fn share_adaptors(&self) -> Vec<ASigS::AdaptorSignature> {
-        self.storage.get_all_my_adaptors()
+        self.storages
+            .iter()
+            .flat_map(|storage| storage.get_all_my_adaptors())
+            .collect()
     }
 
     fn save_cp_verification_key(&mut self, cp_verification_key: PublicKey) {
@@ -131,20 +298,82 @@ where
         self.cp_adaptors = cp_adaptors;
     }
 
-    fn verify_cp_adaptors(&self) -> bool {
-        MyDlcComputation::<ASigS, CU>::verify_cp_adaptors(
-            &self.cp_verification_key,
-            &self.cp_adaptors,
-            self.storage.get_all_elements_vec_ref(),
-        )
+    // Counterparty adaptors are ordered the same way as ours, storage by storage
+    fn verify_cp_adaptors(&self) -> VerificationReport {
+        if self.cp_adaptors.len() != self.nb_storage_elements() {
+            return VerificationReport::length_mismatch(
+                self.nb_storage_elements(),
+                self.cp_adaptors.len(),
+            );
+        }
+
+        let mut report = VerificationReport::default();
+        let mut cp_adaptors = self.cp_adaptors.as_slice();
+        let mut offset = 0;
+        for storage in &self.storages {
+            let storage_elements_vec = storage.get_all_elements_vec_ref();
+            let (storage_cp_adaptors, rest) = cp_adaptors.split_at(storage_elements_vec.len());
+            cp_adaptors = rest;
+            // Verification doesn't depend on outcome type, as adaptors are checked against stored anticipation points
+            let storage_report = <MyDlcComputation<ASigS, CU> as DlcComputation<
+                ASigS,
+                CU,
+                O::Outcome,
+            >>::verify_cp_adaptors(
+                &self.cp_verification_key,
+                storage_cp_adaptors,
+                storage_elements_vec,
+            );
+            report.append(storage_report, offset);
+            offset += storage_elements_vec.len();
+        }
+        report
     }
 
     fn update_cp_adaptors(&mut self) -> Result<(), Error> {
-        self.storage.update_cp_adaptors(self.cp_adaptors.clone())
+        if self.cp_adaptors.len() != self.nb_storage_elements() {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Invalid number of cp adaptors",
+            ));
+        }
+        let mut cp_adaptors = self.cp_adaptors.iter().cloned();
+        for storage in &mut self.storages {
+            let nb_elements = storage.get_all_elements_vec_ref().len();
+            storage.update_cp_adaptors(cp_adaptors.by_ref().take(nb_elements).collect())?;
+        }
+        Ok(())
     }
 
     fn wait_attestation(&mut self) -> Result<(), Error> {
-        self.oracle_attestation = self.oracle.get_event_attestation(0);
+        self.oracle_attestations = self
+            .oracles
+            .iter()
+            .map(|oracle| oracle.get_event_attestation(0))
+            .collect();
+        self.attesting_subset =
+            find_attesting_subset(&self.oracle_attestations, &self.oracle_subsets)?;
+
+        // Adaptor secret is sum of attestations of all oracles in the subset, we check it before adapting with it
+        let subset = &self.oracle_subsets[self.attesting_subset];
+        let outcome = &self.oracle_attestations[subset[0]].outcome;
+        let outcome_element = self.storages[self.attesting_subset]
+            .get_element(outcome)
+            .ok_or_else(|| {
+                attestation_error(AttestationError::OutcomeOutOfRange {
+                    outcome: outcome.to_u64(),
+                })
+            })?;
+        let subset_attestations: Vec<types::Attestation> = subset
+            .iter()
+            .flat_map(|&i| self.oracle_attestations[i].attestations.iter().copied())
+            .collect();
+        self.adaptor_secret = Some(verify_attestations(
+            &subset_attestations,
+            &outcome_element.anticipation_point,
+            outcome.to_u64(),
+        )?);
+        self.attested_outcome = Some(outcome.clone());
 
         // In future, here might be relevant adaptor optimization, ideally as some function eg. `has_winning_payout()` in `fun.rs`
 
@@ -152,32 +381,68 @@ where
     }
 
     // If we are aware of event outcome, we can finalize winning DLC transaction which will be then broadcasted to the blockchain
-    fn finalize_tx(&self) -> types::FinalizedTx<ASigS::Signature> {
+    fn finalize_tx(&self) -> Result<types::FinalizedTx<ASigS::Signature>, Error> {
+        let not_attested = || attestation_error(AttestationError::NotAttested);
+        let outcome = self.attested_outcome.as_ref().ok_or_else(not_attested)?;
+        let adaptor_secret = self.adaptor_secret.ok_or_else(not_attested)?;
         let outcome_element = self
-            .storage
-            .get_element(&self.oracle_attestation.outcome)
-            .unwrap();
-
-        let msg = common::fun::create_message(outcome_element.cet.as_bytes()).unwrap();
-
-        #[cfg(feature = "ecdsa")]
-        let my_sig = self.keypair.secret_key().sign_ecdsa(msg);
-        #[cfg(feature = "schnorr")]
-        let my_sig = self.keypair.sign_schnorr(msg);
-
-        let cp_sig = ASigS::adapt(
-            &outcome_element.cp_adaptor_signature.unwrap(),
-            &self.oracle_attestation.attestation,
-        );
-
-        if self.controller_type == Offerer {
-            types::FinalizedTx::<ASigS::Signature>::new(outcome_element.cet, my_sig, cp_sig)
-        } else if self.controller_type == Accepter {
-            types::FinalizedTx::<ASigS::Signature>::new(outcome_element.cet, cp_sig, my_sig)
-        } else {
-            // Fallback (or panic) if controller type is neither Offerer nor Accepter
-            panic!("Unknown controller type: {:?}", self.controller_type);
-        }
+            .storages
+            .get(self.attesting_subset)
+            .and_then(|storage| storage.get_element(outcome))
+            .ok_or_else(|| {
+                attestation_error(AttestationError::OutcomeOutOfRange {
+                    outcome: outcome.to_u64(),
+                })
+            })?;
+
+        let msg = common::fun::create_message(outcome_element.cet.as_bytes())
+            .map_err(|e| adaptor_signature_error(e.into()))?;
+
+        let my_sig = ASigS::sign(&self.keypair, &msg);
+
+        let cp_adaptor_signature = outcome_element
+            .cp_adaptor_signature
+            .as_ref()
+            .ok_or_else(|| adaptor_signature_error(AdaptorSignatureError::MissingAdaptor))?;
+        let cp_sig =
+            ASigS::adapt(cp_adaptor_signature, &adaptor_secret).map_err(adaptor_signature_error)?;
+
+        Ok(match self.controller_type {
+            Offerer => {
+                types::FinalizedTx::<ASigS::Signature>::new(outcome_element.cet, my_sig, cp_sig)
+            }
+            Accepter => {
+                types::FinalizedTx::<ASigS::Signature>::new(outcome_element.cet, cp_sig, my_sig)
+            }
+        })
+    }
+
+    fn recover_attestation(
+        &mut self,
+        cp_finalized_tx: &types::FinalizedTx<ASigS::Signature>,
+    ) -> Result<RangeInclusive<u64>, Error> {
+        let (subset_idx, cet_idx, adaptor_secret) = recover_adaptor_secret(
+            cp_finalized_tx,
+            self.controller_type,
+            &self.keypair.public_key(),
+            &self.cp_verification_key,
+            self.storages
+                .iter()
+                .map(|storage| storage.get_all_elements_vec_ref()),
+        )?;
+        // Storage elements were put at index of their parsed outcome, so we take the outcome from parsed contract
+        let outcome = self
+            .parsed_contract
+            .iter()
+            .map(|(outcome, _)| outcome)
+            .find(|outcome| outcome.to_u64() == cet_idx as u64)
+            .cloned()
+            .ok_or_else(|| attestation_error(AttestationError::NotRecoverable))?;
+        let outcome_value = outcome.to_u64();
+        self.attesting_subset = subset_idx;
+        self.attested_outcome = Some(outcome);
+        self.adaptor_secret = Some(adaptor_secret);
+        Ok(outcome_value..=outcome_value)
     }
 
     // fn broadcast_to_blockchain(self) -> Result<(), Error> {
@@ -0,0 +1,1634 @@
+// src/dlc_session/mod.rs
+
+use secp256k1_zkp::{
+    ecdsa, rand::Rng, schnorr, EcdsaAdaptorSignature, PublicKey, SchnorrAdaptorPreSignature,
+};
+use std::{fmt, io::Error, ops::RangeInclusive, str::FromStr, sync::Arc};
+
+#[cfg(feature = "schnorr-fun")]
+use crate::{
+    adaptor_signature_scheme::SchnorrFunAdaptorSignatureScheme,
+    crypto_utils::simple_fun_crypto_utils::SimpleFunCryptoUtils,
+};
+use crate::{
+    adaptor_signature_scheme::{EcdsaAdaptorSignatureScheme, SchnorrAdaptorSignatureScheme},
+    common::{error::VerificationReport, fun, seed::Seed, types, ParsedContract},
+    config::{MyOutcome, BASIS_WINDOW_BITS, DEFAULT_BASE, MAX_BASIS_WINDOW_BITS},
+    crypto_utils::{
+        basis_crypto_utils::BasisCryptoUtils, digit_crypto_utils::DigitCryptoUtils,
+        dlcspecs_crypto_utils::DlcSpecsCryptoUtils, simple_crypto_utils::SimpleCryptoUtils,
+    },
+    dlc_controller::{
+        compressed_controller::CompressedController, very_simple_controller::VerySimpleController,
+        ControllerType, DlcController,
+    },
+    oracle::{Oracle, OracleAnnouncement, OracleAttestation, RandIntOracle},
+};
+
+// INFO: `VerySimpleController` is monomorphized over adaptor signature scheme and crypto utils, which is
+// the fastest option, but it forces us to choose configuration at compile time. `DlcSession` wraps all
+// combinations into one enum, so that a single binary (or a single test run) can switch between them.
+// Prefix method uses `CompressedController` instead, which creates one CET per digit prefix.
+
+/// Adaptor signature scheme selectable at runtime. `SchnorrFun` (pure-Rust `schnorr_fun` backend) needs `schnorr-fun` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemeKind {
+    Ecdsa,
+    Schnorr,
+    #[cfg(feature = "schnorr-fun")]
+    SchnorrFun,
+}
+
+/// Anticipation point computation method selectable at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MethodKind {
+    Simple,
+    Basis,
+    Digit,
+    Prefix,
+    DlcSpecs, // Digit-by-digit BIP340 attestations of dlcspecs oracles (see `DlcSpecsCryptoUtils`)
+}
+
+impl SchemeKind {
+    #[cfg(not(feature = "schnorr-fun"))]
+    pub const ALL: [SchemeKind; 2] = [SchemeKind::Ecdsa, SchemeKind::Schnorr];
+    #[cfg(feature = "schnorr-fun")]
+    pub const ALL: [SchemeKind; 3] = [
+        SchemeKind::Ecdsa,
+        SchemeKind::Schnorr,
+        SchemeKind::SchnorrFun,
+    ];
+}
+
+impl MethodKind {
+    pub const ALL: [MethodKind; 5] = [
+        MethodKind::Simple,
+        MethodKind::Basis,
+        MethodKind::Digit,
+        MethodKind::Prefix,
+        MethodKind::DlcSpecs,
+    ];
+}
+
+impl fmt::Display for SchemeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemeKind::Ecdsa => write!(f, "ecdsa"),
+            SchemeKind::Schnorr => write!(f, "schnorr"),
+            #[cfg(feature = "schnorr-fun")]
+            SchemeKind::SchnorrFun => write!(f, "schnorr-fun"),
+        }
+    }
+}
+
+impl fmt::Display for MethodKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MethodKind::Simple => write!(f, "simple"),
+            MethodKind::Basis => write!(f, "basis"),
+            MethodKind::Digit => write!(f, "digit"),
+            MethodKind::Prefix => write!(f, "prefix"),
+            MethodKind::DlcSpecs => write!(f, "dlcspecs"),
+        }
+    }
+}
+
+impl FromStr for SchemeKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ecdsa" => Ok(SchemeKind::Ecdsa),
+            "schnorr" => Ok(SchemeKind::Schnorr),
+            #[cfg(feature = "schnorr-fun")]
+            "schnorr-fun" => Ok(SchemeKind::SchnorrFun),
+            _ => Err(invalid_input(format!(
+                "Unknown adaptor signature scheme '{}' (expected 'ecdsa', 'schnorr' or 'schnorr-fun' with `schnorr-fun` feature)",
+                s
+            ))),
+        }
+    }
+}
+
+impl FromStr for MethodKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "simple" => Ok(MethodKind::Simple),
+            "basis" => Ok(MethodKind::Basis),
+            "digit" => Ok(MethodKind::Digit),
+            "prefix" => Ok(MethodKind::Prefix),
+            "dlcspecs" => Ok(MethodKind::DlcSpecs),
+            _ => Err(invalid_input(format!(
+                "Unknown anticipation point method '{}' (expected 'simple', 'basis', 'digit', 'prefix' or 'dlcspecs')",
+                s
+            ))),
+        }
+    }
+}
+
+/// Runtime configuration of a DLC session. Default is the baseline (ECDSA + simple method).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionConfig {
+    pub scheme: SchemeKind,
+    pub method: MethodKind,
+    pub basis_window: u8, // Bits of precomputed tables of basis method (ignored by other methods)
+}
+
+impl SessionConfig {
+    pub fn new(scheme: SchemeKind, method: MethodKind) -> Self {
+        Self {
+            scheme,
+            method,
+            basis_window: BASIS_WINDOW_BITS,
+        }
+    }
+
+    /// Same configuration with basis method tables of `window_bits` bits (between 1 and `MAX_BASIS_WINDOW_BITS`).
+    pub fn with_basis_window(self, window_bits: u8) -> Self {
+        Self {
+            basis_window: window_bits,
+            ..self
+        }
+    }
+
+    /// Returns all supported scheme/method combinations.
+    pub fn all() -> Vec<SessionConfig> {
+        SchemeKind::ALL
+            .iter()
+            .flat_map(|&scheme| {
+                MethodKind::ALL
+                    .iter()
+                    .map(move |&method| Self::new(scheme, method))
+            })
+            .filter(SessionConfig::is_supported)
+            .collect()
+    }
+
+    /// Whether a session can run with this configuration (`schnorr_fun` only has utilities of simple method).
+    pub fn is_supported(&self) -> bool {
+        #[cfg(feature = "schnorr-fun")]
+        if self.scheme == SchemeKind::SchnorrFun {
+            return self.method == MethodKind::Simple;
+        }
+        true
+    }
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self::new(SchemeKind::Ecdsa, MethodKind::Simple)
+    }
+}
+
+impl fmt::Display for SessionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} + {} method", self.scheme, self.method)?;
+        if self.method == MethodKind::Basis {
+            write!(f, " ({}-bit windows)", self.basis_window)?;
+        }
+        Ok(())
+    }
+}
+
+/// Oracle shared by session controllers. Oracle must attest with the same crypto utils
+/// as controllers use for anticipation points, so it is selected by `MethodKind` as well.
+/// Digit and prefix methods both need oracle attesting digit-by-digit.
+/// DlcSpecs oracle signs outcome strings as external dlcspecs oracles do.
+pub enum SessionOracle {
+    Simple(RandIntOracle<SimpleCryptoUtils, MyOutcome>),
+    Basis(RandIntOracle<BasisCryptoUtils, MyOutcome>),
+    Digit(RandIntOracle<DigitCryptoUtils, MyOutcome>),
+    Prefix(RandIntOracle<DigitCryptoUtils, MyOutcome>),
+    DlcSpecs(RandIntOracle<DlcSpecsCryptoUtils, MyOutcome>),
+}
+
+impl SessionOracle {
+    pub fn new(method: MethodKind, nb_digits: u8) -> Self {
+        match method {
+            MethodKind::Simple => SessionOracle::Simple(RandIntOracle::new(nb_digits)),
+            MethodKind::Basis => SessionOracle::Basis(RandIntOracle::new(nb_digits)),
+            MethodKind::Digit => SessionOracle::Digit(RandIntOracle::new(nb_digits)),
+            MethodKind::Prefix => SessionOracle::Prefix(RandIntOracle::new(nb_digits)),
+            MethodKind::DlcSpecs => SessionOracle::DlcSpecs(RandIntOracle::new(nb_digits)),
+        }
+    }
+
+    /// Oracle attesting to given outcome (see `RandIntOracle::with_outcome`).
+    pub fn with_outcome(method: MethodKind, nb_digits: u8, outcome: u64) -> Self {
+        Self::with_outcome_in_base(method, nb_digits, DEFAULT_BASE, outcome)
+    }
+
+    /// Oracle attesting to given outcome with digits in given base (see `RandIntOracle::with_outcome_in_base`).
+    pub fn with_outcome_in_base(method: MethodKind, nb_digits: u8, base: u8, outcome: u64) -> Self {
+        Self::with_outcome_seeded(method, nb_digits, base, outcome, Seed::random())
+    }
+
+    /// Oracle attesting to given outcome with keys and nonces derived from seed (see `RandIntOracle::with_outcome_seeded`).
+    pub fn with_outcome_seeded(
+        method: MethodKind,
+        nb_digits: u8,
+        base: u8,
+        outcome: u64,
+        seed: Seed,
+    ) -> Self {
+        match method {
+            MethodKind::Simple => SessionOracle::Simple(RandIntOracle::with_outcome_seeded(
+                nb_digits, base, outcome, seed,
+            )),
+            MethodKind::Basis => SessionOracle::Basis(RandIntOracle::with_outcome_seeded(
+                nb_digits, base, outcome, seed,
+            )),
+            MethodKind::Digit => SessionOracle::Digit(RandIntOracle::with_outcome_seeded(
+                nb_digits, base, outcome, seed,
+            )),
+            MethodKind::Prefix => SessionOracle::Prefix(RandIntOracle::with_outcome_seeded(
+                nb_digits, base, outcome, seed,
+            )),
+            MethodKind::DlcSpecs => SessionOracle::DlcSpecs(RandIntOracle::with_outcome_seeded(
+                nb_digits, base, outcome, seed,
+            )),
+        }
+    }
+
+    /// Group of `nb_oracles` independent oracles (own keys and nonces), attesting to random outcomes that differ
+    /// by at most `max_difference` from each other, as price feeds would. With 0 they all attest to the same outcome.
+    pub fn new_group(
+        method: MethodKind,
+        nb_digits: u8,
+        nb_oracles: usize,
+        max_difference: u64,
+    ) -> Vec<Arc<Self>> {
+        Self::new_group_in_base(
+            method,
+            nb_digits,
+            DEFAULT_BASE,
+            nb_oracles,
+            max_difference,
+            Seed::random(),
+        )
+    }
+
+    /// Group of oracles (see `new_group`) attesting to outcomes with digits in given base. Outcomes, keys and nonces
+    /// are derived from seed, each oracle has its own stream.
+    pub fn new_group_in_base(
+        method: MethodKind,
+        nb_digits: u8,
+        base: u8,
+        nb_oracles: usize,
+        max_difference: u64,
+        seed: Seed,
+    ) -> Vec<Arc<Self>> {
+        let mut rng = seed.rng("outcomes");
+        let max_outcome = fun::max_outcome(nb_digits, base);
+        let spread = max_difference.min(max_outcome);
+        let lowest_outcome = rng.gen_range(0..=max_outcome - spread);
+        (0..nb_oracles)
+            .map(|i| {
+                let outcome = lowest_outcome + rng.gen_range(0..=spread);
+                Arc::new(SessionOracle::with_outcome_seeded(
+                    method,
+                    nb_digits,
+                    base,
+                    outcome,
+                    seed.derive(&format!("oracle/{}", i)),
+                ))
+            })
+            .collect()
+    }
+
+    /// Oracle of enumerated event attesting to label at given index (see `RandIntOracle::enumerated_with_outcome`).
+    pub fn enumerated_with_outcome(method: MethodKind, labels: Vec<String>, index: u32) -> Self {
+        Self::enumerated_with_outcome_seeded(method, labels, index, Seed::random())
+    }
+
+    /// Oracle of enumerated event attesting to label at given index, with keys and nonces derived from seed.
+    pub fn enumerated_with_outcome_seeded(
+        method: MethodKind,
+        labels: Vec<String>,
+        index: u32,
+        seed: Seed,
+    ) -> Self {
+        match method {
+            MethodKind::Simple => SessionOracle::Simple(
+                RandIntOracle::enumerated_with_outcome_seeded(labels, index, seed),
+            ),
+            MethodKind::Basis => SessionOracle::Basis(
+                RandIntOracle::enumerated_with_outcome_seeded(labels, index, seed),
+            ),
+            MethodKind::Digit => SessionOracle::Digit(
+                RandIntOracle::enumerated_with_outcome_seeded(labels, index, seed),
+            ),
+            MethodKind::Prefix => SessionOracle::Prefix(
+                RandIntOracle::enumerated_with_outcome_seeded(labels, index, seed),
+            ),
+            MethodKind::DlcSpecs => SessionOracle::DlcSpecs(
+                RandIntOracle::enumerated_with_outcome_seeded(labels, index, seed),
+            ),
+        }
+    }
+
+    /// Group of `nb_oracles` independent oracles of enumerated event, all attesting to the same random label.
+    /// Label, keys and nonces are derived from seed (see `new_group_in_base`).
+    pub fn new_enumerated_group(
+        method: MethodKind,
+        labels: Vec<String>,
+        nb_oracles: usize,
+        seed: Seed,
+    ) -> Vec<Arc<Self>> {
+        let index = seed.rng("outcomes").gen_range(0..labels.len()) as u32;
+        (0..nb_oracles)
+            .map(|i| {
+                Arc::new(SessionOracle::enumerated_with_outcome_seeded(
+                    method,
+                    labels.clone(),
+                    index,
+                    seed.derive(&format!("oracle/{}", i)),
+                ))
+            })
+            .collect()
+    }
+
+    pub fn method(&self) -> MethodKind {
+        match self {
+            SessionOracle::Simple(_) => MethodKind::Simple,
+            SessionOracle::Basis(_) => MethodKind::Basis,
+            SessionOracle::Digit(_) => MethodKind::Digit,
+            SessionOracle::Prefix(_) => MethodKind::Prefix,
+            SessionOracle::DlcSpecs(_) => MethodKind::DlcSpecs,
+        }
+    }
+
+    pub fn get_outcome(&self) -> u64 {
+        match self {
+            SessionOracle::Simple(oracle) => oracle.get_outcome(),
+            SessionOracle::Basis(oracle) => oracle.get_outcome(),
+            SessionOracle::Digit(oracle) => oracle.get_outcome(),
+            SessionOracle::Prefix(oracle) => oracle.get_outcome(),
+            SessionOracle::DlcSpecs(oracle) => oracle.get_outcome(),
+        }
+    }
+}
+
+impl Oracle for SessionOracle {
+    type Outcome = MyOutcome;
+
+    fn get_public_key(&self) -> PublicKey {
+        match self {
+            SessionOracle::Simple(oracle) => oracle.get_public_key(),
+            SessionOracle::Basis(oracle) => oracle.get_public_key(),
+            SessionOracle::Digit(oracle) => oracle.get_public_key(),
+            SessionOracle::Prefix(oracle) => oracle.get_public_key(),
+            SessionOracle::DlcSpecs(oracle) => oracle.get_public_key(),
+        }
+    }
+
+    fn get_event_announcement(&self, event_id: u32) -> OracleAnnouncement {
+        match self {
+            SessionOracle::Simple(oracle) => oracle.get_event_announcement(event_id),
+            SessionOracle::Basis(oracle) => oracle.get_event_announcement(event_id),
+            SessionOracle::Digit(oracle) => oracle.get_event_announcement(event_id),
+            SessionOracle::Prefix(oracle) => oracle.get_event_announcement(event_id),
+            SessionOracle::DlcSpecs(oracle) => oracle.get_event_announcement(event_id),
+        }
+    }
+
+    fn get_event_attestation(&self, event_id: u32) -> OracleAttestation<MyOutcome> {
+        match self {
+            SessionOracle::Simple(oracle) => oracle.get_event_attestation(event_id),
+            SessionOracle::Basis(oracle) => oracle.get_event_attestation(event_id),
+            SessionOracle::Digit(oracle) => oracle.get_event_attestation(event_id),
+            SessionOracle::Prefix(oracle) => oracle.get_event_attestation(event_id),
+            SessionOracle::DlcSpecs(oracle) => oracle.get_event_attestation(event_id),
+        }
+    }
+}
+
+/// Adaptor signatures exchanged between session controllers.
+pub enum SessionAdaptors {
+    Ecdsa(Vec<EcdsaAdaptorSignature>),
+    Schnorr(Vec<SchnorrAdaptorPreSignature>),
+    #[cfg(feature = "schnorr-fun")]
+    SchnorrFun(Vec<schnorr_fun::adaptor::EncryptedSignature>),
+}
+
+/// Finalized transaction produced by a session controller.
+pub enum SessionFinalizedTx {
+    Ecdsa(types::FinalizedTx<ecdsa::Signature>),
+    Schnorr(types::FinalizedTx<schnorr::Signature>),
+    #[cfg(feature = "schnorr-fun")]
+    SchnorrFun(Box<types::FinalizedTx<schnorr_fun::Signature>>), // Boxed, as `schnorr_fun` signature holds uncompressed nonce point
+}
+
+impl SessionFinalizedTx {
+    pub fn payload(&self) -> &types::Cet {
+        match self {
+            SessionFinalizedTx::Ecdsa(tx) => &tx.payload,
+            SessionFinalizedTx::Schnorr(tx) => &tx.payload,
+            #[cfg(feature = "schnorr-fun")]
+            SessionFinalizedTx::SchnorrFun(tx) => &tx.payload,
+        }
+    }
+
+    /// Validates (locally) both signatures of the finalized transaction against the multisig keys.
+    pub fn is_valid(&self, multisig: &types::MultisigFundAddress) -> bool {
+        match self {
+            SessionFinalizedTx::Ecdsa(tx) => {
+                fun::finalized_tx_valid::<EcdsaAdaptorSignatureScheme>(tx, multisig)
+            }
+            SessionFinalizedTx::Schnorr(tx) => {
+                fun::finalized_tx_valid::<SchnorrAdaptorSignatureScheme>(tx, multisig)
+            }
+            #[cfg(feature = "schnorr-fun")]
+            SessionFinalizedTx::SchnorrFun(tx) => {
+                fun::finalized_tx_valid::<SchnorrFunAdaptorSignatureScheme>(tx, multisig)
+            }
+        }
+    }
+}
+
+type SessionController<ASigS, CU> = VerySimpleController<ASigS, CU, SessionOracle>;
+type SessionCompressedController<ASigS> = CompressedController<ASigS, SessionOracle>;
+
+/// DLC controller with adaptor signature scheme and anticipation point method chosen at runtime.
+pub enum DlcSession {
+    EcdsaSimple(SessionController<EcdsaAdaptorSignatureScheme, SimpleCryptoUtils>),
+    EcdsaBasis(SessionController<EcdsaAdaptorSignatureScheme, BasisCryptoUtils>),
+    EcdsaDigit(SessionController<EcdsaAdaptorSignatureScheme, DigitCryptoUtils>),
+    SchnorrSimple(SessionController<SchnorrAdaptorSignatureScheme, SimpleCryptoUtils>),
+    SchnorrBasis(SessionController<SchnorrAdaptorSignatureScheme, BasisCryptoUtils>),
+    SchnorrDigit(SessionController<SchnorrAdaptorSignatureScheme, DigitCryptoUtils>),
+    EcdsaPrefix(SessionCompressedController<EcdsaAdaptorSignatureScheme>),
+    SchnorrPrefix(SessionCompressedController<SchnorrAdaptorSignatureScheme>),
+    EcdsaDlcSpecs(SessionController<EcdsaAdaptorSignatureScheme, DlcSpecsCryptoUtils>),
+    SchnorrDlcSpecs(SessionController<SchnorrAdaptorSignatureScheme, DlcSpecsCryptoUtils>),
+    // Pure-Rust stack, only simple method computes anticipation points with `secp256kfun` as well
+    #[cfg(feature = "schnorr-fun")]
+    SchnorrFunSimple(SessionController<SchnorrFunAdaptorSignatureScheme, SimpleFunCryptoUtils>),
+}
+
+// Forwards a call to the wrapped controller, whichever variant it is.
+macro_rules! dispatch {
+    ($session:expr, $controller:ident => $body:expr) => {
+        match $session {
+            DlcSession::EcdsaSimple($controller) => $body,
+            DlcSession::EcdsaBasis($controller) => $body,
+            DlcSession::EcdsaDigit($controller) => $body,
+            DlcSession::SchnorrSimple($controller) => $body,
+            DlcSession::SchnorrBasis($controller) => $body,
+            DlcSession::SchnorrDigit($controller) => $body,
+            DlcSession::EcdsaPrefix($controller) => $body,
+            DlcSession::SchnorrPrefix($controller) => $body,
+            DlcSession::EcdsaDlcSpecs($controller) => $body,
+            DlcSession::SchnorrDlcSpecs($controller) => $body,
+            #[cfg(feature = "schnorr-fun")]
+            DlcSession::SchnorrFunSimple($controller) => $body,
+        }
+    };
+}
+
+impl DlcSession {
+    /// Creates a new session controller with oracles of the contract (in order of their public keys in contract).
+    /// All oracles must attest using the same method as the session.
+    pub fn new(
+        config: SessionConfig,
+        ctype: ControllerType,
+        oracles: Vec<Arc<SessionOracle>>,
+    ) -> Result<Self, Error> {
+        Self::with_seed(config, ctype, oracles, Seed::random())
+    }
+
+    /// Same as `new`, but controller's keys and pre-signature nonces are derived from run seed (see `Seed`).
+    pub fn with_seed(
+        config: SessionConfig,
+        ctype: ControllerType,
+        oracles: Vec<Arc<SessionOracle>>,
+        seed: Seed,
+    ) -> Result<Self, Error> {
+        if !(1..=MAX_BASIS_WINDOW_BITS).contains(&config.basis_window) {
+            return Err(invalid_input(format!(
+                "Basis window must have between 1 and {} bits, got {}",
+                MAX_BASIS_WINDOW_BITS, config.basis_window
+            )));
+        }
+        if let Some(oracle) = oracles.iter().find(|o| o.method() != config.method) {
+            return Err(invalid_input(format!(
+                "Oracle attests with {} method, but session uses {} method",
+                oracle.method(),
+                config.method
+            )));
+        }
+
+        Ok(match (config.scheme, config.method) {
+            (SchemeKind::Ecdsa, MethodKind::Simple) => {
+                DlcSession::EcdsaSimple(VerySimpleController::with_seed(ctype, oracles, seed))
+            }
+            (SchemeKind::Ecdsa, MethodKind::Basis) => DlcSession::EcdsaBasis(
+                VerySimpleController::with_seed(ctype, oracles, seed)
+                    .with_basis_window(config.basis_window),
+            ),
+            (SchemeKind::Ecdsa, MethodKind::Digit) => {
+                DlcSession::EcdsaDigit(VerySimpleController::with_seed(ctype, oracles, seed))
+            }
+            (SchemeKind::Schnorr, MethodKind::Simple) => {
+                DlcSession::SchnorrSimple(VerySimpleController::with_seed(ctype, oracles, seed))
+            }
+            (SchemeKind::Schnorr, MethodKind::Basis) => DlcSession::SchnorrBasis(
+                VerySimpleController::with_seed(ctype, oracles, seed)
+                    .with_basis_window(config.basis_window),
+            ),
+            (SchemeKind::Schnorr, MethodKind::Digit) => {
+                DlcSession::SchnorrDigit(VerySimpleController::with_seed(ctype, oracles, seed))
+            }
+            (SchemeKind::Ecdsa, MethodKind::Prefix) => {
+                DlcSession::EcdsaPrefix(CompressedController::with_seed(ctype, oracles, seed))
+            }
+            (SchemeKind::Schnorr, MethodKind::Prefix) => {
+                DlcSession::SchnorrPrefix(CompressedController::with_seed(ctype, oracles, seed))
+            }
+            (SchemeKind::Ecdsa, MethodKind::DlcSpecs) => {
+                DlcSession::EcdsaDlcSpecs(VerySimpleController::with_seed(ctype, oracles, seed))
+            }
+            (SchemeKind::Schnorr, MethodKind::DlcSpecs) => {
+                DlcSession::SchnorrDlcSpecs(VerySimpleController::with_seed(ctype, oracles, seed))
+            }
+            #[cfg(feature = "schnorr-fun")]
+            (SchemeKind::SchnorrFun, MethodKind::Simple) => {
+                DlcSession::SchnorrFunSimple(VerySimpleController::with_seed(ctype, oracles, seed))
+            }
+            #[cfg(feature = "schnorr-fun")]
+            (SchemeKind::SchnorrFun, method) => {
+                return Err(invalid_input(format!(
+                "{} scheme only supports simple method (no secp256kfun utilities for {} method)",
+                config.scheme, method
+            )))
+            }
+        })
+    }
+
+    pub fn config(&self) -> SessionConfig {
+        let config = match self {
+            DlcSession::EcdsaSimple(_) => SessionConfig::new(SchemeKind::Ecdsa, MethodKind::Simple),
+            DlcSession::EcdsaBasis(_) => SessionConfig::new(SchemeKind::Ecdsa, MethodKind::Basis),
+            DlcSession::EcdsaDigit(_) => SessionConfig::new(SchemeKind::Ecdsa, MethodKind::Digit),
+            DlcSession::SchnorrSimple(_) => {
+                SessionConfig::new(SchemeKind::Schnorr, MethodKind::Simple)
+            }
+            DlcSession::SchnorrBasis(_) => {
+                SessionConfig::new(SchemeKind::Schnorr, MethodKind::Basis)
+            }
+            DlcSession::SchnorrDigit(_) => {
+                SessionConfig::new(SchemeKind::Schnorr, MethodKind::Digit)
+            }
+            DlcSession::EcdsaPrefix(_) => SessionConfig::new(SchemeKind::Ecdsa, MethodKind::Prefix),
+            DlcSession::SchnorrPrefix(_) => {
+                SessionConfig::new(SchemeKind::Schnorr, MethodKind::Prefix)
+            }
+            DlcSession::EcdsaDlcSpecs(_) => {
+                SessionConfig::new(SchemeKind::Ecdsa, MethodKind::DlcSpecs)
+            }
+            DlcSession::SchnorrDlcSpecs(_) => {
+                SessionConfig::new(SchemeKind::Schnorr, MethodKind::DlcSpecs)
+            }
+            #[cfg(feature = "schnorr-fun")]
+            DlcSession::SchnorrFunSimple(_) => {
+                SessionConfig::new(SchemeKind::SchnorrFun, MethodKind::Simple)
+            }
+        };
+        // Only basis controllers keep their window
+        match self {
+            DlcSession::EcdsaBasis(controller) => {
+                config.with_basis_window(controller.basis_window())
+            }
+            DlcSession::SchnorrBasis(controller) => {
+                config.with_basis_window(controller.basis_window())
+            }
+            _ => config,
+        }
+    }
+
+    pub fn load_input(&mut self, input_path: &str) -> Result<(), Error> {
+        dispatch!(self, controller => controller.load_input(input_path))
+    }
+
+    pub fn init_storage(&mut self) -> Result<(), Error> {
+        dispatch!(self, controller => controller.init_storage())
+    }
+
+    /// (outcome, payout) pairs of contract loaded by `load_input`.
+    pub fn parsed_contract(&self) -> &ParsedContract<MyOutcome> {
+        dispatch!(self, controller => controller.parsed_contract())
+    }
+
+    /// Number of CETs (and adaptor signatures) each party creates for the loaded contract.
+    pub fn nb_cets(&self) -> usize {
+        dispatch!(self, controller => controller.nb_cets())
+    }
+
+    pub fn share_verification_key(&self) -> PublicKey {
+        dispatch!(self, controller => controller.share_verification_key())
+    }
+
+    pub fn share_adaptors(&self) -> SessionAdaptors {
+        match self {
+            DlcSession::EcdsaSimple(c) => SessionAdaptors::Ecdsa(c.share_adaptors()),
+            DlcSession::EcdsaBasis(c) => SessionAdaptors::Ecdsa(c.share_adaptors()),
+            DlcSession::EcdsaDigit(c) => SessionAdaptors::Ecdsa(c.share_adaptors()),
+            DlcSession::SchnorrSimple(c) => SessionAdaptors::Schnorr(c.share_adaptors()),
+            DlcSession::SchnorrBasis(c) => SessionAdaptors::Schnorr(c.share_adaptors()),
+            DlcSession::SchnorrDigit(c) => SessionAdaptors::Schnorr(c.share_adaptors()),
+            DlcSession::EcdsaPrefix(c) => SessionAdaptors::Ecdsa(c.share_adaptors()),
+            DlcSession::SchnorrPrefix(c) => SessionAdaptors::Schnorr(c.share_adaptors()),
+            DlcSession::EcdsaDlcSpecs(c) => SessionAdaptors::Ecdsa(c.share_adaptors()),
+            DlcSession::SchnorrDlcSpecs(c) => SessionAdaptors::Schnorr(c.share_adaptors()),
+            #[cfg(feature = "schnorr-fun")]
+            DlcSession::SchnorrFunSimple(c) => SessionAdaptors::SchnorrFun(c.share_adaptors()),
+        }
+    }
+
+    pub fn save_cp_verification_key(&mut self, cp_verification_key: PublicKey) {
+        dispatch!(self, controller => controller.save_cp_verification_key(cp_verification_key))
+    }
+
+    /// Saves the counterparty's adaptors. Fails if they belong to a different adaptor signature scheme.
+    pub fn save_cp_adaptors(&mut self, cp_adaptors: SessionAdaptors) -> Result<(), Error> {
+        match (self, cp_adaptors) {
+            (DlcSession::EcdsaSimple(c), SessionAdaptors::Ecdsa(a)) => c.save_cp_adaptors(a),
+            (DlcSession::EcdsaBasis(c), SessionAdaptors::Ecdsa(a)) => c.save_cp_adaptors(a),
+            (DlcSession::EcdsaDigit(c), SessionAdaptors::Ecdsa(a)) => c.save_cp_adaptors(a),
+            (DlcSession::SchnorrSimple(c), SessionAdaptors::Schnorr(a)) => c.save_cp_adaptors(a),
+            (DlcSession::SchnorrBasis(c), SessionAdaptors::Schnorr(a)) => c.save_cp_adaptors(a),
+            (DlcSession::SchnorrDigit(c), SessionAdaptors::Schnorr(a)) => c.save_cp_adaptors(a),
+            (DlcSession::EcdsaPrefix(c), SessionAdaptors::Ecdsa(a)) => c.save_cp_adaptors(a),
+            (DlcSession::SchnorrPrefix(c), SessionAdaptors::Schnorr(a)) => c.save_cp_adaptors(a),
+            (DlcSession::EcdsaDlcSpecs(c), SessionAdaptors::Ecdsa(a)) => c.save_cp_adaptors(a),
+            (DlcSession::SchnorrDlcSpecs(c), SessionAdaptors::Schnorr(a)) => c.save_cp_adaptors(a),
+            #[cfg(feature = "schnorr-fun")]
+            (DlcSession::SchnorrFunSimple(c), SessionAdaptors::SchnorrFun(a)) => {
+                c.save_cp_adaptors(a)
+            }
+            _ => {
+                return Err(invalid_input(
+                    "Counterparty adaptors use a different adaptor signature scheme".to_string(),
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    pub fn verify_cp_adaptors(&self) -> VerificationReport {
+        dispatch!(self, controller => controller.verify_cp_adaptors())
+    }
+
+    pub fn update_cp_adaptors(&mut self) -> Result<(), Error> {
+        dispatch!(self, controller => controller.update_cp_adaptors())
+    }
+
+    pub fn wait_attestation(&mut self) -> Result<(), Error> {
+        dispatch!(self, controller => controller.wait_attestation())
+    }
+
+    pub fn finalize_tx(&self) -> Result<SessionFinalizedTx, Error> {
+        Ok(match self {
+            DlcSession::EcdsaSimple(c) => SessionFinalizedTx::Ecdsa(c.finalize_tx()?),
+            DlcSession::EcdsaBasis(c) => SessionFinalizedTx::Ecdsa(c.finalize_tx()?),
+            DlcSession::EcdsaDigit(c) => SessionFinalizedTx::Ecdsa(c.finalize_tx()?),
+            DlcSession::SchnorrSimple(c) => SessionFinalizedTx::Schnorr(c.finalize_tx()?),
+            DlcSession::SchnorrBasis(c) => SessionFinalizedTx::Schnorr(c.finalize_tx()?),
+            DlcSession::SchnorrDigit(c) => SessionFinalizedTx::Schnorr(c.finalize_tx()?),
+            DlcSession::EcdsaPrefix(c) => SessionFinalizedTx::Ecdsa(c.finalize_tx()?),
+            DlcSession::SchnorrPrefix(c) => SessionFinalizedTx::Schnorr(c.finalize_tx()?),
+            DlcSession::EcdsaDlcSpecs(c) => SessionFinalizedTx::Ecdsa(c.finalize_tx()?),
+            DlcSession::SchnorrDlcSpecs(c) => SessionFinalizedTx::Schnorr(c.finalize_tx()?),
+            #[cfg(feature = "schnorr-fun")]
+            DlcSession::SchnorrFunSimple(c) => {
+                SessionFinalizedTx::SchnorrFun(Box::new(c.finalize_tx()?))
+            }
+        })
+    }
+
+    /// Recovers attestation from CET broadcast by counterparty, see `DlcController::recover_attestation`.
+    /// Fails also if the transaction belongs to a different adaptor signature scheme.
+    pub fn recover_attestation(
+        &mut self,
+        cp_finalized_tx: &SessionFinalizedTx,
+    ) -> Result<RangeInclusive<u64>, Error> {
+        match (self, cp_finalized_tx) {
+            (DlcSession::EcdsaSimple(c), SessionFinalizedTx::Ecdsa(tx)) => {
+                c.recover_attestation(tx)
+            }
+            (DlcSession::EcdsaBasis(c), SessionFinalizedTx::Ecdsa(tx)) => c.recover_attestation(tx),
+            (DlcSession::EcdsaDigit(c), SessionFinalizedTx::Ecdsa(tx)) => c.recover_attestation(tx),
+            (DlcSession::SchnorrSimple(c), SessionFinalizedTx::Schnorr(tx)) => {
+                c.recover_attestation(tx)
+            }
+            (DlcSession::SchnorrBasis(c), SessionFinalizedTx::Schnorr(tx)) => {
+                c.recover_attestation(tx)
+            }
+            (DlcSession::SchnorrDigit(c), SessionFinalizedTx::Schnorr(tx)) => {
+                c.recover_attestation(tx)
+            }
+            (DlcSession::EcdsaPrefix(c), SessionFinalizedTx::Ecdsa(tx)) => {
+                c.recover_attestation(tx)
+            }
+            (DlcSession::SchnorrPrefix(c), SessionFinalizedTx::Schnorr(tx)) => {
+                c.recover_attestation(tx)
+            }
+            (DlcSession::EcdsaDlcSpecs(c), SessionFinalizedTx::Ecdsa(tx)) => {
+                c.recover_attestation(tx)
+            }
+            (DlcSession::SchnorrDlcSpecs(c), SessionFinalizedTx::Schnorr(tx)) => {
+                c.recover_attestation(tx)
+            }
+            #[cfg(feature = "schnorr-fun")]
+            (DlcSession::SchnorrFunSimple(c), SessionFinalizedTx::SchnorrFun(tx)) => {
+                c.recover_attestation(tx)
+            }
+            _ => Err(invalid_input(
+                "Broadcast transaction uses a different adaptor signature scheme".to_string(),
+            )),
+        }
+    }
+}
+
+fn invalid_input(msg: String) -> Error {
+    Error::new(std::io::ErrorKind::InvalidInput, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adaptor_signature_scheme::AdaptorSignatureScheme;
+    use crate::common::{
+        error::AttestationError, NumericOutcome, OutcomeBinStr, OutcomeU32, OutcomeU64,
+    };
+    use crate::crypto_utils::CryptoUtils;
+
+    #[test]
+    fn test_parse_session_kinds() {
+        assert_eq!("ECDSA".parse::<SchemeKind>().unwrap(), SchemeKind::Ecdsa);
+        assert_eq!(
+            "schnorr".parse::<SchemeKind>().unwrap(),
+            SchemeKind::Schnorr
+        );
+        assert_eq!("simple".parse::<MethodKind>().unwrap(), MethodKind::Simple);
+        assert_eq!("Basis".parse::<MethodKind>().unwrap(), MethodKind::Basis);
+        assert_eq!("Digit".parse::<MethodKind>().unwrap(), MethodKind::Digit);
+        assert_eq!("prefix".parse::<MethodKind>().unwrap(), MethodKind::Prefix);
+        assert_eq!(
+            "DlcSpecs".parse::<MethodKind>().unwrap(),
+            MethodKind::DlcSpecs
+        );
+        #[cfg(feature = "schnorr-fun")]
+        assert_eq!(
+            "schnorr-fun".parse::<SchemeKind>().unwrap(),
+            SchemeKind::SchnorrFun
+        );
+        assert!("rsa".parse::<SchemeKind>().is_err());
+        assert!("fancy".parse::<MethodKind>().is_err());
+    }
+
+    #[test]
+    fn test_all_configs() {
+        let configs = SessionConfig::all();
+        for scheme in SchemeKind::ALL {
+            for method in MethodKind::ALL {
+                let config = SessionConfig::new(scheme, method);
+                assert_eq!(
+                    configs.contains(&config),
+                    config.is_supported(),
+                    "{}",
+                    config
+                );
+            }
+        }
+        let nb_unsupported = if cfg!(feature = "schnorr-fun") {
+            MethodKind::ALL.len() - 1
+        } else {
+            0
+        };
+        assert_eq!(
+            configs.len(),
+            SchemeKind::ALL.len() * MethodKind::ALL.len() - nb_unsupported
+        );
+    }
+
+    #[cfg(feature = "schnorr-fun")]
+    #[test]
+    fn test_schnorr_fun_rejects_other_methods() {
+        for method in [
+            MethodKind::Basis,
+            MethodKind::Digit,
+            MethodKind::Prefix,
+            MethodKind::DlcSpecs,
+        ] {
+            let config = SessionConfig::new(SchemeKind::SchnorrFun, method);
+            assert!(!config.is_supported());
+            let oracles = SessionOracle::new_group(method, SIMPLE_CONTRACT_NB_DIGITS, 1, 0);
+            let error = DlcSession::new(config, ControllerType::Offerer, oracles)
+                .err()
+                .expect("schnorr-fun only supports simple method");
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        }
+    }
+
+    const SIMPLE_CONTRACT_PATH: &str =
+        "./input_contracts/sample_contracts/simple_contract_input.json";
+    const SIMPLE_CONTRACT_NB_DIGITS: u8 = 3;
+
+    #[test]
+    fn test_basis_window_config() {
+        for window_bits in [1, 2, 8] {
+            let config = SessionConfig::new(SchemeKind::Schnorr, MethodKind::Basis)
+                .with_basis_window(window_bits);
+            let oracles = SessionOracle::new_group(config.method, SIMPLE_CONTRACT_NB_DIGITS, 1, 0);
+            let (mut alice, mut bob) = setup_sessions(config, &oracles, SIMPLE_CONTRACT_PATH);
+            assert_eq!(alice.config(), config);
+            settle_sessions(config, &mut alice, &mut bob);
+        }
+
+        let oracles = SessionOracle::new_group(MethodKind::Basis, SIMPLE_CONTRACT_NB_DIGITS, 1, 0);
+        for window_bits in [0, MAX_BASIS_WINDOW_BITS + 1] {
+            let config = SessionConfig::new(SchemeKind::Ecdsa, MethodKind::Basis)
+                .with_basis_window(window_bits);
+            assert!(DlcSession::new(config, ControllerType::Offerer, oracles.clone()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_session_flow_all_configs() {
+        for config in SessionConfig::all() {
+            let oracles = SessionOracle::new_group(config.method, SIMPLE_CONTRACT_NB_DIGITS, 1, 0);
+            let (mut alice, mut bob) = setup_sessions(config, &oracles, SIMPLE_CONTRACT_PATH);
+            settle_sessions(config, &mut alice, &mut bob);
+        }
+    }
+
+    #[test]
+    fn test_seeded_session_replays() {
+        // Keys, oracle outcome and adaptors (ECDSA has randomized pre-signature nonces) of the same seed match
+        let seeded_run = |method: MethodKind, seed: Seed| {
+            let config = SessionConfig::new(SchemeKind::Ecdsa, method);
+            let oracles = SessionOracle::new_group_in_base(
+                method,
+                SIMPLE_CONTRACT_NB_DIGITS,
+                DEFAULT_BASE,
+                1,
+                0,
+                seed.derive("oracles"),
+            );
+            let mut alice =
+                DlcSession::with_seed(config, ControllerType::Offerer, oracles.clone(), seed)
+                    .unwrap();
+            alice.load_input(SIMPLE_CONTRACT_PATH).unwrap();
+            alice.init_storage().unwrap();
+            let SessionAdaptors::Ecdsa(adaptors) = alice.share_adaptors() else {
+                panic!("ECDSA session shares ECDSA adaptors");
+            };
+            let oracle_keys: Vec<(PublicKey, u64)> = oracles
+                .iter()
+                .map(|oracle| (oracle.get_public_key(), oracle.get_outcome()))
+                .collect();
+            (alice.share_verification_key(), oracle_keys, adaptors)
+        };
+
+        for method in MethodKind::ALL {
+            let run = seeded_run(method, Seed::new(7));
+            assert_eq!(run, seeded_run(method, Seed::new(7)), "{}", method);
+            let other_run = seeded_run(method, Seed::new(8));
+            assert_ne!(run.0, other_run.0);
+            assert_ne!(run.1, other_run.1);
+        }
+
+        // Parties sharing run seed still have their own keys
+        let oracles = SessionOracle::new_group(MethodKind::Simple, SIMPLE_CONTRACT_NB_DIGITS, 1, 0);
+        let keys: Vec<PublicKey> = [ControllerType::Offerer, ControllerType::Accepter]
+            .into_iter()
+            .map(|ctype| {
+                DlcSession::with_seed(
+                    SessionConfig::default(),
+                    ctype,
+                    oracles.clone(),
+                    Seed::new(7),
+                )
+                .unwrap()
+                .share_verification_key()
+            })
+            .collect();
+        assert_ne!(keys[0], keys[1]);
+    }
+
+    // Applies `$tamper` to adaptors vector, whichever scheme they belong to
+    macro_rules! tamper_adaptors {
+        ($adaptors:expr, |$a:ident| $tamper:expr) => {
+            match $adaptors {
+                SessionAdaptors::Ecdsa(mut $a) => {
+                    $tamper;
+                    SessionAdaptors::Ecdsa($a)
+                }
+                SessionAdaptors::Schnorr(mut $a) => {
+                    $tamper;
+                    SessionAdaptors::Schnorr($a)
+                }
+                #[cfg(feature = "schnorr-fun")]
+                SessionAdaptors::SchnorrFun(mut $a) => {
+                    $tamper;
+                    SessionAdaptors::SchnorrFun($a)
+                }
+            }
+        };
+    }
+
+    #[test]
+    fn test_invalid_cp_adaptors_reported() {
+        use crate::common::error::AdaptorVerificationFailure;
+
+        for config in SessionConfig::all() {
+            let oracles = SessionOracle::new_group(config.method, SIMPLE_CONTRACT_NB_DIGITS, 1, 0);
+            let (mut alice, bob) = setup_sessions(config, &oracles, SIMPLE_CONTRACT_PATH);
+            let nb_cets = alice.nb_cets();
+
+            // Swapped adaptors are pre-signatures of each other's CETs
+            let swapped = tamper_adaptors!(bob.share_adaptors(), |a| a.swap(0, 1));
+            alice.save_cp_adaptors(swapped).unwrap();
+            let report = alice.verify_cp_adaptors();
+            assert_eq!(report.invalid_indices(), vec![0, 1], "{}", config);
+
+            let truncated = tamper_adaptors!(bob.share_adaptors(), |a| a.pop());
+            alice.save_cp_adaptors(truncated).unwrap();
+            assert_eq!(
+                alice.verify_cp_adaptors().failures,
+                vec![AdaptorVerificationFailure::LengthMismatch {
+                    expected: nb_cets,
+                    received: nb_cets - 1,
+                }],
+                "{}",
+                config
+            );
+        }
+    }
+
+    // Creates both parties, loads contract and exchanges (and verifies) adaptors
+    fn setup_sessions(
+        config: SessionConfig,
+        oracles: &[Arc<SessionOracle>],
+        contract_path: &str,
+    ) -> (DlcSession, DlcSession) {
+        let mut alice = DlcSession::new(config, ControllerType::Offerer, oracles.to_vec()).unwrap();
+        let mut bob = DlcSession::new(config, ControllerType::Accepter, oracles.to_vec()).unwrap();
+
+        alice.load_input(contract_path).unwrap();
+        bob.load_input(contract_path).unwrap();
+        alice.init_storage().unwrap();
+        bob.init_storage().unwrap();
+
+        alice.save_cp_verification_key(bob.share_verification_key());
+        alice.save_cp_adaptors(bob.share_adaptors()).unwrap();
+        bob.save_cp_verification_key(alice.share_verification_key());
+        bob.save_cp_adaptors(alice.share_adaptors()).unwrap();
+        assert!(alice.verify_cp_adaptors().is_valid(), "{}", config);
+        assert!(bob.verify_cp_adaptors().is_valid(), "{}", config);
+        alice.update_cp_adaptors().unwrap();
+        bob.update_cp_adaptors().unwrap();
+        (alice, bob)
+    }
+
+    // Waits for attestations and checks that both parties finalize valid transaction
+    fn settle_sessions(config: SessionConfig, alice: &mut DlcSession, bob: &mut DlcSession) {
+        let multisig = types::MultisigFundAddress::new(
+            alice.share_verification_key(),
+            bob.share_verification_key(),
+        );
+        alice.wait_attestation().unwrap();
+        bob.wait_attestation().unwrap();
+        assert!(
+            alice.finalize_tx().unwrap().is_valid(&multisig),
+            "{}",
+            config
+        );
+        assert!(bob.finalize_tx().unwrap().is_valid(&multisig), "{}", config);
+    }
+
+    #[test]
+    fn test_finalize_before_attestation_fails() {
+        use crate::common::error::AdaptorSignatureError;
+
+        for config in SessionConfig::all() {
+            let oracles = SessionOracle::new_group(config.method, SIMPLE_CONTRACT_NB_DIGITS, 1, 0);
+            let (mut alice, _) = setup_sessions(config, &oracles, SIMPLE_CONTRACT_PATH);
+            let error = alice.finalize_tx().err().expect("Nothing is attested yet");
+            assert_eq!(
+                error
+                    .get_ref()
+                    .and_then(|e| e.downcast_ref::<AttestationError>()),
+                Some(&AttestationError::NotAttested),
+                "{}",
+                config
+            );
+
+            // Attested, but counterparty adaptors were never saved
+            let mut carol = DlcSession::new(config, ControllerType::Offerer, oracles).unwrap();
+            carol.load_input(SIMPLE_CONTRACT_PATH).unwrap();
+            carol.init_storage().unwrap();
+            carol.wait_attestation().unwrap();
+            let error = carol
+                .finalize_tx()
+                .err()
+                .expect("Adaptors weren't exchanged");
+            assert_eq!(
+                error
+                    .get_ref()
+                    .and_then(|e| e.downcast_ref::<AdaptorSignatureError>()),
+                Some(&AdaptorSignatureError::MissingAdaptor),
+                "{}",
+                config
+            );
+        }
+    }
+
+    const MULTI_ORACLE_CONTRACT_PATH: &str =
+        "./input_contracts/sample_contracts/multi_oracle_contract_input.json";
+    const MULTI_ORACLE_NB_DIGITS: u8 = 6;
+
+    // 2-of-3 oracles, where first oracle attests to different outcome than the other two
+    fn disagreeing_oracles(method: MethodKind, outcome: u64) -> Vec<Arc<SessionOracle>> {
+        let other_outcome = (outcome + 1) % (1 << MULTI_ORACLE_NB_DIGITS);
+        [other_outcome, outcome, outcome]
+            .into_iter()
+            .map(|o| {
+                Arc::new(SessionOracle::with_outcome(
+                    method,
+                    MULTI_ORACLE_NB_DIGITS,
+                    o,
+                ))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_multi_oracle_threshold_all_configs() {
+        for config in SessionConfig::all() {
+            let oracles = disagreeing_oracles(config.method, 37);
+            let (mut alice, mut bob) = setup_sessions(config, &oracles, MULTI_ORACLE_CONTRACT_PATH);
+            // One CET per outcome (or prefix) for each of the 3 oracle pairs
+            assert_eq!(alice.nb_cets() % 3, 0, "{}", config);
+            settle_sessions(config, &mut alice, &mut bob);
+            let expected_payout = fun::create_cet(135000, 200000);
+            assert_eq!(
+                alice.finalize_tx().unwrap().payload(),
+                &expected_payout,
+                "{}",
+                config
+            );
+        }
+    }
+
+    #[test]
+    fn test_recover_attestation_all_configs() {
+        for config in SessionConfig::all() {
+            let oracles = disagreeing_oracles(config.method, 37);
+            let (mut alice, mut bob) = setup_sessions(config, &oracles, MULTI_ORACLE_CONTRACT_PATH);
+            let multisig = types::MultisigFundAddress::new(
+                alice.share_verification_key(),
+                bob.share_verification_key(),
+            );
+            bob.wait_attestation().unwrap();
+            let bob_tx = bob.finalize_tx().unwrap();
+
+            // Alice finalizes the same CET without oracle attestations (many CETs share its payload)
+            let outcomes = alice.recover_attestation(&bob_tx).unwrap();
+            assert!(outcomes.contains(&37), "{}: {:?}", config, outcomes);
+            let alice_tx = alice.finalize_tx().unwrap();
+            assert_eq!(alice_tx.payload(), bob_tx.payload(), "{}", config);
+            assert!(alice_tx.is_valid(&multisig), "{}", config);
+
+            // Her own signature in her transaction isn't adapted, so it reveals nothing
+            let error = alice.recover_attestation(&alice_tx).unwrap_err();
+            assert_eq!(
+                error
+                    .get_ref()
+                    .and_then(|e| e.downcast_ref::<AttestationError>()),
+                Some(&AttestationError::NotRecoverable),
+                "{}",
+                config
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_oracle_below_threshold() {
+        let config = SessionConfig::default();
+        // Every oracle attests to different outcome
+        let oracles: Vec<Arc<SessionOracle>> = [1, 2, 3]
+            .into_iter()
+            .map(|o| {
+                Arc::new(SessionOracle::with_outcome(
+                    config.method,
+                    MULTI_ORACLE_NB_DIGITS,
+                    o,
+                ))
+            })
+            .collect();
+        let (mut alice, _) = setup_sessions(config, &oracles, MULTI_ORACLE_CONTRACT_PATH);
+        assert!(alice.wait_attestation().is_err());
+    }
+
+    #[test]
+    fn test_nb_oracles_mismatch_rejected() {
+        let config = SessionConfig::default();
+        let oracles = SessionOracle::new_group(config.method, MULTI_ORACLE_NB_DIGITS, 2, 0);
+        let mut session = DlcSession::new(config, ControllerType::Offerer, oracles).unwrap();
+        assert!(session.load_input(MULTI_ORACLE_CONTRACT_PATH).is_err());
+    }
+
+    #[test]
+    fn test_oracle_subsets() {
+        assert_eq!(fun::oracle_subsets(3, 3), vec![vec![0, 1, 2]]);
+        assert_eq!(
+            fun::oracle_subsets(3, 2),
+            vec![vec![0, 1], vec![0, 2], vec![1, 2]]
+        );
+        assert_eq!(fun::oracle_subsets(5, 3).len(), 10);
+        assert_eq!(fun::oracle_subsets(4, 1).len(), 4);
+        for (nb_oracles, threshold) in [(3, 3), (3, 2), (5, 3), (4, 1), (7, 4)] {
+            assert_eq!(
+                fun::nb_oracle_subsets(nb_oracles, threshold),
+                Some(fun::oracle_subsets(nb_oracles, threshold).len())
+            );
+        }
+        assert_eq!(fun::nb_oracle_subsets(2, 3), Some(0));
+        assert_eq!(fun::nb_oracle_subsets(usize::MAX, 2), None);
+        // First subset where all oracles agree
+        assert_eq!(
+            fun::find_agreeing_subset(&[7, 5, 5], &fun::oracle_subsets(3, 2)),
+            Some(2)
+        );
+        assert_eq!(
+            fun::find_agreeing_subset(&[7, 5, 6], &fun::oracle_subsets(3, 2)),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "threshold must be in range 1..=nb_oracles")]
+    fn test_oracle_subsets_threshold_above_nb_oracles() {
+        fun::oracle_subsets(2, 3);
+    }
+
+    const PRICE_FEED_CONTRACT_PATH: &str =
+        "./input_contracts/sample_contracts/price_feed_contract_input.json";
+    const PRICE_FEED_NB_DIGITS: u8 = 8;
+    // Outcomes differing by at most 2^minSupportExp always settle the price feed contract
+    const PRICE_FEED_MIN_SUPPORT: u64 = 4;
+
+    fn oracles_with_outcomes(method: MethodKind, outcomes: &[u64]) -> Vec<Arc<SessionOracle>> {
+        outcomes
+            .iter()
+            .map(|&o| Arc::new(SessionOracle::with_outcome(method, PRICE_FEED_NB_DIGITS, o)))
+            .collect()
+    }
+
+    #[test]
+    fn test_bounded_difference_prefix() {
+        for scheme in [SchemeKind::Ecdsa, SchemeKind::Schnorr] {
+            let config = SessionConfig::new(scheme, MethodKind::Prefix);
+            // First two oracles are within allowed difference, third one is far off
+            let oracles = oracles_with_outcomes(config.method, &[125, 127, 230]);
+            let (mut alice, mut bob) = setup_sessions(config, &oracles, PRICE_FEED_CONTRACT_PATH);
+            settle_sessions(config, &mut alice, &mut bob);
+            // Payout is given by outcome of the first oracle of the subset
+            let expected_payout = fun::create_cet(100000, 200000);
+            assert_eq!(
+                alice.finalize_tx().unwrap().payload(),
+                &expected_payout,
+                "{}",
+                config
+            );
+        }
+    }
+
+    #[test]
+    fn test_bounded_difference_random_feeds() {
+        let config = SessionConfig::new(SchemeKind::Schnorr, MethodKind::Prefix);
+        let oracles = SessionOracle::new_group(
+            config.method,
+            PRICE_FEED_NB_DIGITS,
+            3,
+            PRICE_FEED_MIN_SUPPORT,
+        );
+        let (mut alice, mut bob) = setup_sessions(config, &oracles, PRICE_FEED_CONTRACT_PATH);
+        settle_sessions(config, &mut alice, &mut bob);
+    }
+
+    #[test]
+    fn test_bounded_difference_exceeded() {
+        let config = SessionConfig::new(SchemeKind::Ecdsa, MethodKind::Prefix);
+        // Outcomes differ by at least 2^maxErrorExp + 2^minSupportExp = 20
+        let oracles = oracles_with_outcomes(config.method, &[125, 150, 230]);
+        let (mut alice, _) = setup_sessions(config, &oracles, PRICE_FEED_CONTRACT_PATH);
+        assert!(alice.wait_attestation().is_err());
+    }
+
+    #[test]
+    fn test_bounded_difference_needs_prefix_method() {
+        let config = SessionConfig::default();
+        let oracles = oracles_with_outcomes(config.method, &[125, 127, 230]);
+        let mut session = DlcSession::new(config, ControllerType::Offerer, oracles).unwrap();
+        assert!(session.load_input(PRICE_FEED_CONTRACT_PATH).is_err());
+    }
+
+    const ENUMERATED_CONTRACT_PATH: &str =
+        "./input_contracts/sample_contracts/enumerated_contract_input.json";
+
+    fn enumerated_labels() -> Vec<String> {
+        ["team_a_wins", "draw", "team_b_wins"]
+            .iter()
+            .map(|label| label.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_enumerated_contract() {
+        for (scheme, method) in [SchemeKind::Ecdsa, SchemeKind::Schnorr]
+            .into_iter()
+            .flat_map(|scheme| [(scheme, MethodKind::Simple), (scheme, MethodKind::DlcSpecs)])
+        {
+            let config = SessionConfig::new(scheme, method);
+            let oracles = vec![Arc::new(SessionOracle::enumerated_with_outcome(
+                config.method,
+                enumerated_labels(),
+                1,
+            ))];
+            let (mut alice, mut bob) = setup_sessions(config, &oracles, ENUMERATED_CONTRACT_PATH);
+            assert_eq!(alice.nb_cets(), 3, "{}", config);
+            settle_sessions(config, &mut alice, &mut bob);
+            let expected_payout = fun::create_cet(100000, 200000);
+            assert_eq!(
+                alice.finalize_tx().unwrap().payload(),
+                &expected_payout,
+                "{}",
+                config
+            );
+        }
+    }
+
+    #[test]
+    fn test_dlcspecs_attestations_are_bip340_signatures() {
+        use crate::crypto_utils::dlcspecs_crypto_utils::attestation_message;
+        use secp256k1_zkp::{Message, SECP256K1};
+
+        // Outcome 5 has binary digits "101", each signed as dlcspecs oracle would
+        let oracles = vec![Arc::new(SessionOracle::with_outcome(
+            MethodKind::DlcSpecs,
+            SIMPLE_CONTRACT_NB_DIGITS,
+            5,
+        ))];
+        let announcement = oracles[0].get_event_announcement(0);
+        let attestation = oracles[0].get_event_attestation(0);
+        let xonly_public_key = announcement.public_key.x_only_public_key().0;
+        for ((nonce, secret), digit) in announcement
+            .public_nonces
+            .iter()
+            .zip(&attestation.attestations)
+            .zip(["1", "0", "1"])
+        {
+            let mut signature = [0u8; 64];
+            signature[..32].copy_from_slice(&nonce.x_only_public_key().0.serialize());
+            signature[32..].copy_from_slice(&secret.secret_bytes());
+            assert!(SECP256K1
+                .verify_schnorr(
+                    &schnorr::Signature::from_slice(&signature).unwrap(),
+                    &Message::from_digest(attestation_message(digit)),
+                    &xonly_public_key
+                )
+                .is_ok());
+        }
+
+        for scheme in [SchemeKind::Ecdsa, SchemeKind::Schnorr] {
+            let config = SessionConfig::new(scheme, MethodKind::DlcSpecs);
+            let (mut alice, mut bob) = setup_sessions(config, &oracles, SIMPLE_CONTRACT_PATH);
+            settle_sessions(config, &mut alice, &mut bob);
+        }
+    }
+
+    #[test]
+    fn test_recover_enumerated_attestation() {
+        // Recovered outcome is the attested label, not the first one of the contract
+        let config = SessionConfig::default();
+        for (index, payout) in [(1, 100000), (2, 0)] {
+            let oracles = vec![Arc::new(SessionOracle::enumerated_with_outcome(
+                config.method,
+                enumerated_labels(),
+                index,
+            ))];
+            let (mut alice, mut bob) = setup_sessions(config, &oracles, ENUMERATED_CONTRACT_PATH);
+            bob.wait_attestation().unwrap();
+            let bob_tx = bob.finalize_tx().unwrap();
+
+            let outcomes = alice.recover_attestation(&bob_tx).unwrap();
+            assert_eq!(outcomes, index as u64..=index as u64);
+            let alice_tx = alice.finalize_tx().unwrap();
+            assert_eq!(alice_tx.payload(), &fun::create_cet(payout, 200000));
+            assert_eq!(alice_tx.payload(), bob_tx.payload());
+        }
+    }
+
+    #[test]
+    fn test_enumerated_contract_rejected() {
+        // Oracle announcing different labels than contract expects
+        let config = SessionConfig::default();
+        let mut labels = enumerated_labels();
+        labels.swap(0, 2);
+        let oracles = vec![Arc::new(SessionOracle::enumerated_with_outcome(
+            config.method,
+            labels,
+            0,
+        ))];
+        let mut session = DlcSession::new(config, ControllerType::Offerer, oracles).unwrap();
+        session.load_input(ENUMERATED_CONTRACT_PATH).unwrap();
+        assert!(session.init_storage().is_err());
+
+        // Methods decomposing outcome into digits can't attest enumerated outcomes
+        for method in [MethodKind::Basis, MethodKind::Digit, MethodKind::Prefix] {
+            let config = SessionConfig::new(SchemeKind::Ecdsa, method);
+            let oracles =
+                SessionOracle::new_enumerated_group(method, enumerated_labels(), 1, Seed::random());
+            let mut session = DlcSession::new(config, ControllerType::Offerer, oracles).unwrap();
+            assert!(
+                session.load_input(ENUMERATED_CONTRACT_PATH).is_err(),
+                "{}",
+                config
+            );
+        }
+    }
+
+    #[test]
+    fn test_prefix_method_compresses_cets() {
+        let config = SessionConfig::new(SchemeKind::Schnorr, MethodKind::Prefix);
+        let oracles = SessionOracle::new_group(config.method, 14, 1, 0);
+        let mut session = DlcSession::new(config, ControllerType::Offerer, oracles).unwrap();
+        session
+            .load_input("./input_contracts/sample_contracts/reduced_contract_input.json")
+            .unwrap();
+        // Compressed contract lists only outcomes where payout changes
+        assert!(session.parsed_contract().len() < 1 << 14);
+        assert!(session.nb_cets() < 1 << 14);
+    }
+
+    #[test]
+    fn test_prefix_method_wide_contract() {
+        // 2^40 outcomes, which can't be parsed (or signed) one by one
+        const WIDE_CONTRACT_NB_DIGITS: u8 = 40;
+        let config = SessionConfig::new(SchemeKind::Schnorr, MethodKind::Prefix);
+        let oracles = SessionOracle::new_group(config.method, WIDE_CONTRACT_NB_DIGITS, 1, 0);
+        let (mut alice, mut bob) = setup_sessions(
+            config,
+            &oracles,
+            "./input_contracts/sample_contracts/wide_contract_input.json",
+        );
+        assert!(alice.nb_cets() < 1000);
+        settle_sessions(config, &mut alice, &mut bob);
+    }
+
+    const DECIMAL_CONTRACT_PATH: &str =
+        "./input_contracts/sample_contracts/decimal_contract_input.json";
+
+    #[test]
+    fn test_decimal_contract_all_configs() {
+        // 3 decimal digits, outcomes 0..=999
+        for config in SessionConfig::all() {
+            let oracles =
+                SessionOracle::new_group_in_base(config.method, 3, 10, 1, 0, Seed::random());
+            let (mut alice, mut bob) = setup_sessions(config, &oracles, DECIMAL_CONTRACT_PATH);
+            settle_sessions(config, &mut alice, &mut bob);
+        }
+    }
+
+    #[test]
+    fn test_base_mismatch_rejected() {
+        // Oracle attesting to binary digits can't be used for contract on decimal digits
+        let config = SessionConfig::new(SchemeKind::Schnorr, MethodKind::Digit);
+        let oracles = SessionOracle::new_group(config.method, 3, 1, 0);
+        let mut session = DlcSession::new(config, ControllerType::Offerer, oracles).unwrap();
+        session.load_input(DECIMAL_CONTRACT_PATH).unwrap();
+        assert!(session.init_storage().is_err());
+    }
+
+    #[test]
+    fn test_nb_digits_mismatch_rejected() {
+        let config = SessionConfig::default();
+        let oracles = SessionOracle::new_group(config.method, SIMPLE_CONTRACT_NB_DIGITS + 1, 1, 0);
+        let mut session = DlcSession::new(config, ControllerType::Offerer, oracles).unwrap();
+        session.load_input(SIMPLE_CONTRACT_PATH).unwrap();
+        assert!(session.init_storage().is_err());
+    }
+
+    #[test]
+    fn test_oracle_announces_nonces_per_method() {
+        for method in MethodKind::ALL {
+            let oracle = SessionOracle::new(method, SIMPLE_CONTRACT_NB_DIGITS);
+            let announcement = oracle.get_event_announcement(0);
+            let attestation = oracle.get_event_attestation(0);
+            let (nb_nonces, nb_attestations) = match method {
+                MethodKind::Simple => (1, 1),
+                // Partial attestations of basis method are published only as their sum
+                MethodKind::Basis => (SIMPLE_CONTRACT_NB_DIGITS as usize, 1),
+                MethodKind::Digit | MethodKind::Prefix | MethodKind::DlcSpecs => (
+                    SIMPLE_CONTRACT_NB_DIGITS as usize,
+                    SIMPLE_CONTRACT_NB_DIGITS as usize,
+                ),
+            };
+            assert_eq!(announcement.public_nonces.len(), nb_nonces, "{}", method);
+            assert_eq!(
+                attestation.attestations.len(),
+                nb_attestations,
+                "{}",
+                method
+            );
+        }
+    }
+
+    #[test]
+    fn test_oracle_method_mismatch_rejected() {
+        let config = SessionConfig::new(SchemeKind::Ecdsa, MethodKind::Basis);
+        let oracles = SessionOracle::new_group(MethodKind::Simple, SIMPLE_CONTRACT_NB_DIGITS, 1, 0);
+        assert!(DlcSession::new(config, ControllerType::Offerer, oracles).is_err());
+    }
+    // Runs whole protocol with controllers used directly (without session), so any oracle and its outcome
+    // representation can be used. Returns CET finalized by both parties.
+    fn run_controllers<ASigS, C, O>(oracles: Vec<Arc<O>>, contract_path: &str) -> types::Cet
+    where
+        ASigS: AdaptorSignatureScheme,
+        C: DlcController<ASigS, O>,
+        O: Oracle,
+    {
+        let mut alice = C::new(ControllerType::Offerer, oracles.clone());
+        let mut bob = C::new(ControllerType::Accepter, oracles);
+        alice.load_input(contract_path).unwrap();
+        bob.load_input(contract_path).unwrap();
+        alice.init_storage().unwrap();
+        bob.init_storage().unwrap();
+
+        alice.save_cp_verification_key(bob.share_verification_key());
+        alice.save_cp_adaptors(bob.share_adaptors());
+        bob.save_cp_verification_key(alice.share_verification_key());
+        bob.save_cp_adaptors(alice.share_adaptors());
+        assert!(alice.verify_cp_adaptors().is_valid() && bob.verify_cp_adaptors().is_valid());
+        alice.update_cp_adaptors().unwrap();
+        bob.update_cp_adaptors().unwrap();
+
+        let multisig = types::MultisigFundAddress::new(
+            alice.share_verification_key(),
+            bob.share_verification_key(),
+        );
+        alice.wait_attestation().unwrap();
+        bob.wait_attestation().unwrap();
+        let (alice_tx, bob_tx) = (alice.finalize_tx().unwrap(), bob.finalize_tx().unwrap());
+        assert!(fun::finalized_tx_valid::<ASigS>(&alice_tx, &multisig));
+        assert!(fun::finalized_tx_valid::<ASigS>(&bob_tx, &multisig));
+        assert_eq!(alice_tx.payload, bob_tx.payload);
+        alice_tx.payload
+    }
+
+    // Settles simple contract with oracle attesting to `outcome` in representation `Out`, with every method
+    fn check_outcome_representation<Out: NumericOutcome + Send + Sync>(outcome: u64) {
+        type Ecdsa = EcdsaAdaptorSignatureScheme;
+        type Schnorr = SchnorrAdaptorSignatureScheme;
+        // Simple contract pays linearly from 25 at outcome 0 to 200 at outcome 7 (total collateral 200)
+        let expected_cet = fun::create_cet(25 + 25 * outcome as types::PayoutT, 200);
+
+        let oracle = Arc::new(RandIntOracle::<SimpleCryptoUtils, Out>::with_outcome(
+            SIMPLE_CONTRACT_NB_DIGITS,
+            outcome,
+        ));
+        let cet = run_controllers::<Ecdsa, VerySimpleController<Ecdsa, SimpleCryptoUtils, _>, _>(
+            vec![oracle],
+            SIMPLE_CONTRACT_PATH,
+        );
+        assert_eq!(cet, expected_cet);
+
+        let oracle = Arc::new(RandIntOracle::<BasisCryptoUtils, Out>::with_outcome(
+            SIMPLE_CONTRACT_NB_DIGITS,
+            outcome,
+        ));
+        let cet = run_controllers::<Schnorr, VerySimpleController<Schnorr, BasisCryptoUtils, _>, _>(
+            vec![oracle],
+            SIMPLE_CONTRACT_PATH,
+        );
+        assert_eq!(cet, expected_cet);
+
+        let oracle = Arc::new(RandIntOracle::<DigitCryptoUtils, Out>::with_outcome(
+            SIMPLE_CONTRACT_NB_DIGITS,
+            outcome,
+        ));
+        let cet = run_controllers::<Ecdsa, VerySimpleController<Ecdsa, DigitCryptoUtils, _>, _>(
+            vec![oracle.clone()],
+            SIMPLE_CONTRACT_PATH,
+        );
+        assert_eq!(cet, expected_cet);
+        let cet = run_controllers::<Ecdsa, CompressedController<Ecdsa, _>, _>(
+            vec![oracle],
+            SIMPLE_CONTRACT_PATH,
+        );
+        assert_eq!(cet, expected_cet);
+    }
+
+    // Oracle claiming other outcome than the one it really attests to
+    struct ForgingOracle<CU: CryptoUtils> {
+        oracle: RandIntOracle<CU, MyOutcome>,
+        claimed_outcome: u64,
+    }
+
+    impl<CU: CryptoUtils> Oracle for ForgingOracle<CU> {
+        type Outcome = MyOutcome;
+
+        fn get_public_key(&self) -> PublicKey {
+            self.oracle.get_public_key()
+        }
+
+        fn get_event_announcement(&self, event_id: u32) -> OracleAnnouncement {
+            self.oracle.get_event_announcement(event_id)
+        }
+
+        fn get_event_attestation(&self, event_id: u32) -> OracleAttestation<MyOutcome> {
+            let attestation = self.oracle.get_event_attestation(event_id);
+            OracleAttestation {
+                outcome: MyOutcome::from_u64(self.claimed_outcome, SIMPLE_CONTRACT_NB_DIGITS),
+                attestations: attestation.attestations,
+            }
+        }
+    }
+
+    // Error of controller waiting for attestation of oracle attesting to 3, but claiming `claimed_outcome`
+    fn forged_attestation_error<C, CU>(claimed_outcome: u64) -> AttestationError
+    where
+        C: DlcController<SchnorrAdaptorSignatureScheme, ForgingOracle<CU>>,
+        CU: CryptoUtils,
+    {
+        let oracle = Arc::new(ForgingOracle {
+            oracle: RandIntOracle::<CU, MyOutcome>::with_outcome(SIMPLE_CONTRACT_NB_DIGITS, 3),
+            claimed_outcome,
+        });
+        let mut controller = C::new(ControllerType::Offerer, vec![oracle]);
+        controller.load_input(SIMPLE_CONTRACT_PATH).unwrap();
+        controller.init_storage().unwrap();
+        let error = controller.wait_attestation().unwrap_err();
+        *error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<AttestationError>())
+            .expect("Error must be AttestationError")
+    }
+
+    #[test]
+    fn test_forged_attestation_rejected() {
+        type Schnorr = SchnorrAdaptorSignatureScheme;
+        type Simple =
+            VerySimpleController<Schnorr, SimpleCryptoUtils, ForgingOracle<SimpleCryptoUtils>>;
+        type Compressed = CompressedController<Schnorr, ForgingOracle<DigitCryptoUtils>>;
+
+        // Every outcome of simple contract has its own payout, so attestation of 3 doesn't unlock CET of 5
+        assert_eq!(
+            forged_attestation_error::<Simple, _>(5),
+            AttestationError::InvalidAttestation { outcome: 5 }
+        );
+        assert_eq!(
+            forged_attestation_error::<Compressed, _>(5),
+            AttestationError::InvalidAttestation { outcome: 5 }
+        );
+        // Simple contract has 3 binary digits
+        assert_eq!(
+            forged_attestation_error::<Simple, _>(8),
+            AttestationError::OutcomeOutOfRange { outcome: 8 }
+        );
+        assert_eq!(
+            forged_attestation_error::<Compressed, _>(8),
+            AttestationError::OutcomeOutOfRange { outcome: 8 }
+        );
+    }
+
+    #[test]
+    fn test_outcome_representations() {
+        for outcome in [0, 5, 7] {
+            check_outcome_representation::<OutcomeU32>(outcome);
+            check_outcome_representation::<OutcomeU64>(outcome);
+            check_outcome_representation::<OutcomeBinStr>(outcome);
+        }
+    }
+
+    #[test]
+    fn test_enumerated_contract_binary_string_outcomes() {
+        type Ecdsa = EcdsaAdaptorSignatureScheme;
+        let oracle = Arc::new(
+            RandIntOracle::<SimpleCryptoUtils, OutcomeBinStr>::enumerated_with_outcome(
+                enumerated_labels(),
+                2,
+            ),
+        );
+        let cet = run_controllers::<Ecdsa, VerySimpleController<Ecdsa, SimpleCryptoUtils, _>, _>(
+            vec![oracle],
+            ENUMERATED_CONTRACT_PATH,
+        );
+        assert_eq!(cet, fun::create_cet(0, 200000));
+    }
+}
@@ -9,7 +9,7 @@ where
     ASigS: AdaptorSignatureScheme,
     Out: types::Outcome,
 {
-    fn new(nb_outcomes: u32) -> Self
+    fn new(nb_outcomes: usize) -> Self
     where
         Self: Sized;
 
@@ -65,4 +65,5 @@ where
     }
 }
 
+pub mod prefix_storage;
 pub mod simple_array_storage;
@@ -0,0 +1,164 @@
+use std::io::Error;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use crate::adaptor_signature_scheme::AdaptorSignatureScheme;
+use crate::common::{NumericOutcome, OutcomeU32};
+use crate::dlc_storage::{DlcStorage, StorageElement};
+
+/// Storage for compressed CETs, where each element covers a range of outcomes (digit prefix).
+/// Elements are keyed by the first outcome of their range and have to be put in non-decreasing order of it
+/// (several elements can share a range, e.g. when other oracles' prefixes differ, see `cover_oracle_outcomes`).
+/// Looking up an outcome returns the (last) element whose range contains it.
+pub struct PrefixStorage<ASigS: AdaptorSignatureScheme, Out = OutcomeU32> {
+    range_starts: Vec<u64>,
+    storage: Vec<StorageElement<ASigS>>,
+    _phantom: PhantomData<Out>,
+}
+
+impl<ASigS: AdaptorSignatureScheme, Out: NumericOutcome> PrefixStorage<ASigS, Out> {
+    /// Index of element whose range contains the outcome (ranges are contiguous, so it's the last one starting before it)
+    pub fn find_index(&self, outcome: &Out) -> Option<usize> {
+        let value = outcome.to_u64();
+        match self.range_starts.partition_point(|&start| start <= value) {
+            0 => None,
+            idx => Some(idx - 1),
+        }
+    }
+
+    /// Indices of all elements whose range contains the outcome
+    pub fn find_indices(&self, outcome: &Out) -> Range<usize> {
+        match self.find_index(outcome) {
+            None => 0..0,
+            Some(last) => {
+                let range_start = self.range_starts[last];
+                self.range_starts
+                    .partition_point(|&start| start < range_start)..last + 1
+            }
+        }
+    }
+}
+
+impl<ASigS, Out> DlcStorage<ASigS, Out> for PrefixStorage<ASigS, Out>
+where
+    ASigS: AdaptorSignatureScheme,
+    Out: NumericOutcome,
+{
+    fn new(nb_elements: usize) -> Self {
+        Self {
+            range_starts: Vec::with_capacity(nb_elements),
+            storage: Vec::with_capacity(nb_elements),
+            _phantom: PhantomData,
+        }
+    }
+
+    // `outcome` is the first outcome of range covered by the element
+    fn put_element(&mut self, outcome: &Out, element: StorageElement<ASigS>) -> Result<(), Error> {
+        let range_start = outcome.to_u64();
+        if self
+            .range_starts
+            .last()
+            .is_some_and(|&last| last > range_start)
+        {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Ranges must be put in non-decreasing order",
+            ));
+        }
+        self.range_starts.push(range_start);
+        self.storage.push(element);
+        Ok(())
+    }
+
+    fn get_element(&self, outcome: &Out) -> Option<StorageElement<ASigS>> {
+        self.find_index(outcome)
+            .map(|index| self.storage[index].clone())
+    }
+
+    fn get_all_my_adaptors(&self) -> Vec<ASigS::AdaptorSignature> {
+        self.storage
+            .iter()
+            .filter_map(|element| element.my_adaptor_signature.clone())
+            .collect()
+    }
+
+    fn get_all_elements_vec_ref(&self) -> &Vec<StorageElement<ASigS>> {
+        &self.storage
+    }
+
+    fn update_cp_adaptors(
+        &mut self,
+        cp_adaptors: Vec<ASigS::AdaptorSignature>,
+    ) -> Result<(), Error> {
+        if cp_adaptors.len() != self.storage.len() {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Invalid number of cp adaptors",
+            ));
+        }
+
+        for (element, cp_adaptor) in self.storage.iter_mut().zip(cp_adaptors) {
+            element.cp_adaptor_signature = Some(cp_adaptor);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adaptor_signature_scheme::EcdsaAdaptorSignatureScheme;
+    use crate::common::OutcomeBinStr;
+
+    #[test]
+    fn test_lookup_covering_range() {
+        let mut storage = PrefixStorage::<EcdsaAdaptorSignatureScheme>::new(3);
+        for (start, cet) in [(0, "a"), (4, "b"), (4, "b2"), (6, "c")] {
+            let element = StorageElement {
+                cet: cet.to_string(),
+                ..Default::default()
+            };
+            storage
+                .put_element(&OutcomeU32::from(start), element)
+                .unwrap();
+        }
+        let cet_of = |outcome: u32| storage.get_element(&OutcomeU32::from(outcome)).unwrap().cet;
+        assert_eq!(cet_of(0), "a");
+        assert_eq!(cet_of(3), "a");
+        assert_eq!(cet_of(4), "b2");
+        assert_eq!(cet_of(5), "b2");
+        assert_eq!(storage.find_indices(&OutcomeU32::from(5)), 1..3);
+        assert_eq!(storage.find_indices(&OutcomeU32::from(6)), 3..4);
+        assert_eq!(cet_of(7), "c");
+        assert_eq!(cet_of(u32::MAX), "c");
+
+        // Ranges can't be put out of order
+        assert!(storage
+            .put_element(&OutcomeU32::from(5), StorageElement::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_lookup_binary_string_outcomes() {
+        let mut storage = PrefixStorage::<EcdsaAdaptorSignatureScheme, OutcomeBinStr>::new(2);
+        for (start, cet) in [("000", "a"), ("100", "b")] {
+            let element = StorageElement {
+                cet: cet.to_string(),
+                ..Default::default()
+            };
+            storage
+                .put_element(&OutcomeBinStr::from(start.to_string()), element)
+                .unwrap();
+        }
+        let cet_of = |outcome: u64| {
+            storage
+                .get_element(&OutcomeBinStr::from_u64(outcome, 3))
+                .unwrap()
+                .cet
+        };
+        assert_eq!(cet_of(3), "a");
+        assert_eq!(cet_of(4), "b");
+        assert_eq!(cet_of(7), "b");
+    }
+}
@@ -1,28 +1,31 @@
 use std::io::Error;
+use std::marker::PhantomData;
 
-use crate::common::Outcome;
+use crate::adaptor_signature_scheme::AdaptorSignatureScheme;
+use crate::common::{NumericOutcome, OutcomeU32};
 use crate::dlc_storage::{DlcStorage, StorageElement};
-use crate::{adaptor_signature_scheme::AdaptorSignatureScheme, common::types};
 
-pub struct SimpleArrayStorage<ASigS: AdaptorSignatureScheme> {
+/// Storage with element for each outcome, indexed by integer value of the outcome.
+pub struct SimpleArrayStorage<ASigS: AdaptorSignatureScheme, Out = OutcomeU32> {
     storage: Vec<StorageElement<ASigS>>,
+    _phantom: PhantomData<Out>,
 }
 
-impl<ASigS> DlcStorage<ASigS, types::OutcomeU32> for SimpleArrayStorage<ASigS>
+impl<ASigS, Out> DlcStorage<ASigS, Out> for SimpleArrayStorage<ASigS, Out>
 where
     ASigS: AdaptorSignatureScheme,
+    Out: NumericOutcome,
 {
-    fn new(nb_outcomes: u32) -> Self {
-        let storage = vec![StorageElement::<ASigS>::default(); nb_outcomes as usize];
-        Self { storage }
+    fn new(nb_outcomes: usize) -> Self {
+        let storage = vec![StorageElement::<ASigS>::default(); nb_outcomes];
+        Self {
+            storage,
+            _phantom: PhantomData,
+        }
     }
 
-    fn put_element(
-        &mut self,
-        outcome: &types::OutcomeU32,
-        element: StorageElement<ASigS>,
-    ) -> Result<(), Error> {
-        let index = outcome.get_value() as usize;
+    fn put_element(&mut self, outcome: &Out, element: StorageElement<ASigS>) -> Result<(), Error> {
+        let index = outcome.to_u64() as usize;
         if index < self.storage.len() {
             self.storage[index] = element;
             Ok(())
@@ -34,8 +37,8 @@ where
         }
     }
 
-    fn get_element(&self, outcome: &types::OutcomeU32) -> Option<StorageElement<ASigS>> {
-        let index = outcome.get_value() as usize;
+    fn get_element(&self, outcome: &Out) -> Option<StorageElement<ASigS>> {
+        let index = outcome.to_u64() as usize;
         if index < self.storage.len() {
             Some(self.storage[index].clone())
         } else {
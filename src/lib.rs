@@ -5,6 +5,7 @@ pub mod config;
 pub mod crypto_utils;
 pub mod dlc_computation;
 pub mod dlc_controller;
+pub mod dlc_session;
 pub mod dlc_storage;
 pub mod oracle;
 pub mod parser;
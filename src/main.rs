@@ -1,60 +1,103 @@
 // main.rs
 
-use std::sync::Arc;
-#[cfg(feature = "enable-benchmarks")]
-use std::time::Instant;
-
-use common::{types, FinalizedTx};
-use config::{
-    constants::CONTRACT_INPUT_PATH,
-    runparams::{MyAdaptorSignatureScheme, MyCryptoUtils, MyOracle, MySignature},
-    MAX_OUTCOME,
+use std::io::{Error, ErrorKind};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use clap::{Args, Parser as ClapParser, Subcommand};
+
+use dlc_fast_prototyping::common::{digit_decomposition, fun, seed::Seed, types, ContractInput};
+use dlc_fast_prototyping::config::{
+    MyEnumParser, MyParser, MySparseParser, BASIS_WINDOW_BITS, MAX_BASIS_WINDOW_BITS,
+};
+use dlc_fast_prototyping::dlc_controller::ControllerType;
+use dlc_fast_prototyping::dlc_session::{
+    DlcSession, MethodKind, SchemeKind, SessionConfig, SessionFinalizedTx, SessionOracle,
 };
-use dlc_controller::{very_simple_controller::VerySimpleController, ControllerType, DlcController};
-use secp256k1_zkp::Secp256k1;
-
-mod adaptor_signature_scheme;
-mod common;
-mod config;
-mod crypto_utils;
-mod dlc_computation;
-mod dlc_controller;
-mod dlc_storage;
-mod oracle;
-mod parser;
-
-mod bench {
-    use std::time::Duration;
-    #[cfg(feature = "enable-benchmarks")]
-    use std::time::Instant;
-
-    #[cfg(feature = "enable-benchmarks")]
-    pub fn measure_step<R, F: FnOnce() -> R>(
-        label: &str,
-        steps: &mut Vec<(String, Duration)>,
-        f: F,
-    ) -> R {
+use dlc_fast_prototyping::parser::Parser;
+
+#[derive(ClapParser)]
+#[command(version, about = "Fast prototyping of numeric outcome DLCs")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Set up DLC between Alice and Bob and settle it with oracle attestation
+    Run(SessionArgs),
+    /// Check contract against all validation rules and report every broken one
+    Validate {
+        /// Path to contract input JSON
+        contract: String,
+    },
+    /// Print intervals, number of outcomes and unique payouts of contract
+    Inspect {
+        /// Path to contract input JSON
+        contract: String,
+    },
+    /// Same as `run`, but measures each DLC setup step and prints timing table
+    Bench(SessionArgs),
+}
+
+#[derive(Args)]
+struct SessionArgs {
+    /// Path to contract input JSON
+    contract: String,
+    /// Adaptor signature scheme [ecdsa|schnorr|schnorr-fun (with `schnorr-fun` feature, simple method only)]
+    #[arg(long, default_value_t = SchemeKind::Ecdsa)]
+    scheme: SchemeKind,
+    /// Anticipation point method [simple|basis|digit|prefix|dlcspecs]
+    #[arg(long, default_value_t = MethodKind::Simple)]
+    method: MethodKind,
+    /// Bits of precomputed tables of basis method (larger tables, fewer point additions per anticipation point)
+    #[arg(long, default_value_t = BASIS_WINDOW_BITS,
+        value_parser = clap::value_parser!(u8).range(1..=MAX_BASIS_WINDOW_BITS as i64))]
+    basis_window: u8,
+    /// Seed of all keys, nonces and oracle outcomes, to replay a run (random if not given, printed either way)
+    #[arg(long)]
+    seed: Option<Seed>,
+}
+
+impl SessionArgs {
+    fn config(&self) -> SessionConfig {
+        SessionConfig::new(self.scheme, self.method).with_basis_window(self.basis_window)
+    }
+
+    fn seed(&self) -> Seed {
+        self.seed.unwrap_or_else(Seed::random)
+    }
+}
+
+// Measures duration of DLC setup steps. If disabled, steps are only executed.
+struct StepTimer {
+    enabled: bool,
+    steps: Vec<(String, Duration)>,
+}
+
+impl StepTimer {
+    fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            steps: Vec::new(),
+        }
+    }
+
+    fn measure<R, F: FnOnce() -> R>(&mut self, label: &str, f: F) -> R {
+        if !self.enabled {
+            return f();
+        }
         let start = Instant::now();
         let result = f();
         let duration = start.elapsed();
         println!("{}: {}ms", label, duration.as_millis());
-        steps.push((label.to_string(), duration));
+        self.steps.push((label.to_string(), duration));
         result
     }
 
-    #[cfg(not(feature = "enable-benchmarks"))]
-    pub fn measure_step<R, F: FnOnce() -> R>(
-        _label: &str,
-        _steps: &mut [(String, Duration)],
-        f: F,
-    ) -> R {
-        // No-op version
-        f()
-    }
-
     // Function to print benchmarking table.
-    #[cfg(feature = "enable-benchmarks")]
-    pub fn print_table(steps: &Vec<(String, Duration)>, total_time: Duration) {
+    fn print_table(&self, total_time: Duration) {
         println!("\n-------------------------------------------------------------");
         println!("{:<35}{:<15}{:<15}", "STEP", "TIME", "RATIO");
         println!("-------------------------------------------------------------");
@@ -62,7 +105,7 @@ mod bench {
         let mut alice_time = Duration::new(0, 0);
         let mut bob_time = Duration::new(0, 0);
 
-        for (label, step_dur) in steps {
+        for (label, step_dur) in &self.steps {
             let ratio = (step_dur.as_secs_f64() / total_time.as_secs_f64()) * 100.0;
             println!(
                 "{:<35}{:<15}{:.2}%",
@@ -80,10 +123,9 @@ mod bench {
 
         println!("-------------------------------------------------------------");
         println!(
-            "{:<35}{:<15}{}",
+            "{:<35}{:<15}100.00%",
             "TOTAL RUNTIME:",
             format!("{}ms", total_time.as_millis()),
-            "100.00%"
         );
         println!("-------------------------------------------------------------");
         println!(
@@ -105,138 +147,134 @@ mod bench {
 // Validates (locally) final transaction which would be broadcasted to blockchain and by doing so it
 // simulates blockchain acceptance or rejection of final tx.
 fn finalized_tx_valid(
-    finalized_tx: &FinalizedTx<MySignature>,
+    finalized_tx: &SessionFinalizedTx,
     multisig: &types::MultisigFundAddress,
 ) -> bool {
-    let secp = Secp256k1::verification_only();
-
-    let msg = match common::fun::create_message(finalized_tx.payload.as_bytes()) {
-        Ok(m) => m,
-        Err(_) => return false,
-    };
-
-    let (sig1_ok, sig2_ok) = {
-        #[cfg(feature = "ecdsa")]
-        {
-            let sig1_ok = secp
-                .verify_ecdsa(&msg, &finalized_tx.offerer_sig, &multisig.offerer_pubkey)
-                .is_ok();
-            let sig2_ok = secp
-                .verify_ecdsa(&msg, &finalized_tx.accepter_sig, &multisig.accepter_pubkey)
-                .is_ok();
-            (sig1_ok, sig2_ok)
-        }
-        #[cfg(feature = "schnorr")]
-        {
-            let sig1_ok = secp
-                .verify_schnorr(
-                    &finalized_tx.offerer_sig,
-                    &msg,
-                    &multisig.offerer_pubkey.x_only_public_key().0,
-                )
-                .is_ok();
-            let sig2_ok = secp
-                .verify_schnorr(
-                    &finalized_tx.accepter_sig,
-                    &msg,
-                    &multisig.accepter_pubkey.x_only_public_key().0,
-                )
-                .is_ok();
-            (sig1_ok, sig2_ok)
-        }
-    };
-
-    if sig1_ok && sig2_ok {
-        println!("Transaction \"{}\" is valid.", finalized_tx.payload);
+    if finalized_tx.is_valid(multisig) {
+        println!("Transaction \"{}\" is valid.", finalized_tx.payload());
         return true;
     }
     false
 }
 
-fn main() {
-    #[cfg(feature = "enable-benchmarks")]
-    let start = Instant::now();
-
-    let mut steps = Vec::new();
-
-    // Create oracle pointer, so both controllers use API of same oracle
-    let oracle = Arc::new(MyOracle::new());
+// Runs whole DLC setup between Alice and Bob, timing each step with `timer`.
+fn run_session(args: &SessionArgs, timer: &mut StepTimer) -> Result<(), Error> {
+    let config = args.config();
+    let seed = args.seed();
+    println!("Configuration: {}", config);
+    println!("Seed: {} (replay with --seed {})", seed, seed);
+
+    // Oracles attest outcomes with as many digits in the same base (or the same labels) as the contract expects
+    let contract_info = MyParser::read_input(&args.contract)?.contract_info;
+    let oracle_input = contract_info.oracle;
+    let (nb_digits, base) = (oracle_input.nb_digits, oracle_input.base());
+    let outcome_labels = contract_info.contract_descriptor.outcome_labels();
+
+    // Create oracle pointers (one per oracle in contract), so both controllers use API of same oracles
+    let oracles = if outcome_labels.is_empty() {
+        SessionOracle::new_group_in_base(
+            config.method,
+            nb_digits,
+            base,
+            oracle_input.nb_oracles(),
+            // Simulated price feeds stay within the difference that always settles
+            oracle_input
+                .allowed_difference()
+                .map_or(0, |difference| difference.min_support(base)),
+            seed.derive("oracles"),
+        )
+    } else {
+        SessionOracle::new_enumerated_group(
+            config.method,
+            outcome_labels.clone(),
+            oracle_input.nb_oracles(),
+            seed.derive("oracles"),
+        )
+    };
 
-    println!(
-        "Oracle outcome: {:?} from {:?}",
-        oracle.get_outcome(),
-        MAX_OUTCOME
-    );
+    if outcome_labels.is_empty() {
+        println!(
+            "Oracle outcome: {:?} from {:?} ({} of {} oracles needed)",
+            oracles[0].get_outcome(),
+            fun::max_outcome(nb_digits, base),
+            oracle_input.threshold(),
+            oracle_input.nb_oracles()
+        );
+    } else {
+        println!(
+            "Oracle outcome: {:?} from {:?} ({} of {} oracles needed)",
+            outcome_labels[oracles[0].get_outcome() as usize],
+            outcome_labels,
+            oracle_input.threshold(),
+            oracle_input.nb_oracles()
+        );
+    }
+    if let Some(difference) = oracle_input.allowed_difference() {
+        let outcomes: Vec<u64> = oracles.iter().map(|oracle| oracle.get_outcome()).collect();
+        println!(
+            "Oracle outcomes: {:?} (supported difference {}, max error {})",
+            outcomes,
+            difference.min_support(base),
+            difference.max_error(base)
+        );
+    }
 
     // Create controllers
-    let mut controller_alice =
-        bench::measure_step("Construct controller (Alice)", &mut steps, || {
-            VerySimpleController::<MyAdaptorSignatureScheme, MyCryptoUtils, MyOracle>::new(
-                ControllerType::Offerer,
-                Arc::clone(&oracle),
-            )
-        });
-    let mut controller_bob = bench::measure_step("Construct controller (Bob)", &mut steps, || {
-        VerySimpleController::<MyAdaptorSignatureScheme, MyCryptoUtils, MyOracle>::new(
-            ControllerType::Accepter,
-            Arc::clone(&oracle),
-        )
-    });
+    let mut controller_alice = timer.measure("Construct controller (Alice)", || {
+        DlcSession::with_seed(config, ControllerType::Offerer, oracles.clone(), seed)
+    })?;
+    let mut controller_bob = timer.measure("Construct controller (Bob)", || {
+        DlcSession::with_seed(config, ControllerType::Accepter, oracles.clone(), seed)
+    })?;
 
     // Load input files
-    bench::measure_step("Load input (Alice)", &mut steps, || {
-        if let Err(e) = controller_alice.load_input(CONTRACT_INPUT_PATH) {
-            eprintln!("Error loading input (Alice): {}", e);
-            std::process::exit(1);
-        }
-    });
-
-    bench::measure_step("Load input (Bob)", &mut steps, || {
-        if let Err(e) = controller_bob.load_input(CONTRACT_INPUT_PATH) {
-            eprintln!("Error loading input (Bob): {}", e);
-            std::process::exit(1);
-        }
-    });
+    timer.measure("Load input (Alice)", || {
+        controller_alice.load_input(&args.contract)
+    })?;
+    timer.measure("Load input (Bob)", || {
+        controller_bob.load_input(&args.contract)
+    })?;
 
     // Initialize storage
-    bench::measure_step("Init storage (Alice)", &mut steps, || {
-        controller_alice.init_storage().unwrap();
-    });
-    bench::measure_step("Init storage (Bob)", &mut steps, || {
-        controller_bob.init_storage().unwrap();
-    });
+    timer.measure("Init storage (Alice)", || controller_alice.init_storage())?;
+    timer.measure("Init storage (Bob)", || controller_bob.init_storage())?;
 
     // Share verification keys and adaptors
-    bench::measure_step("Exchange keys and adaptors (Alice)", &mut steps, || {
+    timer.measure("Exchange keys and adaptors (Alice)", || {
         controller_alice.save_cp_verification_key(controller_bob.share_verification_key());
-        controller_alice.save_cp_adaptors(controller_bob.share_adaptors());
-    });
-    bench::measure_step("Exchange keys and adaptors (Bob)", &mut steps, || {
+        controller_alice.save_cp_adaptors(controller_bob.share_adaptors())
+    })?;
+    timer.measure("Exchange keys and adaptors (Bob)", || {
         controller_bob.save_cp_verification_key(controller_alice.share_verification_key());
-        controller_bob.save_cp_adaptors(controller_alice.share_adaptors());
-    });
+        controller_bob.save_cp_adaptors(controller_alice.share_adaptors())
+    })?;
 
     // Verify counterparty adaptors
-    bench::measure_step("Verify adaptors (Alice)", &mut steps, || {
-        assert!(
-            controller_alice.verify_cp_adaptors(),
-            "Counterparty adaptors are not valid."
-        );
+    let alice_report = timer.measure("Verify adaptors (Alice)", || {
+        controller_alice.verify_cp_adaptors()
     });
-    bench::measure_step("Verify adaptors (Bob)", &mut steps, || {
-        assert!(
-            controller_bob.verify_cp_adaptors(),
-            "Counterparty adaptors are not valid."
-        );
+    let bob_report = timer.measure("Verify adaptors (Bob)", || {
+        controller_bob.verify_cp_adaptors()
     });
+    for (party, report) in [("Alice", &alice_report), ("Bob", &bob_report)] {
+        if !report.is_valid() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Counterparty adaptors are not valid ({}):\n{}",
+                    party, report
+                ),
+            ));
+        }
+    }
 
     // Update counterparty adaptors
-    bench::measure_step("Update cp adaptors (Alice)", &mut steps, || {
-        controller_alice.update_cp_adaptors().unwrap()
-    });
-    bench::measure_step("Update cp adaptors (Bob)", &mut steps, || {
-        controller_bob.update_cp_adaptors().unwrap()
-    });
+    timer.measure("Update cp adaptors (Alice)", || {
+        controller_alice.update_cp_adaptors()
+    })?;
+    timer.measure("Update cp adaptors (Bob)", || {
+        controller_bob.update_cp_adaptors()
+    })?;
 
     // Fund the multisig address
     let multisig = types::MultisigFundAddress::new(
@@ -246,34 +284,234 @@ fn main() {
 
     // Wait for oracle attestation and finalize
     // INFO: for now, we finalize all results, we don't do optimistic optimization.
-    bench::measure_step("Wait attestation + finalize (Alice)", &mut steps, || {
-        controller_alice.wait_attestation().unwrap();
-        let finalized_tx = controller_alice.finalize_tx();
+    let alice_valid = timer.measure("Wait attestation + finalize (Alice)", || {
+        controller_alice.wait_attestation()?;
+        let finalized_tx = controller_alice.finalize_tx()?;
         print!("Offerer: ");
-        assert!(finalized_tx_valid(&finalized_tx, &multisig));
-    });
-
-    bench::measure_step("Wait attestation + finalize (Bob)", &mut steps, || {
-        controller_bob.wait_attestation().unwrap();
-        let finalized_tx = controller_bob.finalize_tx();
+        Ok::<bool, Error>(finalized_tx_valid(&finalized_tx, &multisig))
+    })?;
+    let bob_valid = timer.measure("Wait attestation + finalize (Bob)", || {
+        controller_bob.wait_attestation()?;
+        let finalized_tx = controller_bob.finalize_tx()?;
         print!("Accepter: ");
-        assert!(finalized_tx_valid(&finalized_tx, &multisig));
-    });
+        Ok::<bool, Error>(finalized_tx_valid(&finalized_tx, &multisig))
+    })?;
+    if !alice_valid || !bob_valid {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "Finalized transaction is not valid.",
+        ));
+    }
+
+    // Without compression there is one CET per outcome, with prefix method one per digit prefix
+    let parsed_contract = controller_alice.parsed_contract();
+    let nb_outcomes = if outcome_labels.is_empty() {
+        fun::nb_outcomes(nb_digits, base)
+    } else {
+        outcome_labels.len() as u128
+    };
+    println!(
+        "Summary: {} outcomes, {} unique payouts, {} CETs",
+        nb_outcomes,
+        fun::count_unique_payouts(parsed_contract),
+        controller_alice.nb_cets()
+    );
+
+    Ok(())
+}
+
+fn run(args: &SessionArgs) -> Result<(), Error> {
+    run_session(args, &mut StepTimer::new(false))
+}
+
+fn bench(args: &SessionArgs) -> Result<(), Error> {
+    let start = Instant::now();
+    let mut timer = StepTimer::new(true);
+    run_session(args, &mut timer)?;
+    timer.print_table(start.elapsed());
+    Ok(())
+}
+
+// Reports every broken validation rule of contract. Fails if there is at least one.
+fn validate(contract_path: &str) -> Result<(), Error> {
+    let contract_input = MyParser::read_input(contract_path)?;
+    check_contract(&contract_input)?;
+    println!("Contract \"{}\" is valid.", contract_path);
+    Ok(())
+}
+
+fn check_contract(contract_input: &ContractInput) -> Result<(), Error> {
+    let errors = contract_input.validate_all();
+    if errors.is_empty() {
+        return Ok(());
+    }
+    for e in &errors {
+        println!("  - {:?}: {}", e, e);
+    }
+    Err(Error::new(
+        ErrorKind::InvalidData,
+        format!("Contract breaks {} validation rule(s)", errors.len()),
+    ))
+}
+
+// Prints contract summary: collaterals, oracle, payout intervals and number of outcomes and unique payouts.
+fn inspect(contract_path: &str) -> Result<(), Error> {
+    let contract_input = MyParser::read_input(contract_path)?;
+    check_contract(&contract_input)?;
+
+    let oracle = &contract_input.contract_info.oracle;
+    let intervals = &contract_input
+        .contract_info
+        .contract_descriptor
+        .payout_intervals;
+
+    println!("Contract: {}", contract_path);
+    println!(
+        "Collateral: offer {} sats, accept {} sats (total {} sats)",
+        contract_input.offer_collateral,
+        contract_input.accept_collateral,
+        contract_input.offer_collateral + contract_input.accept_collateral
+    );
+    println!("Fee rate: {}", contract_input.fee_rate);
+
+    // Each subset of threshold oracles needs its own CETs
+    let nb_subsets = fun::oracle_subsets(oracle.nb_oracles(), oracle.threshold()).len();
+    let descriptor = &contract_input.contract_info.contract_descriptor;
+    if descriptor.is_enumerated() {
+        println!(
+            "Oracle: event \"{}\", {} of {} oracles needed",
+            oracle.event_id,
+            oracle.threshold(),
+            oracle.nb_oracles()
+        );
+        println!(
+            "Enumerated outcomes: {}",
+            descriptor.enumerated_payouts.len()
+        );
+        for ep in &descriptor.enumerated_payouts {
+            println!("  \"{}\": {} sats", ep.outcome, ep.outcome_payout);
+        }
+        let parsed_contract = MyEnumParser::parse_contract_input(contract_input)?;
+        println!(
+            "Unique payouts: {}",
+            fun::count_unique_payouts(&parsed_contract)
+        );
+        println!("Oracle subsets: {}", nb_subsets);
+        println!("CETs: {}", parsed_contract.len() * nb_subsets);
+        return Ok(());
+    }
 
-    #[cfg(feature = "enable-benchmarks")]
-    let total_time = start.elapsed();
-    #[cfg(feature = "enable-benchmarks")]
-    bench::print_table(&steps, total_time);
+    let allowed_difference = oracle
+        .allowed_difference()
+        .map_or("none".to_string(), |difference| {
+            format!(
+                "{} (max error {})",
+                difference.min_support(oracle.base()),
+                difference.max_error(oracle.base())
+            )
+        });
+    println!(
+        "Oracle: event \"{}\", {} digits in base {}, {} of {} oracles needed, allowed difference {}",
+        oracle.event_id,
+        oracle.nb_digits,
+        oracle.base(),
+        oracle.threshold(),
+        oracle.nb_oracles(),
+        allowed_difference
+    );
+    println!("Intervals: {}", intervals.len());
+    for (i, interval) in intervals.iter().enumerate() {
+        let start = interval.payout_points.first().unwrap();
+        let end = interval.payout_points.last().unwrap();
+        let shape = if interval.hyperbola.is_some() {
+            "hyperbola".to_string()
+        } else if interval.payout_points.len() > 2 {
+            format!("polynomial of degree {}", interval.payout_points.len() - 1)
+        } else if start.outcome_payout == end.outcome_payout {
+            "constant".to_string()
+        } else {
+            "linear".to_string()
+        };
+        println!(
+            "  #{} [{}, {}]: {} -> {} sats ({})",
+            i + 1,
+            start.event_outcome,
+            end.event_outcome,
+            start.outcome_payout,
+            end.outcome_payout,
+            shape
+        );
+    }
+
+    let rounding_intervals = &contract_input
+        .contract_info
+        .contract_descriptor
+        .rounding_intervals;
+    if !rounding_intervals.is_empty() {
+        println!("Rounding intervals: {}", rounding_intervals.len());
+        for ri in rounding_intervals {
+            println!(
+                "  from {}: payouts rounded to {} sats",
+                ri.begin_interval, ri.rounding_mod
+            );
+        }
+    }
+
+    let (nb_digits, base) = (oracle.nb_digits, oracle.base());
+    let (threshold, difference) = (oracle.threshold(), oracle.allowed_difference());
+    let nb_outcomes = fun::nb_outcomes(nb_digits, base);
+    // Sparse contract, so that contracts with more than 32 digits can be inspected too
+    let parsed_contract = MySparseParser::parse_contract_input(contract_input)?;
+    println!("Outcomes: {}", nb_outcomes);
+    println!(
+        "Unique payouts: {}",
+        fun::count_unique_payouts(&parsed_contract)
+    );
+    println!("Oracle subsets: {}", nb_subsets);
+    let compressed_contract =
+        digit_decomposition::compress_contract(&parsed_contract, nb_digits, base);
+    let oracle_prefixes = digit_decomposition::cover_oracle_outcomes(
+        &compressed_contract,
+        nb_digits,
+        base,
+        threshold,
+        difference,
+    );
+    println!(
+        "CETs with prefix compression: {}",
+        oracle_prefixes.len() * nb_subsets
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match &cli.command {
+        Command::Run(args) => run(args),
+        Command::Validate { contract } => validate(contract),
+        Command::Inspect { contract } => inspect(contract),
+        Command::Bench(args) => bench(args),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{
+    use super::{Cli, Command, MethodKind, SchemeKind};
+    use clap::{CommandFactory, Parser};
+    use dlc_fast_prototyping::{
         adaptor_signature_scheme::AdaptorSignatureScheme,
-        adaptor_signature_scheme::EcdsaAdaptorSignatureScheme,
+        adaptor_signature_scheme::EcdsaAdaptorSignatureScheme, config::MyCryptoUtils,
     };
 
-    use super::*;
     use rand::thread_rng;
     use secp256k1_zkp::{Keypair, Message, Secp256k1};
     use sha2::{Digest, Sha256};
@@ -295,8 +533,8 @@ mod tests {
 
     #[test]
     fn test_ecdsa_adaptor_sign() {
-        use common::types::OutcomeU32;
-        use crypto_utils::CryptoUtils;
+        use dlc_fast_prototyping::common::types::OutcomeU32;
+        use dlc_fast_prototyping::crypto_utils::{combine_attestations, CryptoUtils};
 
         let secp = Secp256k1::new();
         let mut rng = thread_rng();
@@ -307,7 +545,7 @@ mod tests {
         let (nonce_sk, nonce_pk) = secp.generate_keypair(&mut rng);
 
         // Create MyCryptoUtils engine
-        let crypto_utils_engine = MyCryptoUtils::new(&keypair.public_key(), &nonce_pk);
+        let crypto_utils_engine = MyCryptoUtils::new(&keypair.public_key(), &[nonce_pk], 20, 2);
 
         // Create message
         let message_str = "Adaptor signature test";
@@ -325,24 +563,27 @@ mod tests {
 
         // Create adaptor signature and verify pre-adaptation
         let adaptor_sig =
-            EcdsaAdaptorSignatureScheme::pre_sign(&keypair, &msg, &anticipation_point);
+            EcdsaAdaptorSignatureScheme::pre_sign(&keypair, &msg, &anticipation_point, &mut rng);
         assert!(
             EcdsaAdaptorSignatureScheme::pre_verify(
                 &keypair.public_key(),
                 &msg,
                 &anticipation_point,
                 &adaptor_sig
-            ),
+            )
+            .is_ok(),
             "Pre-verification failed"
         );
 
         // Compute attestation using MyCryptoUtils (using nonce_sk as private nonce)
-        let attestation = crypto_utils_engine
-            .compute_attestation(&keypair.secret_key(), &nonce_sk, &outcome)
+        let attestations = crypto_utils_engine
+            .compute_attestations(&keypair.secret_key(), &[nonce_sk], &outcome)
             .expect("Failed to compute attestation");
+        let attestation = combine_attestations(&attestations).unwrap();
 
         // Adapt the adaptor signature using computed attestation and verify signature
-        let adapted_sig = EcdsaAdaptorSignatureScheme::adapt(&adaptor_sig, &attestation);
+        let adapted_sig = EcdsaAdaptorSignatureScheme::adapt(&adaptor_sig, &attestation)
+            .expect("Failed to adapt signature");
         assert!(
             secp.verify_ecdsa(&msg, &adapted_sig, &keypair.public_key())
                 .is_ok(),
@@ -351,4 +592,24 @@ mod tests {
 
         println!("ECDSA adaptor signature test passed.");
     }
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_cli_session_args() {
+        let cli = Cli::parse_from(["dlc", "bench", "contract.json", "--scheme", "schnorr"]);
+        match cli.command {
+            Command::Bench(args) => {
+                assert_eq!(args.contract, "contract.json");
+                assert_eq!(args.scheme, SchemeKind::Schnorr);
+                assert_eq!(args.method, MethodKind::Simple);
+            }
+            _ => panic!("Expected bench subcommand"),
+        }
+        assert!(Cli::try_parse_from(["dlc", "run", "contract.json", "--method", "fancy"]).is_err());
+        assert!(Cli::try_parse_from(["dlc", "validate"]).is_err());
+    }
 }
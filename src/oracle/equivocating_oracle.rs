@@ -0,0 +1,72 @@
+use crate::common::{NumericOutcome, OutcomeU32};
+use crate::config::DEFAULT_BASE;
+use crate::crypto_utils::CryptoUtils;
+use secp256k1_zkp::PublicKey;
+
+use super::{Oracle, OracleAnnouncement, OracleAttestation, RandIntOracle};
+
+// INFO: Dishonest oracle for testing fraud proofs (see `prove_equivocation`). It announces one event, but attests
+// to two different outcomes of it with the same nonces. Through `Oracle` it behaves as `RandIntOracle` attesting
+// to the first outcome, attestation of the conflicting outcome is published by `get_conflicting_attestation`.
+
+pub struct EquivocatingOracle<CU: CryptoUtils, Out: NumericOutcome = OutcomeU32> {
+    oracle: RandIntOracle<CU, Out>,
+    conflicting_outcome: Out,
+}
+
+impl<CU: CryptoUtils, Out: NumericOutcome> EquivocatingOracle<CU, Out> {
+    /// Oracle attesting to both outcomes with `nb_digits` binary digits.
+    pub fn with_outcomes(nb_digits: u8, outcome: u64, conflicting_outcome: u64) -> Self {
+        Self::with_outcomes_in_base(nb_digits, DEFAULT_BASE, outcome, conflicting_outcome)
+    }
+
+    /// Oracle attesting to both outcomes with `nb_digits` digits in given base.
+    pub fn with_outcomes_in_base(
+        nb_digits: u8,
+        base: u8,
+        outcome: u64,
+        conflicting_outcome: u64,
+    ) -> Self {
+        Self {
+            oracle: RandIntOracle::with_outcome_in_base(nb_digits, base, outcome),
+            conflicting_outcome: Out::from_u64(conflicting_outcome, nb_digits),
+        }
+    }
+
+    /// Oracle of enumerated event attesting to labels at both indices.
+    pub fn enumerated_with_outcomes(
+        labels: Vec<String>,
+        index: u32,
+        conflicting_index: u32,
+    ) -> Self {
+        debug_assert!(
+            (conflicting_index as usize) < labels.len(),
+            "Outcome index must point to one of the labels"
+        );
+        Self {
+            oracle: RandIntOracle::enumerated_with_outcome(labels, index),
+            conflicting_outcome: Out::from_u64(conflicting_index as u64, 0),
+        }
+    }
+
+    /// Attestation of the other outcome, under the same nonces as `get_event_attestation`.
+    pub fn get_conflicting_attestation(&self, _event_id: u32) -> OracleAttestation<Out> {
+        self.oracle.attest(&self.conflicting_outcome)
+    }
+}
+
+impl<CU: CryptoUtils, Out: NumericOutcome> Oracle for EquivocatingOracle<CU, Out> {
+    type Outcome = Out;
+
+    fn get_public_key(&self) -> PublicKey {
+        self.oracle.get_public_key()
+    }
+
+    fn get_event_announcement(&self, event_id: u32) -> OracleAnnouncement {
+        self.oracle.get_event_announcement(event_id)
+    }
+
+    fn get_event_attestation(&self, event_id: u32) -> OracleAttestation<Out> {
+        self.oracle.get_event_attestation(event_id)
+    }
+}
@@ -0,0 +1,239 @@
+use crate::common::{error::EquivocationError, types, NumericOutcome, Outcome, OutcomeEnum};
+use crate::crypto_utils::{combine_attestations, CryptoUtils};
+use secp256k1_zkp::{PublicKey, SecretKey, SECP256K1};
+
+use super::{OracleAnnouncement, OracleAttestation};
+
+// INFO: Oracle attestation is a Schnorr signature s = k + H(P, R, m) * x of outcome m under announced nonce R = kG.
+// Attestations of two different outcomes under the same nonce are two equations with unknowns k and x, so anyone
+// can solve them for oracle's private key x. Published private key is undeniable proof that oracle equivocated.
+// This holds when each attestation is signed under a single nonce (simple and digit methods); the basis method
+// publishes only sum of digit attestations, which doesn't reveal the key.
+
+/// Proof that oracle attested to two different outcomes of the same event, i.e. its private key.
+pub struct FraudProof {
+    pub public_key: PublicKey,
+    pub private_key: SecretKey,
+    pub outcomes: (u64, u64),
+}
+
+impl FraudProof {
+    /// Checks that private key of the proof belongs to oracle's public key.
+    pub fn is_valid(&self) -> bool {
+        self.private_key.public_key(SECP256K1) == self.public_key
+    }
+}
+
+/// Verifies both attestations against oracle's announcement and recovers oracle's private key from them.
+/// Crypto utils must be the same as the oracle attests with. Enumerated outcomes are given as label indices.
+pub fn prove_equivocation<CU: CryptoUtils, Out: NumericOutcome>(
+    announcement: &OracleAnnouncement,
+    attestation_a: &OracleAttestation<Out>,
+    attestation_b: &OracleAttestation<Out>,
+) -> Result<FraudProof, EquivocationError> {
+    let (outcome_a, outcome_b) = (
+        attestation_a.outcome.to_u64(),
+        attestation_b.outcome.to_u64(),
+    );
+    if outcome_a == outcome_b {
+        return Err(EquivocationError::SameOutcome { outcome: outcome_a });
+    }
+    let engine = CU::new(
+        &announcement.public_key,
+        &announcement.public_nonces,
+        announcement.nb_digits,
+        announcement.base,
+    );
+
+    // Enumerated outcomes are attested by their labels
+    let private_key = if announcement.outcomes.is_empty() {
+        recover_private_key(
+            &engine,
+            &announcement.public_key,
+            [
+                (
+                    &attestation_a.attestations,
+                    &attestation_a.outcome,
+                    outcome_a,
+                ),
+                (
+                    &attestation_b.attestations,
+                    &attestation_b.outcome,
+                    outcome_b,
+                ),
+            ],
+        )?
+    } else {
+        let label_outcome = |index: u64| {
+            announcement
+                .outcomes
+                .get(index as usize)
+                .map(|label| OutcomeEnum::new(index as u32, label.clone()))
+                .ok_or(EquivocationError::InvalidAttestation { outcome: index })
+        };
+        recover_private_key(
+            &engine,
+            &announcement.public_key,
+            [
+                (
+                    &attestation_a.attestations,
+                    &label_outcome(outcome_a)?,
+                    outcome_a,
+                ),
+                (
+                    &attestation_b.attestations,
+                    &label_outcome(outcome_b)?,
+                    outcome_b,
+                ),
+            ],
+        )?
+    };
+
+    Ok(FraudProof {
+        public_key: announcement.public_key,
+        private_key,
+        outcomes: (outcome_a, outcome_b),
+    })
+}
+
+// Checks that attestations unlock anticipation points of their outcomes, then solves them for private key
+fn recover_private_key<CU: CryptoUtils, O: Outcome>(
+    engine: &CU,
+    public_key: &PublicKey,
+    attested: [(&Vec<types::Attestation>, &O, u64); 2],
+) -> Result<SecretKey, EquivocationError> {
+    for (attestations, outcome, value) in attested {
+        let unlocks = combine_attestations(attestations)
+            .ok()
+            .zip(engine.compute_anticipation_point(outcome).ok())
+            .is_some_and(|(secret, point)| secret.public_key(SECP256K1) == point);
+        if !unlocks {
+            return Err(EquivocationError::InvalidAttestation { outcome: value });
+        }
+    }
+    let [(attestations_a, outcome_a, _), (attestations_b, outcome_b, _)] = attested;
+    engine
+        .extract_private_key((attestations_a, outcome_a), (attestations_b, outcome_b))
+        .filter(|private_key| private_key.public_key(SECP256K1) == *public_key)
+        .ok_or(EquivocationError::KeyNotRevealed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::OutcomeU32;
+    use crate::crypto_utils::{
+        basis_crypto_utils::BasisCryptoUtils, digit_crypto_utils::DigitCryptoUtils,
+        dlcspecs_crypto_utils::DlcSpecsCryptoUtils, simple_crypto_utils::SimpleCryptoUtils,
+    };
+    use crate::oracle::{EquivocatingOracle, Oracle, RandIntOracle};
+
+    fn equivocation_proof<CU: CryptoUtils>(
+        oracle: &EquivocatingOracle<CU>,
+    ) -> Result<FraudProof, EquivocationError> {
+        prove_equivocation::<CU, OutcomeU32>(
+            &oracle.get_event_announcement(0),
+            &oracle.get_event_attestation(0),
+            &oracle.get_conflicting_attestation(0),
+        )
+    }
+
+    fn check_fraud_proof<CU: CryptoUtils>(oracle: &EquivocatingOracle<CU>, outcomes: (u64, u64)) {
+        let proof = equivocation_proof(oracle).unwrap();
+        assert!(proof.is_valid());
+        assert_eq!(proof.public_key, oracle.get_public_key());
+        assert_eq!(proof.outcomes, outcomes);
+    }
+
+    #[test]
+    fn test_equivocation_simple() {
+        let oracle = EquivocatingOracle::<SimpleCryptoUtils>::with_outcomes(3, 3, 5);
+        check_fraud_proof(&oracle, (3, 5));
+        // Zero outcome isn't special for the simple method
+        let oracle = EquivocatingOracle::<SimpleCryptoUtils>::with_outcomes(3, 0, 7);
+        check_fraud_proof(&oracle, (0, 7));
+    }
+
+    #[test]
+    fn test_equivocation_digit() {
+        // Outcomes differ only in the least significant digit
+        let oracle = EquivocatingOracle::<DigitCryptoUtils>::with_outcomes(8, 200, 201);
+        check_fraud_proof(&oracle, (200, 201));
+        let oracle =
+            EquivocatingOracle::<DigitCryptoUtils>::with_outcomes_in_base(4, 10, 1234, 9234);
+        check_fraud_proof(&oracle, (1234, 9234));
+    }
+
+    #[test]
+    fn test_equivocation_dlcspecs() {
+        let oracle =
+            EquivocatingOracle::<DlcSpecsCryptoUtils>::with_outcomes_in_base(4, 10, 1234, 1294);
+        check_fraud_proof(&oracle, (1234, 1294));
+        let labels: Vec<String> = ["yes", "no"]
+            .iter()
+            .map(|label| label.to_string())
+            .collect();
+        let oracle =
+            EquivocatingOracle::<DlcSpecsCryptoUtils>::enumerated_with_outcomes(labels, 1, 0);
+        check_fraud_proof(&oracle, (1, 0));
+    }
+
+    #[test]
+    fn test_equivocation_enumerated() {
+        let labels: Vec<String> = ["team_a_wins", "draw", "team_b_wins"]
+            .iter()
+            .map(|label| label.to_string())
+            .collect();
+        let oracle =
+            EquivocatingOracle::<SimpleCryptoUtils>::enumerated_with_outcomes(labels, 0, 2);
+        check_fraud_proof(&oracle, (0, 2));
+    }
+
+    #[cfg(feature = "schnorr-fun")]
+    #[test]
+    fn test_equivocation_simple_fun() {
+        use crate::crypto_utils::simple_fun_crypto_utils::SimpleFunCryptoUtils;
+
+        let oracle = EquivocatingOracle::<SimpleFunCryptoUtils>::with_outcomes(3, 3, 5);
+        check_fraud_proof(&oracle, (3, 5));
+    }
+
+    #[test]
+    fn test_equivocation_basis_not_revealed() {
+        let oracle = EquivocatingOracle::<BasisCryptoUtils>::with_outcomes(3, 3, 5);
+        assert_eq!(
+            equivocation_proof(&oracle).err(),
+            Some(EquivocationError::KeyNotRevealed)
+        );
+    }
+
+    #[test]
+    fn test_no_equivocation() {
+        // Honest oracle attests to one outcome only
+        let oracle = RandIntOracle::<SimpleCryptoUtils>::with_outcome(3, 5);
+        let attestation = oracle.get_event_attestation(0);
+        assert_eq!(
+            prove_equivocation::<SimpleCryptoUtils, _>(
+                &oracle.get_event_announcement(0),
+                &attestation,
+                &oracle.get_event_attestation(0),
+            )
+            .err(),
+            Some(EquivocationError::SameOutcome { outcome: 5 })
+        );
+
+        // Attestation claiming other outcome than it signs
+        let equivocating = EquivocatingOracle::<SimpleCryptoUtils>::with_outcomes(3, 3, 6);
+        let mut forged = equivocating.get_conflicting_attestation(0);
+        forged.attestations = attestation.attestations;
+        assert_eq!(
+            prove_equivocation::<SimpleCryptoUtils, _>(
+                &equivocating.get_event_announcement(0),
+                &equivocating.get_event_attestation(0),
+                &forged,
+            )
+            .err(),
+            Some(EquivocationError::InvalidAttestation { outcome: 6 })
+        );
+    }
+}
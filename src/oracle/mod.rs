@@ -1,25 +1,35 @@
 // src/oracle/mod.rs
 
-use crate::common::OutcomeU32;
+use crate::common::NumericOutcome;
 use secp256k1_zkp::{PublicKey, SecretKey};
 
 #[allow(dead_code)] // delete if get_public_key used
 pub trait Oracle {
+    /// Representation of attested outcomes (controllers key their storages by it).
+    type Outcome: NumericOutcome;
+
     fn get_public_key(&self) -> PublicKey;
     fn get_event_announcement(&self, event_id: u32) -> OracleAnnouncement; // event_id not in use right now, but is here for future experimentations
-    fn get_event_attestation(&self, event_id: u32) -> OracleAttestation;
+    fn get_event_attestation(&self, event_id: u32) -> OracleAttestation<Self::Outcome>;
 }
 
 pub struct OracleAnnouncement {
     pub public_key: PublicKey,
-    pub public_nonce: PublicKey, // INFO: Converted to a single public_nonce instead of a vector of public_nonces (as in `rust-dlc`), as our code currently uses only one nonce and attestation. If we later decide to implement a digit_decomposition approach, both nonce and attestation fields will be transformed into vectors, with the [0] element serving as the default for our approach.
+    pub public_nonces: Vec<PublicKey>, // As in `rust-dlc`. Number of nonces depends on attestation method (`CryptoUtils::nb_nonces`), e.g. one per digit when attesting digit-by-digit
+    pub nb_digits: u8, // Number of digits of attested outcome (event descriptor in dlcspecs)
+    pub base: u8,      // Base of digits of attested outcome (event descriptor in dlcspecs)
+    pub outcomes: Vec<String>, // Outcome labels of enumerated event (enum event descriptor in dlcspecs), empty for numeric event
     pub _next_attestation_time: u32, // unix timestamp, INFO: not in use now
 }
 
-pub struct OracleAttestation {
-    pub outcome: OutcomeU32, // INFO: Oracle will always return outcome in an integer form. We call for it just once, so if we need, we can convert it for negligible perf cost
-    pub attestation: SecretKey,
+pub struct OracleAttestation<Out> {
+    pub outcome: Out, // INFO: Oracle will always return outcome in a numeric form (index of the label for enumerated event). We call for it just once, so if we need, we can convert it for negligible perf cost
+    pub attestations: Vec<SecretKey>, // Either single attestation of whole outcome or one per digit (most significant first), sum of them unlocks outcome's anticipation point
 }
 
+mod equivocating_oracle;
+mod fraud_proof;
 mod rand_int_oracle;
+pub use equivocating_oracle::EquivocatingOracle;
+pub use fraud_proof::{prove_equivocation, FraudProof};
 pub use rand_int_oracle::RandIntOracle;
@@ -1,64 +1,124 @@
+use crate::common::{fun, seed::Seed, NumericOutcome, OutcomeEnum, OutcomeU32};
+use crate::config::DEFAULT_BASE;
 use crate::crypto_utils::CryptoUtils;
-use crate::{
-    common::{types, Outcome, OutcomeU32},
-    config::NB_OUTCOMES,
-};
 use core::marker::PhantomData;
-use secp256k1_zkp::{
-    global::SECP256K1,
-    rand::{thread_rng, Rng},
-    Keypair, PublicKey,
-};
+use secp256k1_zkp::{global::SECP256K1, rand::Rng, Keypair, PublicKey, SecretKey};
 
 use super::{Oracle, OracleAnnouncement, OracleAttestation};
 
-pub struct RandIntOracle<CU: CryptoUtils> {
-    nonces: Keypair,
+pub struct RandIntOracle<CU: CryptoUtils, Out: NumericOutcome = OutcomeU32> {
+    nonces: Vec<Keypair>,
     keys: Keypair,
-    outcome: types::OutcomeU32,
+    outcome: Out,
+    nb_digits: u8,
+    base: u8,
+    labels: Vec<String>, // Outcomes of enumerated event (empty for numeric event)
     _phantom: PhantomData<CU>,
     crypto_utils_engine: CU,
 }
 
-// INFO: This oracle will always return and attest to a random integer (representing outcome) in the range [0, NB_OUTCOMES)
+// INFO: This oracle will always return and attest to a random integer (representing outcome) in the range [0, base^nb_digits)
 // so we synchronize and seamlessly work with benchmarking environment locally.
-// Doing it like this we can change contracts and their respective maximum outcomes and oracle will change accordingly,
-// so that it attests in correct interval.
+// Oracle is created for given number of digits and their base (taken from contract we want to run), so that it attests
+// in correct interval. Digits are binary, unless created with `new_in_base` or `with_outcome_in_base`.
+// It announces as many nonces as its crypto utils need, e.g. with `DigitCryptoUtils` one per digit, and then attests digit-by-digit.
+// Outcome is represented (and attested) as `Out` (`OutcomeU32` by default), controllers compute anticipation points with the same representation.
 
-// In real situations, we would not have constant `NB_OUTCOMES` available at oracle side, but that wouldn't matter, as oracle
-// don't have to care and we would choose such oracle, that would fit our needs.
+// For enumerated event, oracle attests to random one of its outcome labels, and outcome is index of the label.
+// Keys, nonces and random outcome are derived from seed (random unless created with `*_seeded` constructor).
 
-impl<CU: CryptoUtils> RandIntOracle<CU> {
-    pub fn new() -> Self {
-        let nonces = Keypair::new(SECP256K1, &mut thread_rng());
-        let keys = Keypair::new(SECP256K1, &mut thread_rng());
+// In real situations, oracle would announce number of digits of its event (as in dlcspecs event descriptor) and we would
+// choose such oracle, that would fit our needs.
 
-        let mut rng = thread_rng();
-        let outcome = OutcomeU32::from(rng.gen::<u32>() % NB_OUTCOMES); // if we would remove "% NB_OUTCOMES", we wouldn't break any core functionality, but we would need to use input contracts with number of outcomes 2^32.
+impl<CU: CryptoUtils, Out: NumericOutcome> RandIntOracle<CU, Out> {
+    pub fn new(nb_digits: u8) -> Self {
+        Self::new_in_base(nb_digits, DEFAULT_BASE)
+    }
+
+    /// Oracle attesting to random outcome with `nb_digits` digits in given base.
+    pub fn new_in_base(nb_digits: u8, base: u8) -> Self {
+        Self::new_seeded(nb_digits, base, Seed::random())
+    }
+
+    /// Oracle attesting to outcome derived from seed (see `new_in_base`), so that it can be replayed.
+    pub fn new_seeded(nb_digits: u8, base: u8, seed: Seed) -> Self {
+        let outcome = seed
+            .rng("outcome")
+            .gen_range(0..=fun::max_outcome(nb_digits, base));
+        Self::with_outcome_seeded(nb_digits, base, outcome, seed)
+    }
+
+    /// Oracle attesting to given outcome, e.g. so that several oracles agree on it (multi-oracle contracts).
+    pub fn with_outcome(nb_digits: u8, outcome: u64) -> Self {
+        Self::with_outcome_in_base(nb_digits, DEFAULT_BASE, outcome)
+    }
+
+    /// Oracle attesting to given outcome with `nb_digits` digits in given base.
+    pub fn with_outcome_in_base(nb_digits: u8, base: u8, outcome: u64) -> Self {
+        Self::with_outcome_seeded(nb_digits, base, outcome, Seed::random())
+    }
 
-        let cu_engine = CU::new(&keys.public_key(), &nonces.public_key());
+    /// Oracle attesting to given outcome with keys and nonces derived from seed.
+    pub fn with_outcome_seeded(nb_digits: u8, base: u8, outcome: u64, seed: Seed) -> Self {
+        debug_assert!(
+            outcome <= fun::max_outcome(nb_digits, base),
+            "Outcome must be representable with nb_digits digits"
+        );
+        Self::from_parts(nb_digits, base, outcome, Vec::new(), seed)
+    }
+
+    /// Oracle of enumerated event attesting to random one of `labels`.
+    pub fn enumerated(labels: Vec<String>) -> Self {
+        let seed = Seed::random();
+        let index = seed.rng("outcome").gen_range(0..labels.len()) as u32;
+        Self::enumerated_with_outcome_seeded(labels, index, seed)
+    }
+
+    /// Oracle of enumerated event attesting to label at given index.
+    pub fn enumerated_with_outcome(labels: Vec<String>, index: u32) -> Self {
+        Self::enumerated_with_outcome_seeded(labels, index, Seed::random())
+    }
+
+    /// Oracle of enumerated event attesting to label at given index, with keys and nonces derived from seed.
+    pub fn enumerated_with_outcome_seeded(labels: Vec<String>, index: u32, seed: Seed) -> Self {
+        debug_assert!(
+            (index as usize) < labels.len(),
+            "Outcome index must point to one of the labels"
+        );
+        Self::from_parts(0, DEFAULT_BASE, index as u64, labels, seed)
+    }
+
+    fn from_parts(nb_digits: u8, base: u8, outcome: u64, labels: Vec<String>, seed: Seed) -> Self {
+        let mut rng = seed.rng("keys");
+        let nonces: Vec<Keypair> = (0..CU::nb_nonces(nb_digits))
+            .map(|_| Keypair::new(SECP256K1, &mut rng))
+            .collect();
+        let keys = Keypair::new(SECP256K1, &mut rng);
+        let outcome = Out::from_u64(outcome, nb_digits);
+
+        let public_nonces: Vec<PublicKey> = nonces.iter().map(|nonce| nonce.public_key()).collect();
+        let cu_engine = CU::new(&keys.public_key(), &public_nonces, nb_digits, base);
 
         Self {
             nonces,
             keys,
             outcome,
+            nb_digits,
+            base,
+            labels,
             _phantom: PhantomData,
             crypto_utils_engine: cu_engine,
         }
     }
 
-    pub fn get_outcome(&self) -> u32 {
-        self.outcome.get_value()
+    pub fn get_outcome(&self) -> u64 {
+        self.outcome.to_u64()
     }
 }
 
-impl<CU: CryptoUtils> Default for RandIntOracle<CU> {
-    fn default() -> Self {
-        Self::new()
-    }
-}
+impl<CU: CryptoUtils, Out: NumericOutcome> Oracle for RandIntOracle<CU, Out> {
+    type Outcome = Out;
 
-impl<CU: CryptoUtils> Oracle for RandIntOracle<CU> {
     fn get_public_key(&self) -> PublicKey {
         self.keys.public_key()
     }
@@ -66,23 +126,45 @@ impl<CU: CryptoUtils> Oracle for RandIntOracle<CU> {
     fn get_event_announcement(&self, _event_id: u32) -> OracleAnnouncement {
         OracleAnnouncement {
             public_key: self.keys.public_key(),
-            public_nonce: self.nonces.public_key(),
+            public_nonces: self.nonces.iter().map(|nonce| nonce.public_key()).collect(),
+            nb_digits: self.nb_digits,
+            base: self.base,
+            outcomes: self.labels.clone(),
             _next_attestation_time: 0,
         }
     }
 
-    /// Returns attestation structure with already moduled outcome with NB_OUTCOMES and attestation secret key
-    fn get_event_attestation(&self, _event_id: u32) -> OracleAttestation {
+    /// Returns attestation structure with outcome in range [0, base^nb_digits) and its attestations.
+    /// Whether outcome is attested at once or digit-by-digit depends on crypto utils of the oracle.
+    /// Enumerated outcome is attested by its label, but returned as index of the label.
+    fn get_event_attestation(&self, _event_id: u32) -> OracleAttestation<Out> {
+        self.attest(&self.outcome)
+    }
+}
+
+impl<CU: CryptoUtils, Out: NumericOutcome> RandIntOracle<CU, Out> {
+    // Attests to any outcome with the announced nonces (honest oracle does it for its outcome only)
+    pub(super) fn attest(&self, outcome: &Out) -> OracleAttestation<Out> {
+        let private_nonces: Vec<SecretKey> =
+            self.nonces.iter().map(|nonce| nonce.secret_key()).collect();
+        let attestations = if self.labels.is_empty() {
+            self.crypto_utils_engine.compute_attestations(
+                &self.keys.secret_key(),
+                &private_nonces,
+                outcome,
+            )
+        } else {
+            let index = outcome.to_u64() as u32;
+            let outcome = OutcomeEnum::new(index, self.labels[index as usize].clone());
+            self.crypto_utils_engine.compute_attestations(
+                &self.keys.secret_key(),
+                &private_nonces,
+                &outcome,
+            )
+        };
         OracleAttestation {
-            outcome: self.outcome,
-            attestation: self
-                .crypto_utils_engine
-                .compute_attestation(
-                    &self.keys.secret_key(),
-                    &self.nonces.secret_key(),
-                    &self.outcome,
-                )
-                .expect("Error computing event attestation"),
+            outcome: outcome.clone(),
+            attestations: attestations.expect("Error computing event attestation"),
         }
     }
 }
@@ -9,5 +9,8 @@ pub trait Parser<Out: types::Outcome> {
     ) -> Result<ParsedContract<Out>, std::io::Error>;
 }
 
+pub mod parser_enum;
 pub mod parser_mock;
 pub mod parser_out_u32;
+pub mod parser_sparse;
+pub mod payout_curve;
@@ -0,0 +1,90 @@
+use crate::common::{types, ContractInput, OutcomeEnum, ParsedContract};
+use crate::parser::parser_out_u32::{contract_error, SimpleOutU32Parser};
+use crate::parser::Parser;
+
+use std::io::Error;
+
+/// Parser of enumerated contracts, creating one (outcome, payout) pair per enumerated outcome.
+pub struct EnumOutcomeParser;
+
+impl Parser<types::OutcomeEnum> for EnumOutcomeParser {
+    // Contract input format is shared with numeric contracts
+    fn read_input(contract_path: &str) -> Result<ContractInput, Error> {
+        SimpleOutU32Parser::read_input(contract_path)
+    }
+
+    fn parse_contract_input(
+        contract_input: ContractInput,
+    ) -> Result<ParsedContract<OutcomeEnum>, Error> {
+        contract_input.validate().map_err(contract_error)?;
+        let descriptor = contract_input.contract_info.contract_descriptor;
+        if !descriptor.is_enumerated() {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Contract has no enumerated outcomes",
+            ));
+        }
+
+        Ok(descriptor
+            .enumerated_payouts
+            .into_iter()
+            .enumerate()
+            .map(|(i, ep)| (OutcomeEnum::new(i as u32, ep.outcome), ep.outcome_payout))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::error::ContractError;
+    use crate::common::Outcome;
+
+    #[test]
+    fn test_parse_enumerated_contract() {
+        let contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/sample_contracts/enumerated_contract_input.json"
+        ))
+        .unwrap();
+        let parsed_contract = EnumOutcomeParser::parse_contract_input(contract_input).unwrap();
+        let labels: Vec<String> = parsed_contract
+            .iter()
+            .map(|(outcome, _)| outcome.get_value())
+            .collect();
+        assert_eq!(labels, ["team_a_wins", "draw", "team_b_wins"]);
+        assert_eq!(parsed_contract[1].0.index(), 1);
+        assert_eq!(parsed_contract[1].1, 100000);
+    }
+
+    #[test]
+    fn test_invalid_enumerated_contract() {
+        let contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/test_contracts/invalid_enumerated_input.json"
+        ))
+        .unwrap();
+        assert_eq!(
+            contract_input.validate_all(),
+            vec![ContractError::InvalidEnumeratedOutcomes]
+        );
+        assert!(EnumOutcomeParser::parse_contract_input(contract_input).is_err());
+    }
+
+    #[test]
+    fn test_numeric_contract_rejected() {
+        let contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/sample_contracts/simple_contract_input.json"
+        ))
+        .unwrap();
+        assert!(EnumOutcomeParser::parse_contract_input(contract_input).is_err());
+    }
+
+    #[test]
+    fn test_label_hash_serialization() {
+        let outcome = OutcomeEnum::new(0, "draw".to_string());
+        assert_eq!(outcome.serialize().len(), 32);
+        assert_ne!(
+            outcome.serialize(),
+            OutcomeEnum::new(0, "Draw".to_string()).serialize()
+        );
+    }
+}
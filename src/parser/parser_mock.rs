@@ -1,5 +1,4 @@
-use crate::common::{types, OutcomeU32, ParsedContract};
-use crate::config::MAX_OUTCOME;
+use crate::common::{fun, types, OutcomeU32, ParsedContract};
 use crate::parser::Parser;
 
 pub struct MockU32Parser;
@@ -10,10 +9,12 @@ impl Parser<types::OutcomeU32> for MockU32Parser {
     }
 
     fn parse_contract_input(
-        _contract_input: types::ContractInput,
+        contract_input: types::ContractInput,
     ) -> Result<ParsedContract<types::OutcomeU32>, std::io::Error> {
-        Ok((0..=MAX_OUTCOME)
-            .map(|i| (OutcomeU32::from(i), i.into()))
+        let oracle_input = &contract_input.contract_info.oracle;
+        let max_outcome = fun::max_outcome(oracle_input.nb_digits, oracle_input.base());
+        Ok((0..=max_outcome)
+            .map(|i| (OutcomeU32::from(i as u32), i))
             .collect())
     }
 }
@@ -1,5 +1,7 @@
-use crate::common::{types, ContractInput, OutcomeU32, ParsedContract};
-use crate::config::NB_OUTCOMES;
+use crate::common::error::ContractError;
+use crate::common::types::{ContractDescriptor, Outcome};
+use crate::common::{fun, types, ContractInput, OutcomeU32, ParsedContract};
+use crate::parser::payout_curve::PayoutCurve;
 use crate::parser::Parser;
 
 #[cfg(feature = "parallel-parser")]
@@ -71,6 +73,77 @@ impl SimpleOutU32Parser {
             .map(|i| (OutcomeU32::from(start_outcome + i), constant_payout))
             .collect()
     }
+
+    #[cfg(not(feature = "parallel-parser"))]
+    fn create_curve_seq(
+        start_outcome: u32,
+        len: u32,
+        curve: &dyn PayoutCurve,
+        max_payout: types::PayoutT,
+    ) -> Result<Vec<(types::OutcomeU32, types::PayoutT)>, ContractError> {
+        (0..len)
+            .map(|i| {
+                let outcome = start_outcome + i;
+                curve
+                    .rounded_payout(outcome as u64, max_payout)
+                    .map(|payout| (OutcomeU32::from(outcome), payout))
+                    .ok_or(ContractError::InvalidPayout)
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "parallel-parser")]
+    fn create_curve_seq(
+        start_outcome: u32,
+        len: u32,
+        curve: &dyn PayoutCurve,
+        max_payout: types::PayoutT,
+    ) -> Result<Vec<(types::OutcomeU32, types::PayoutT)>, ContractError> {
+        (0..len)
+            .into_par_iter()
+            .map(|i| {
+                let outcome = start_outcome + i;
+                curve
+                    .rounded_payout(outcome as u64, max_payout)
+                    .map(|payout| (OutcomeU32::from(outcome), payout))
+                    .ok_or(ContractError::InvalidPayout)
+            })
+            .collect()
+    }
+
+    // Rounds payouts according to rounding intervals of contract descriptor
+    #[cfg(not(feature = "parallel-parser"))]
+    fn apply_rounding(
+        parsed_contract: &mut ParsedContract<OutcomeU32>,
+        descriptor: &ContractDescriptor,
+        max_payout: types::PayoutT,
+    ) {
+        for (outcome, payout) in parsed_contract.iter_mut() {
+            let rounding_mod = descriptor.rounding_mod(outcome.get_value() as u64);
+            *payout = fun::round_payout(*payout, rounding_mod, max_payout);
+        }
+    }
+
+    #[cfg(feature = "parallel-parser")]
+    fn apply_rounding(
+        parsed_contract: &mut ParsedContract<OutcomeU32>,
+        descriptor: &ContractDescriptor,
+        max_payout: types::PayoutT,
+    ) {
+        parsed_contract
+            .par_iter_mut()
+            .for_each(|(outcome, payout)| {
+                let rounding_mod = descriptor.rounding_mod(outcome.get_value() as u64);
+                *payout = fun::round_payout(*payout, rounding_mod, max_payout);
+            });
+    }
+}
+
+pub(crate) fn contract_error(e: ContractError) -> Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("{:?} error - {} ", e, e),
+    )
 }
 
 impl Parser<types::OutcomeU32> for SimpleOutU32Parser {
@@ -95,18 +168,33 @@ impl Parser<types::OutcomeU32> for SimpleOutU32Parser {
         contract_input: ContractInput,
     ) -> Result<ParsedContract<OutcomeU32>, Error> {
         // Call validation first
-        contract_input.validate().map_err(|e| {
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("{:?} error - {} ", e, e),
-            )
-        })?;
+        contract_input.validate().map_err(contract_error)?;
+        if contract_input
+            .contract_info
+            .contract_descriptor
+            .is_enumerated()
+        {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Enumerated contract has no numeric outcomes (use `EnumOutcomeParser`)",
+            ));
+        }
+        // Outcomes are parsed one by one, so there can't be more of them than fits into u32
+        let oracle_input = &contract_input.contract_info.oracle;
+        let nb_outcomes = fun::nb_outcomes(oracle_input.nb_digits, oracle_input.base());
+        if nb_outcomes > 1 << 32 {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Contract with more than 2^32 outcomes has too many outcomes to parse one by one (use `SparseOutU64Parser`)",
+            ));
+        }
+        let max_payout = contract_input.offer_collateral + contract_input.accept_collateral;
 
         // At this point, if we have reached here, we can safely assume that the contract is valid
 
         // Reserve capacity for final vector based on number of possible outcomes (we avoid reallocating by doing this)
         let mut parsed_contract =
-            ParsedContract::<types::OutcomeU32>::with_capacity(NB_OUTCOMES as usize);
+            ParsedContract::<types::OutcomeU32>::with_capacity(nb_outcomes as usize);
 
         // Parse contract intervals and create a vector of (outcome, payout) pairs
         for interval in &contract_input
@@ -115,12 +203,12 @@ impl Parser<types::OutcomeU32> for SimpleOutU32Parser {
             .payout_intervals
         {
             // Get start and end points of the interval
-            let start_point = &interval.payout_points[0];
-            let end_point = &interval.payout_points[1];
+            let start_point = interval.payout_points.first().unwrap();
+            let end_point = interval.payout_points.last().unwrap();
 
             // Calculate interval length and payout difference
-            let start_outcome = start_point.event_outcome;
-            let end_outcome = end_point.event_outcome;
+            let start_outcome = start_point.event_outcome as u32;
+            let end_outcome = end_point.event_outcome as u32;
             assert!(
                 end_outcome > start_outcome,
                 "end outcome must be greater than start outcome"
@@ -131,15 +219,21 @@ impl Parser<types::OutcomeU32> for SimpleOutU32Parser {
             let end_payout = end_point.outcome_payout;
             let diff = (end_payout as i64) - (start_payout as i64);
 
-            if diff == 0 {
+            if let Some(curve) = interval.curve() {
+                // Non-linear (polynomial or hyperbola) piece, evaluated outcome by outcome
+                let curve_seq =
+                    Self::create_curve_seq(start_outcome, interval_len, curve.as_ref(), max_payout)
+                        .map_err(contract_error)?;
+                parsed_contract.extend(curve_seq);
+            } else if diff == 0 {
                 let repeated =
                     Self::create_const_payout_vec(start_outcome, interval_len, start_payout); // Might also use end_payout. It doesn't matter as both are same
-                parsed_contract.extend(repeated.into_iter());
+                parsed_contract.extend(repeated);
             } else {
                 let step: f64 = (diff as f64) / (interval_len as f64);
                 let linear_seq =
                     Self::create_linear_seq(start_outcome, interval_len, start_payout, step);
-                parsed_contract.extend(linear_seq.into_iter());
+                parsed_contract.extend(linear_seq);
             }
         }
 
@@ -152,10 +246,16 @@ impl Parser<types::OutcomeU32> for SimpleOutU32Parser {
             .unwrap();
         let last_point = last_interval.payout_points.last().unwrap();
         parsed_contract.push((
-            OutcomeU32::from(last_point.event_outcome),
+            OutcomeU32::from(last_point.event_outcome as u32),
             last_point.outcome_payout,
         ));
 
+        // Round payouts, so nearly-equal payouts collapse into one (and need one CET)
+        let descriptor = &contract_input.contract_info.contract_descriptor;
+        if !descriptor.rounding_intervals.is_empty() {
+            Self::apply_rounding(&mut parsed_contract, descriptor, max_payout);
+        }
+
         Ok(parsed_contract)
     }
 }
@@ -163,6 +263,8 @@ impl Parser<types::OutcomeU32> for SimpleOutU32Parser {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::types::PayoutPoint;
+    use secp256k1_zkp::{SecretKey, SECP256K1};
 
     fn validate_parse_test_contracts(json_input: &str) {
         let contract_input: ContractInput =
@@ -233,9 +335,27 @@ mod tests {
 
     #[test]
     fn test_invalid_interval_points() {
-        validate_parse_test_contracts(include_str!(
+        let contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/test_contracts/single_interval_point_input.json"
+        ))
+        .unwrap();
+        assert_eq!(
+            contract_input.validate(),
+            Err(ContractError::InvalidIntervalPoints)
+        );
+    }
+
+    #[test]
+    fn test_three_point_interval_is_polynomial() {
+        // Three payout points used to be rejected, now they define a (here constant) polynomial piece
+        let contract_input: ContractInput = serde_json::from_str(include_str!(
             "../../input_contracts/test_contracts/invalid_interval_points_input.json"
-        ));
+        ))
+        .unwrap();
+        let parsed_contract = SimpleOutU32Parser::parse_contract_input(contract_input).unwrap();
+
+        assert_eq!(parsed_contract.len(), 32);
+        assert!(parsed_contract.iter().all(|(_, payout)| *payout == 10));
     }
 
     #[test]
@@ -251,4 +371,306 @@ mod tests {
             "../../input_contracts/test_contracts/noncontinuous_input.json"
         ));
     }
+
+    #[test]
+    fn test_nonincreasing_outcomes() {
+        validate_parse_test_contracts(include_str!(
+            "../../input_contracts/test_contracts/nonincreasing_outcomes_input.json"
+        ));
+    }
+
+    #[test]
+    fn test_parse_polynomial_contract() {
+        let contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/sample_contracts/polynomial_contract_input.json"
+        ))
+        .unwrap();
+        let parsed_contract = SimpleOutU32Parser::parse_contract_input(contract_input).unwrap();
+
+        assert_eq!(parsed_contract.len(), 1024);
+        for (i, (outcome, _)) in parsed_contract.iter().enumerate() {
+            assert_eq!(outcome.get_value(), i as u32);
+        }
+        // Polynomial piece goes exactly through all of its payout points
+        assert_eq!(parsed_contract[300].1, 0);
+        assert_eq!(parsed_contract[600].1, 30000);
+        assert_eq!(parsed_contract[1023].1, 100000);
+        // Curve is convex, so it lies under chord between payout points
+        assert!(parsed_contract[450].1 < 15000);
+    }
+
+    #[test]
+    fn test_polynomial_payout_above_collateral() {
+        let mut contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/sample_contracts/simple_contract_input.json"
+        ))
+        .unwrap();
+        // Parabola through these points peaks above total collateral (200) between points
+        contract_input
+            .contract_info
+            .contract_descriptor
+            .payout_intervals[0]
+            .payout_points = vec![
+            PayoutPoint {
+                event_outcome: 0,
+                outcome_payout: 100,
+            },
+            PayoutPoint {
+                event_outcome: 2,
+                outcome_payout: 200,
+            },
+            PayoutPoint {
+                event_outcome: 7,
+                outcome_payout: 100,
+            },
+        ];
+        assert_eq!(
+            contract_input.validate().unwrap_err(),
+            ContractError::InvalidPayout
+        );
+        assert!(SimpleOutU32Parser::parse_contract_input(contract_input).is_err());
+    }
+
+    #[test]
+    fn test_invalid_hyperbola() {
+        let contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/test_contracts/invalid_hyperbola_input.json"
+        ))
+        .unwrap();
+        assert_eq!(
+            contract_input.validate_all(),
+            vec![ContractError::InvalidHyperbolaPiece]
+        );
+    }
+
+    #[test]
+    fn test_parse_inverse_contract() {
+        let contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/sample_contracts/inverse_contract_input.json"
+        ))
+        .unwrap();
+        let parsed_contract = SimpleOutU32Parser::parse_contract_input(contract_input).unwrap();
+
+        assert_eq!(parsed_contract.len(), 1024);
+        assert_eq!(parsed_contract[50].1, 200000);
+        // payout = 20_000_000 / outcome on hyperbola piece
+        assert_eq!(parsed_contract[100].1, 200000);
+        assert_eq!(parsed_contract[200].1, 100000);
+        assert_eq!(parsed_contract[800].1, 25000);
+        assert_eq!(parsed_contract[1023].1, 19550);
+        assert!(parsed_contract[100..].windows(2).all(|w| w[0].1 >= w[1].1));
+    }
+
+    #[test]
+    fn test_invalid_rounding_intervals() {
+        validate_parse_test_contracts(include_str!(
+            "../../input_contracts/test_contracts/invalid_rounding_input.json"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_oracle_threshold() {
+        let contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/test_contracts/invalid_oracle_threshold_input.json"
+        ))
+        .unwrap();
+        assert_eq!(
+            contract_input.validate_all(),
+            vec![ContractError::InvalidOracleThreshold]
+        );
+
+        // Same oracle can't be counted twice towards threshold
+        let mut duplicate_input = contract_input.clone();
+        let first_key = duplicate_input.contract_info.oracle.public_keys[0];
+        duplicate_input.contract_info.oracle.public_keys[1] = first_key;
+        duplicate_input.contract_info.oracle.threshold = Some(2);
+        assert_eq!(
+            duplicate_input.validate(),
+            Err(ContractError::InvalidOracleThreshold)
+        );
+    }
+
+    #[test]
+    fn test_too_many_oracle_subsets() {
+        let mut contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/test_contracts/invalid_oracle_threshold_input.json"
+        ))
+        .unwrap();
+        // C(20, 10) = 184756 subsets, each of them would need its own adaptors
+        contract_input.contract_info.oracle.public_keys = (1..=20)
+            .map(|i| {
+                SecretKey::from_slice(&[i; 32])
+                    .unwrap()
+                    .public_key(SECP256K1)
+            })
+            .collect();
+        contract_input.contract_info.oracle.threshold = Some(10);
+        assert_eq!(
+            contract_input.validate(),
+            Err(ContractError::TooManyOracleSubsets)
+        );
+
+        // C(20, 2) = 190 subsets are fine
+        contract_input.contract_info.oracle.threshold = Some(2);
+        assert!(contract_input.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_allowed_difference() {
+        let contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/test_contracts/invalid_allowed_difference_input.json"
+        ))
+        .unwrap();
+        // maxErrorExp must be less than nbDigits
+        assert_eq!(
+            contract_input.validate_all(),
+            vec![ContractError::InvalidAllowedDifference]
+        );
+
+        let mut exps_input = contract_input.clone();
+        exps_input.contract_info.oracle.max_error_exp = Some(1);
+        assert_eq!(
+            exps_input.validate(),
+            Err(ContractError::InvalidAllowedDifference)
+        );
+        exps_input.contract_info.oracle.min_support_exp = None;
+        assert_eq!(
+            exps_input.validate(),
+            Err(ContractError::InvalidAllowedDifference)
+        );
+        exps_input.contract_info.oracle.max_error_exp = Some(2);
+        exps_input.contract_info.oracle.min_support_exp = Some(1);
+        assert!(exps_input.validate().is_ok());
+
+        let price_feed_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/sample_contracts/price_feed_contract_input.json"
+        ))
+        .unwrap();
+        assert!(price_feed_input.validate().is_ok());
+        let difference = price_feed_input
+            .contract_info
+            .oracle
+            .allowed_difference()
+            .unwrap();
+        assert_eq!(
+            (difference.min_support(2), difference.max_error(2)),
+            (4, 16)
+        );
+    }
+
+    #[test]
+    fn test_invalid_base() {
+        let mut contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/test_contracts/invalid_base_input.json"
+        ))
+        .unwrap();
+        assert_eq!(
+            contract_input.validate_all(),
+            vec![ContractError::InvalidBase]
+        );
+
+        contract_input.contract_info.oracle.base = Some(10);
+        assert!(contract_input.validate().is_ok());
+
+        // 10^20 outcomes don't fit into u64
+        let mut wide_input = contract_input.clone();
+        wide_input.contract_info.oracle.nb_digits = 20;
+        assert_eq!(wide_input.validate(), Err(ContractError::InvalidNbDigits));
+    }
+
+    #[test]
+    fn test_oracle_public_keys() {
+        let single_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/sample_contracts/simple_contract_input.json"
+        ))
+        .unwrap();
+        assert_eq!(single_input.contract_info.oracle.nb_oracles(), 1);
+        assert_eq!(single_input.contract_info.oracle.threshold(), 1);
+
+        let multi_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/sample_contracts/multi_oracle_contract_input.json"
+        ))
+        .unwrap();
+        assert!(multi_input.validate().is_ok());
+        assert_eq!(multi_input.contract_info.oracle.nb_oracles(), 3);
+        assert_eq!(multi_input.contract_info.oracle.threshold(), 2);
+    }
+
+    #[test]
+    fn test_parse_rounded_contract() {
+        let rounded_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/sample_contracts/rounded_contract_input.json"
+        ))
+        .unwrap();
+        let mut exact_input = rounded_input.clone();
+        exact_input
+            .contract_info
+            .contract_descriptor
+            .rounding_intervals
+            .clear();
+
+        let rounded = SimpleOutU32Parser::parse_contract_input(rounded_input).unwrap();
+        let exact = SimpleOutU32Parser::parse_contract_input(exact_input).unwrap();
+        assert_eq!(rounded.len(), exact.len());
+
+        for ((outcome, payout), (_, exact_payout)) in rounded.iter().zip(&exact) {
+            let rounding_mod = if outcome.get_value() < 85000 {
+                100_000
+            } else {
+                1_000_000
+            };
+            assert_eq!(payout % rounding_mod, 0);
+            assert!(payout.abs_diff(*exact_payout) <= rounding_mod / 2);
+        }
+        assert!(fun::count_unique_payouts(&rounded) < fun::count_unique_payouts(&exact) / 100);
+    }
+
+    #[test]
+    fn test_round_payout() {
+        assert_eq!(fun::round_payout(149, 100, 1000), 100);
+        assert_eq!(fun::round_payout(150, 100, 1000), 200);
+        assert_eq!(fun::round_payout(12, 5, 1000), 10);
+        assert_eq!(fun::round_payout(13, 5, 1000), 15);
+        assert_eq!(fun::round_payout(990, 100, 995), 995);
+        assert_eq!(fun::round_payout(7, 1, 1000), 7);
+    }
+
+    #[test]
+    fn test_validate_all_reports_every_error() {
+        let mut contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/test_contracts/nonzero_first_point_input.json"
+        ))
+        .unwrap();
+        contract_input.fee_rate = 25 * 250 + 1;
+        contract_input.contract_info.oracle.nb_digits = 4; // last outcome 31 no longer final
+        let intervals = &mut contract_input
+            .contract_info
+            .contract_descriptor
+            .payout_intervals;
+        intervals[0].payout_points[1].outcome_payout = 1000;
+        intervals[1].payout_points[0].outcome_payout = 1000;
+
+        assert_eq!(
+            contract_input.validate_all(),
+            vec![
+                ContractError::TooHighFeeRate,
+                ContractError::InvalidFirstOutcome,
+                ContractError::OutcomeRangeMismatch,
+                ContractError::InvalidPayout,
+            ]
+        );
+        assert_eq!(
+            contract_input.validate().unwrap_err(),
+            ContractError::TooHighFeeRate
+        );
+    }
+
+    #[test]
+    fn test_validate_all_valid_contract() {
+        let contract_input: ContractInput = serde_json::from_str(include_str!(
+            "../../input_contracts/sample_contracts/simple_contract_input.json"
+        ))
+        .unwrap();
+        assert!(contract_input.validate_all().is_empty());
+    }
 }
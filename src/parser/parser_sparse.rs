@@ -0,0 +1,291 @@
+use crate::common::error::ContractError;
+use crate::common::types::{ContractDescriptor, PayoutInterval};
+use crate::common::{fun, types, ContractInput, OutcomeU64, ParsedContract};
+use crate::parser::parser_out_u32::{contract_error, SimpleOutU32Parser};
+use crate::parser::Parser;
+
+use std::io::Error;
+
+// INFO: Contracts with more than 32 digits have too many outcomes to be parsed one by one (2^40 outcomes would need
+// terabytes of memory), so this parser lists only outcomes where payout changes. Payout of each listed outcome holds
+// up to the next one, which is all CET compression needs (see `digit_decomposition::compress_contract`).
+// Constant and linear pieces are monotone, so the next change of their (rounded) payout is found by binary search
+// and parsing takes time proportional to number of distinct payouts. Non-linear pieces (polynomial, hyperbola)
+// are split into ranges where they are monotone (see `PayoutCurve::monotone_ranges`) and parsed the same way.
+
+/// Parser creating sparse contract: (outcome, payout) pairs only for outcomes where payout changes, starting at 0.
+pub struct SparseOutU64Parser;
+
+impl SparseOutU64Parser {
+    // Appends outcome unless its payout is the same as payout of the previous one
+    fn push_change(
+        parsed_contract: &mut ParsedContract<OutcomeU64>,
+        outcome: u64,
+        payout: types::PayoutT,
+    ) {
+        if parsed_contract
+            .last()
+            .is_none_or(|&(_, last_payout)| last_payout != payout)
+        {
+            parsed_contract.push((OutcomeU64::from(outcome), payout));
+        }
+    }
+
+    // Appends changes of monotone payout function in [start, end], each found by binary search
+    fn push_monotone_changes(
+        parsed_contract: &mut ParsedContract<OutcomeU64>,
+        start: u64,
+        end: u64,
+        payout_of: impl Fn(u64) -> types::PayoutT,
+    ) {
+        let mut from = start;
+        loop {
+            let payout = payout_of(from);
+            Self::push_change(parsed_contract, from, payout);
+            // u128, so that range can end at the last 64-digit outcome
+            let (mut same, mut changed) = (from as u128, end as u128 + 1);
+            while changed - same > 1 {
+                let mid = same + (changed - same) / 2;
+                if payout_of(mid as u64) == payout {
+                    same = mid;
+                } else {
+                    changed = mid;
+                }
+            }
+            if changed > end as u128 {
+                return;
+            }
+            from = changed as u64;
+        }
+    }
+
+    // Appends changes of payouts of outcomes [start, end] of the interval, rounded with `rounding_mod`
+    fn push_interval_changes(
+        parsed_contract: &mut ParsedContract<OutcomeU64>,
+        interval: &PayoutInterval,
+        (start, end): (u64, u64),
+        rounding_mod: u64,
+        max_payout: types::PayoutT,
+    ) -> Result<(), ContractError> {
+        let start_point = interval.payout_points.first().unwrap();
+        let end_point = interval.payout_points.last().unwrap();
+        let round = |payout| fun::round_payout(payout, rounding_mod, max_payout);
+
+        if let Some(curve) = interval.curve() {
+            for (from, to) in curve.monotone_ranges(start, end) {
+                // Payout is defined either in whole range, or nowhere in it
+                if curve.rounded_payout(from, max_payout).is_none()
+                    || curve.rounded_payout(to, max_payout).is_none()
+                {
+                    return Err(ContractError::InvalidPayout);
+                }
+                Self::push_monotone_changes(parsed_contract, from, to, |outcome| {
+                    curve.rounded_payout(outcome, max_payout).map_or(0, round)
+                });
+            }
+        } else if start_point.outcome_payout == end_point.outcome_payout {
+            Self::push_change(parsed_contract, start, round(start_point.outcome_payout));
+        } else {
+            // Same payouts as `SimpleOutU32Parser` computes for linear piece
+            let interval_start = start_point.event_outcome;
+            let diff = (end_point.outcome_payout as i64) - (start_point.outcome_payout as i64);
+            let step = (diff as f64) / ((end_point.event_outcome - interval_start) as f64);
+            let start_payout_f = start_point.outcome_payout as f64;
+            Self::push_monotone_changes(parsed_contract, start, end, |outcome| {
+                let i = (outcome - interval_start) as f64;
+                round((start_payout_f + i * step).round() as u64)
+            });
+        }
+        Ok(())
+    }
+
+    // Splits outcomes [start, end] into ranges with the same rounding modulus
+    fn rounding_ranges(descriptor: &ContractDescriptor, start: u64, end: u64) -> Vec<(u64, u64)> {
+        let mut ranges = Vec::new();
+        let mut from = start;
+        for ri in &descriptor.rounding_intervals {
+            if ri.begin_interval > from && ri.begin_interval <= end {
+                ranges.push((from, ri.begin_interval - 1));
+                from = ri.begin_interval;
+            }
+        }
+        ranges.push((from, end));
+        ranges
+    }
+}
+
+impl Parser<types::OutcomeU64> for SparseOutU64Parser {
+    // Contract input format is shared with dense parser
+    fn read_input(contract_path: &str) -> Result<ContractInput, Error> {
+        SimpleOutU32Parser::read_input(contract_path)
+    }
+
+    fn parse_contract_input(
+        contract_input: ContractInput,
+    ) -> Result<ParsedContract<OutcomeU64>, Error> {
+        contract_input.validate().map_err(contract_error)?;
+        let descriptor = &contract_input.contract_info.contract_descriptor;
+        if descriptor.is_enumerated() {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Enumerated contract has no numeric outcomes (use `EnumOutcomeParser`)",
+            ));
+        }
+        let max_payout = contract_input.offer_collateral + contract_input.accept_collateral;
+
+        // Each interval covers its outcomes except the last one, which belongs to the next interval
+        let mut parsed_contract = ParsedContract::<OutcomeU64>::new();
+        for interval in &descriptor.payout_intervals {
+            let start = interval.payout_points.first().unwrap().event_outcome;
+            let end = interval.payout_points.last().unwrap().event_outcome - 1;
+            for (from, to) in Self::rounding_ranges(descriptor, start, end) {
+                Self::push_interval_changes(
+                    &mut parsed_contract,
+                    interval,
+                    (from, to),
+                    descriptor.rounding_mod(from),
+                    max_payout,
+                )
+                .map_err(contract_error)?;
+            }
+        }
+
+        // Last point of the last interval is the last outcome
+        let last_point = descriptor
+            .payout_intervals
+            .last()
+            .unwrap()
+            .payout_points
+            .last()
+            .unwrap();
+        let last_payout = fun::round_payout(
+            last_point.outcome_payout,
+            descriptor.rounding_mod(last_point.event_outcome),
+            max_payout,
+        );
+        Self::push_change(&mut parsed_contract, last_point.event_outcome, last_payout);
+
+        Ok(parsed_contract)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Outcome;
+    use crate::common::{digit_decomposition, OutcomeU32};
+
+    fn read_contract(path: &str) -> ContractInput {
+        SparseOutU64Parser::read_input(path).unwrap()
+    }
+
+    // Payout of each outcome is payout of the last listed outcome not greater than it
+    fn expand(
+        sparse_contract: &ParsedContract<OutcomeU64>,
+        max_outcome: u64,
+    ) -> Vec<types::PayoutT> {
+        (0..=max_outcome)
+            .map(|outcome| {
+                let idx = sparse_contract.partition_point(|(o, _)| o.get_value() <= outcome);
+                sparse_contract[idx - 1].1
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sparse_matches_dense_parser() {
+        for name in [
+            "simple_contract_input.json",
+            "rounded_contract_input.json",
+            "polynomial_contract_input.json",
+            "inverse_contract_input.json",
+            "price_feed_contract_input.json",
+            "decimal_contract_input.json",
+        ] {
+            let path = format!("./input_contracts/sample_contracts/{}", name);
+            let oracle_input = read_contract(&path).contract_info.oracle;
+            let (nb_digits, base) = (oracle_input.nb_digits, oracle_input.base());
+            let dense_contract: ParsedContract<OutcomeU32> =
+                SimpleOutU32Parser::parse_contract_input(read_contract(&path)).unwrap();
+            let sparse_contract =
+                SparseOutU64Parser::parse_contract_input(read_contract(&path)).unwrap();
+
+            assert_eq!(sparse_contract[0].0.get_value(), 0, "{}", name);
+            assert!(
+                sparse_contract.windows(2).all(|w| w[0].1 != w[1].1),
+                "{}",
+                name
+            );
+            let dense_payouts: Vec<types::PayoutT> =
+                dense_contract.iter().map(|&(_, payout)| payout).collect();
+            assert_eq!(
+                expand(&sparse_contract, fun::max_outcome(nb_digits, base)),
+                dense_payouts,
+                "{}",
+                name
+            );
+            assert_eq!(
+                digit_decomposition::compress_contract(&sparse_contract, nb_digits, base),
+                digit_decomposition::compress_contract(&dense_contract, nb_digits, base),
+                "{}",
+                name
+            );
+        }
+    }
+
+    const WIDE_CONTRACT_PATH: &str = "./input_contracts/sample_contracts/wide_contract_input.json";
+
+    #[test]
+    fn test_parse_wide_contract() {
+        let contract_input = read_contract(WIDE_CONTRACT_PATH);
+        let nb_digits = contract_input.contract_info.oracle.nb_digits;
+        assert_eq!(nb_digits, 40);
+        let sparse_contract = SparseOutU64Parser::parse_contract_input(contract_input).unwrap();
+
+        // Payout rounded to multiples of 25000 sats out of 200000 sats
+        assert_eq!(sparse_contract.len(), 9);
+        assert!(sparse_contract
+            .windows(2)
+            .all(|w| w[0].0.get_value() < w[1].0.get_value() && w[0].1 < w[1].1));
+        assert_eq!(sparse_contract.last().unwrap().1, 200000);
+        let compressed = digit_decomposition::compress_contract(&sparse_contract, nb_digits, 2);
+        assert!(compressed.len() < 9 * 2 * nb_digits as usize);
+
+        // Dense parser would need 2^40 outcomes
+        assert!(
+            SimpleOutU32Parser::parse_contract_input(read_contract(WIDE_CONTRACT_PATH)).is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_wide_hyperbola_contract() {
+        // Payout 209715200000 / x over 2^40 outcomes, rounded to multiples of 10000 sats
+        let contract_input =
+            read_contract("./input_contracts/sample_contracts/wide_inverse_contract_input.json");
+        assert!(contract_input.validate().is_ok());
+        let interval = &contract_input
+            .contract_info
+            .contract_descriptor
+            .payout_intervals[1];
+        let curve = interval.curve().unwrap();
+        let sparse_contract = SparseOutU64Parser::parse_contract_input(contract_input).unwrap();
+
+        // One entry per multiple of 10000 sats from 200000 down to 0
+        assert_eq!(sparse_contract.len(), 21);
+        assert!(sparse_contract
+            .windows(2)
+            .all(|w| w[0].0.get_value() < w[1].0.get_value() && w[0].1 > w[1].1));
+        // Each payout change is exactly where rounded payout of the hyperbola changes
+        for (outcome, payout) in &sparse_contract[1..] {
+            let rounded = |outcome| {
+                fun::round_payout(
+                    curve.rounded_payout(outcome, 200000).unwrap(),
+                    10000,
+                    200000,
+                )
+            };
+            assert_eq!(rounded(outcome.get_value()), *payout);
+            assert_ne!(rounded(outcome.get_value() - 1), *payout);
+        }
+    }
+}
@@ -0,0 +1,379 @@
+// src/parser/payout_curve.rs
+
+//! Payout curve pieces used to interpolate payouts between payout points of an interval.
+//! Based on https://github.com/discreetlogcontracts/dlcspecs/blob/master/PayoutCurve.md
+
+use crate::common::types::{HyperbolaPiece, PayoutPoint, PayoutT};
+
+/// Non-linear payout curve. It is evaluated outcome by outcome, but it is monotone between its critical outcomes,
+/// so whole ranges of outcomes can be checked and parsed from payouts at their ends (see `monotone_ranges`).
+pub trait PayoutCurve: Sync {
+    /// Evaluates (not rounded) payout of the curve at given outcome.
+    fn evaluate(&self, outcome: u64) -> f64;
+
+    /// Outcomes (not necessarily integer) where the curve may turn back (its extrema), or start or stop being defined.
+    /// Between two consecutive ones, the curve is either defined and monotone, or not defined at all.
+    /// Only outcomes in `[start, end]` are needed, others may be listed as well.
+    fn critical_outcomes(&self, start: u64, end: u64) -> Vec<f64>;
+
+    /// Splits outcomes `[start, end]` into ranges where (rounded) payout is monotone, or not defined at all,
+    /// so payouts inside each range lie between payouts at its ends. A few outcomes around each critical
+    /// outcome get range of their own, so that imprecise critical outcome doesn't break it.
+    fn monotone_ranges(&self, start: u64, end: u64) -> Vec<(u64, u64)> {
+        // Cuts are first outcomes of ranges (except the first one)
+        let mut cuts: Vec<u64> = self
+            .critical_outcomes(start, end)
+            .into_iter()
+            .filter(|critical| critical.is_finite())
+            .flat_map(|critical| (-1..=2).map(move |offset| critical.floor() + offset as f64))
+            .filter(|&cut| cut > start as f64 && cut <= end as f64)
+            .map(|cut| cut as u64)
+            .collect();
+        cuts.sort_unstable();
+        cuts.dedup();
+        let mut ranges = Vec::with_capacity(cuts.len() + 1);
+        let mut from = start;
+        for cut in cuts {
+            ranges.push((from, cut - 1));
+            from = cut;
+        }
+        ranges.push((from, end));
+        ranges
+    }
+
+    /// Payout rounded to whole satoshis, or `None` if it is not finite or out of `0..=max_payout` range.
+    fn rounded_payout(&self, outcome: u64, max_payout: PayoutT) -> Option<PayoutT> {
+        let payout = self.evaluate(outcome).round();
+        if !payout.is_finite() || payout < 0.0 || payout > max_payout as f64 {
+            return None;
+        }
+        Some(payout as PayoutT)
+    }
+}
+
+/// Polynomial payout curve piece passing through all payout points of an interval.
+/// The polynomial is evaluated in Lagrange form, so it exactly hits every payout point.
+pub struct PolynomialPiece {
+    // (outcome, payout) pairs as floats, so we don't convert them on each evaluation
+    points: Vec<(f64, f64)>,
+    // Coefficients (constant term first) of the polynomial of u = (outcome - first outcome) / width of the piece,
+    // whose derivative gives extrema of the piece
+    scaled_coefficients: Vec<f64>,
+}
+
+impl PolynomialPiece {
+    /// Creates piece from payout points. Points must have strictly increasing outcomes (checked by validation).
+    pub fn new(payout_points: &[PayoutPoint]) -> Self {
+        debug_assert!(
+            payout_points.len() >= 2,
+            "Polynomial piece needs at least 2 points"
+        );
+        let points: Vec<(f64, f64)> = payout_points
+            .iter()
+            .map(|pt| (pt.event_outcome as f64, pt.outcome_payout as f64))
+            .collect();
+        let scaled_coefficients = lagrange_coefficients(&Self::scaled_points(&points));
+        Self {
+            points,
+            scaled_coefficients,
+        }
+    }
+
+    // Points with outcomes scaled into [0, 1], so coefficients stay small even for outcomes of 2^40 and more
+    fn scaled_points(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        let (first, width) = Self::scale(points);
+        points
+            .iter()
+            .map(|&(x, y)| ((x - first) / width, y))
+            .collect()
+    }
+
+    // First outcome and width of the piece
+    fn scale(points: &[(f64, f64)]) -> (f64, f64) {
+        let first = points[0].0;
+        (first, points[points.len() - 1].0 - first)
+    }
+
+    /// Degree of the polynomial (number of points - 1).
+    pub fn degree(&self) -> usize {
+        self.points.len() - 1
+    }
+}
+
+impl PayoutCurve for PolynomialPiece {
+    fn evaluate(&self, outcome: u64) -> f64 {
+        let x = outcome as f64;
+        let mut payout = 0.0;
+        for (i, &(x_i, y_i)) in self.points.iter().enumerate() {
+            let mut basis = 1.0;
+            for (j, &(x_j, _)) in self.points.iter().enumerate() {
+                if i != j {
+                    basis *= (x - x_j) / (x_i - x_j);
+                }
+            }
+            payout += y_i * basis;
+        }
+        payout
+    }
+
+    // Roots of derivative, there are at most `degree() - 1` of them
+    fn critical_outcomes(&self, start: u64, end: u64) -> Vec<f64> {
+        let (first, width) = Self::scale(&self.points);
+        let scaled = |outcome: u64| (outcome as f64 - first) / width;
+        sign_changes(
+            &derivative(&self.scaled_coefficients),
+            scaled(start),
+            scaled(end),
+        )
+        .into_iter()
+        .map(|u| first + u * width)
+        .collect()
+    }
+}
+
+// Coefficients (constant term first) of polynomial passing through all points
+fn lagrange_coefficients(points: &[(f64, f64)]) -> Vec<f64> {
+    let mut coefficients = vec![0.0; points.len()];
+    for (i, &(x_i, y_i)) in points.iter().enumerate() {
+        // Basis polynomial of i-th point, multiplied by (x - x_j) / (x_i - x_j) for each other point
+        let mut basis = vec![1.0];
+        for (j, &(x_j, _)) in points.iter().enumerate() {
+            if i != j {
+                let mut next = vec![0.0; basis.len() + 1];
+                for (k, &coefficient) in basis.iter().enumerate() {
+                    next[k + 1] += coefficient / (x_i - x_j);
+                    next[k] -= coefficient * x_j / (x_i - x_j);
+                }
+                basis = next;
+            }
+        }
+        for (coefficient, basis_coefficient) in coefficients.iter_mut().zip(basis) {
+            *coefficient += y_i * basis_coefficient;
+        }
+    }
+    coefficients
+}
+
+fn derivative(coefficients: &[f64]) -> Vec<f64> {
+    coefficients
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(power, coefficient)| power as f64 * coefficient)
+        .collect()
+}
+
+// Horner's scheme
+fn evaluate_polynomial(coefficients: &[f64], x: f64) -> f64 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0.0, |value, coefficient| value * x + coefficient)
+}
+
+// Roots of polynomial in [lo, hi] where it changes sign (or is zero at start of its monotone part). Polynomial is
+// monotone between roots of its derivative, so each of its parts has at most one root, found by bisection.
+fn sign_changes(coefficients: &[f64], lo: f64, hi: f64) -> Vec<f64> {
+    if coefficients.len() <= 1 {
+        return Vec::new(); // Constant
+    }
+    let mut bounds = vec![lo];
+    bounds.extend(sign_changes(&derivative(coefficients), lo, hi));
+    bounds.push(hi);
+    bounds
+        .windows(2)
+        .filter_map(|w| {
+            let (mut below, mut above) = (w[0], w[1]);
+            let value_below = evaluate_polynomial(coefficients, below);
+            if value_below == 0.0 {
+                return Some(below);
+            }
+            if value_below.signum() == evaluate_polynomial(coefficients, above).signum() {
+                return None;
+            }
+            for _ in 0..100 {
+                let mid = (below + above) / 2.0;
+                if evaluate_polynomial(coefficients, mid).signum() == value_below.signum() {
+                    below = mid;
+                } else {
+                    above = mid;
+                }
+            }
+            Some(below)
+        })
+        .collect()
+}
+
+// Hyperbola piece as defined in dlcspecs: with translated outcome t = x - translateOutcome and
+// s = ±sqrt(t^2 - 4ab) (sign by usePositivePiece), payout is c(t + s)/2a + 2ad/(t + s) + translatePayout.
+// E.g. for a = 1, b = 0 and t > 0 it simplifies to c*t + d/t + translatePayout.
+impl PayoutCurve for HyperbolaPiece {
+    fn evaluate(&self, outcome: u64) -> f64 {
+        let t = outcome as f64 - self.translate_outcome;
+        let sqrt_term_abs = (t * t - 4.0 * self.a * self.b).sqrt();
+        let sqrt_term = if self.use_positive_piece {
+            sqrt_term_abs
+        } else {
+            -sqrt_term_abs
+        };
+        let first_term = self.c * (t + sqrt_term) / (2.0 * self.a);
+        let second_term = 2.0 * self.a * self.d / (t + sqrt_term);
+        first_term + second_term + self.translate_payout
+    }
+
+    // With u = t + s, payout is c*u/2a + 2ad/u + translatePayout. u is monotone in t wherever it is defined
+    // and nonzero, so payout turns back only where c/2a = 2ad/u^2, with t = (u^2 + 4ab) / 2u.
+    // Otherwise it can change only at ends of its domain t^2 = 4ab (t = 0 for b = 0, where u may vanish).
+    fn critical_outcomes(&self, _start: u64, _end: u64) -> Vec<f64> {
+        let four_ab = 4.0 * self.a * self.b;
+        let mut critical_ts = Vec::new();
+        if four_ab >= 0.0 {
+            critical_ts.extend([four_ab.sqrt(), -four_ab.sqrt()]);
+        }
+        let u_squared = 4.0 * self.a * self.a * self.d / self.c; // Infinite (and skipped) for c = 0
+        if u_squared > 0.0 && u_squared.is_finite() {
+            let u = u_squared.sqrt();
+            critical_ts.extend([u, -u].map(|u| (u * u + four_ab) / (2.0 * u)));
+        }
+        critical_ts
+            .into_iter()
+            .map(|t| t + self.translate_outcome)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(event_outcome: u64, outcome_payout: u64) -> PayoutPoint {
+        PayoutPoint {
+            event_outcome,
+            outcome_payout,
+        }
+    }
+
+    #[test]
+    fn test_polynomial_hits_payout_points() {
+        let points = [point(0, 10), point(7, 0), point(20, 300), point(31, 40)];
+        let piece = PolynomialPiece::new(&points);
+        assert_eq!(piece.degree(), 3);
+        for pt in &points {
+            assert_eq!(piece.evaluate(pt.event_outcome), pt.outcome_payout as f64);
+        }
+    }
+
+    #[test]
+    fn test_polynomial_matches_quadratic() {
+        // Points lie on payout = outcome^2 + 3
+        let piece = PolynomialPiece::new(&[point(1, 4), point(4, 19), point(10, 103)]);
+        for outcome in 0..=16u64 {
+            let expected = (outcome * outcome + 3) as f64;
+            assert!((piece.evaluate(outcome) - expected).abs() < 1e-9);
+        }
+    }
+
+    fn inverse_piece(d: f64, translate_payout: f64) -> HyperbolaPiece {
+        HyperbolaPiece {
+            use_positive_piece: true,
+            translate_outcome: 0.0,
+            translate_payout,
+            a: 1.0,
+            b: 0.0,
+            c: 0.0,
+            d,
+        }
+    }
+
+    #[test]
+    fn test_hyperbola_inverse_payout() {
+        // payout = 20_000_000 / x + 100
+        let piece = inverse_piece(20_000_000.0, 100.0);
+        assert!((piece.evaluate(100) - 200_100.0).abs() < 1e-6);
+        assert!((piece.evaluate(1000) - 20_100.0).abs() < 1e-6);
+        assert_eq!(piece.rounded_payout(1023, 1_000_000), Some(19_650));
+        // Payout above maximum
+        assert_eq!(piece.rounded_payout(10, 1_000_000), None);
+        // Division by zero at translated outcome 0
+        assert_eq!(piece.rounded_payout(0, u64::MAX), None);
+    }
+
+    #[test]
+    fn test_hyperbola_linear_and_negative_piece() {
+        // With a = 1, b = 0 and c != 0, positive piece adds linear term c*x
+        let mut piece = inverse_piece(1000.0, 0.0);
+        piece.c = 2.0;
+        assert!((piece.evaluate(10) - 120.0).abs() < 1e-9);
+        // Negative piece for t > 0 collapses to t + s = 0, so payout isn't finite
+        piece.use_positive_piece = false;
+        assert!(piece.rounded_payout(10, u64::MAX).is_none());
+    }
+
+    // Payouts inside each monotone range lie between payouts at its ends
+    fn check_monotone_ranges(curve: &dyn PayoutCurve, start: u64, end: u64) {
+        for (from, to) in curve.monotone_ranges(start, end) {
+            let (low, high) = (curve.evaluate(from), curve.evaluate(to));
+            let (low, high) = (low.min(high), low.max(high));
+            for outcome in from..=to {
+                let payout = curve.evaluate(outcome);
+                assert!(
+                    low <= payout && payout <= high,
+                    "{} in {}..={}",
+                    outcome,
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_polynomial_monotone_ranges() {
+        // Parabola with maximum at 500 and cubic with extrema around 200 and 800
+        let parabola = PolynomialPiece::new(&[point(0, 0), point(500, 1000), point(1000, 0)]);
+        let critical = parabola.critical_outcomes(0, 1000);
+        assert_eq!(critical.len(), 1);
+        assert!((critical[0] - 500.0).abs() < 1e-6);
+        check_monotone_ranges(&parabola, 0, 1000);
+        let cubic = PolynomialPiece::new(&[
+            point(0, 500),
+            point(200, 900),
+            point(800, 100),
+            point(1000, 500),
+        ]);
+        assert_eq!(cubic.critical_outcomes(0, 1000).len(), 2);
+        check_monotone_ranges(&cubic, 0, 1000);
+        check_monotone_ranges(&cubic, 300, 700);
+
+        // Extremum of 2^40-wide piece is found without evaluating every outcome
+        let wide =
+            PolynomialPiece::new(&[point(0, 0), point(1 << 39, 1000), point((1 << 40) - 1, 0)]);
+        let ranges = wide.monotone_ranges(0, (1 << 40) - 1);
+        assert!(ranges.len() <= 5);
+        assert_eq!(ranges.first().unwrap().0, 0);
+        assert_eq!(ranges.last().unwrap().1, (1 << 40) - 1);
+    }
+
+    #[test]
+    fn test_hyperbola_monotone_ranges() {
+        // 2x + 1000/x has minimum at sqrt(500), and isn't defined at 0
+        let mut piece = inverse_piece(1000.0, 0.0);
+        piece.c = 2.0;
+        check_monotone_ranges(&piece, 1, 100);
+        assert!(piece
+            .monotone_ranges(1, 100)
+            .iter()
+            .any(|&(from, to)| from <= 22 && 22 <= to && to - from <= 1));
+        assert!(piece
+            .monotone_ranges(0, 100)
+            .iter()
+            .any(|&(from, to)| (from, to) == (0, 0)));
+    }
+
+    #[test]
+    fn test_two_points_is_linear() {
+        let piece = PolynomialPiece::new(&[point(0, 25), point(7, 200)]);
+        assert_eq!(piece.degree(), 1);
+        assert!((piece.evaluate(4) - 125.0).abs() < 1e-9);
+    }
+}